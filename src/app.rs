@@ -1,20 +1,34 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use ratatui::layout::{Position, Rect};
 use ratatui::widgets::TableState;
 
-use crate::discovery::RepoRef;
-use crate::status::{parse_ahead_behind, RepoState, NO_REMOTE};
-use crate::worker::{Action, WorkerCmd};
+use crate::branches::{default_branch, BranchInfo, CheckoutPlan, PruneCandidate};
+use crate::browse::{browse_links, BrowseTarget, HostConfig};
+use crate::clipboard::copy_to_clipboard;
+use crate::columns::{
+    default_columns, group_children, status_extras, Column, ColumnPicker, ColumnSpec, SortOrder,
+};
+use crate::config::Config;
+use crate::diff::{DiffLine, DiffLineKind};
+use crate::discovery::{RepoRef, ScanTargets};
+use crate::exec::{tally, ExecCommand, ExecOptions, ExecOutcome};
+use crate::history::{LogEntry, LogMode};
+use crate::keymap::{Command, KeyChord, KeyContext, Keymap, Lookup};
+use crate::launch::{LaunchRequest, Launcher, Remote};
+use crate::manifest::{Manifest, ManifestMark, ManifestRepo};
+use crate::palette::{filter_entries, Palette, PaletteEntry};
+use crate::status::{RepoState, DETACHED_BRANCH, NO_BRANCH, NO_REMOTE};
+use crate::submodule::Submodule;
+use crate::worker::{repo_status, Action, PruneScanResult, WorkerCmd};
 
-#[derive(Clone, Copy, PartialEq)]
-pub enum SortOrder {
-    Name,
-    Status,
-    AheadBehind,
-    LastFetch,
-}
+/// Time allowed between the keys of a multi-key binding.
+const KEY_SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum StatusType {
@@ -23,8 +37,282 @@ pub enum StatusType {
     Info,
 }
 
+/// A full-screen view stacked on top of the repository table.
+/// The last pane in `App::panes` is the one rendered and receiving keys.
+pub enum Pane {
+    Log(LogView),
+    Detail(DetailView),
+    Diff(DiffView),
+    Branches(BranchView),
+    Batch(BatchView),
+    Prune(PruneView),
+    Exec(ExecView),
+}
+
+impl Pane {
+    /// Repository the pane belongs to; `None` for panes spanning several repositories.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Pane::Log(view) => Some(&view.path),
+            Pane::Detail(view) => Some(&view.path),
+            Pane::Diff(view) => Some(&view.path),
+            Pane::Branches(view) => Some(&view.path),
+            Pane::Batch(_) | Pane::Prune(_) | Pane::Exec(_) => None,
+        }
+    }
+}
+
+/// Screen regions recorded while rendering, used to hit-test mouse clicks.
+#[derive(Default)]
+pub struct HitAreas {
+    /// Repository table including its header row; empty while a pane is shown.
+    pub table: Rect,
+    /// Each shown table column with its start and width.
+    pub columns: Vec<(Column, u16, u16)>,
+    /// Footer hints and the command each one runs.
+    pub hints: Vec<(Rect, Command)>,
+    pub menu: Option<Rect>,
+}
+
+/// Popup listing keyed entries for the focused repository.
+pub struct Menu {
+    pub kind: MenuKind,
+    pub selected: usize,
+}
+
+pub enum MenuKind {
+    /// Launch actions opening the repository in another program.
+    Launch,
+    /// Web pages of the repository on its hosting service.
+    Browse(Vec<(BrowseTarget, String)>),
+    /// Values of the repository that can be copied, as `(key, label, value)`.
+    Copy(Vec<(char, &'static str, String)>),
+}
+
+/// Single-line text input shown in the footer.
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PromptKind {
+    /// Branch name to check out across the batch targets.
+    BatchCheckout,
+    /// Shell command to run in each of the batch targets.
+    Exec,
+    /// Branch to check out in a new worktree of the focused repository.
+    AddWorktree,
+}
+
+impl PromptKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            PromptKind::BatchCheckout => "Checkout branch",
+            PromptKind::Exec => "Run command",
+            PromptKind::AddWorktree => "New worktree for branch",
+        }
+    }
+}
+
+pub struct LogView {
+    pub path: PathBuf,
+    pub repo_name: String,
+    pub mode: LogMode,
+    pub entries: Vec<LogEntry>,
+    pub loading: bool,
+    pub error: Option<String>,
+    pub scroll: usize,
+}
+
+impl LogView {
+    fn scroll_to(&mut self, offset: usize) {
+        self.scroll = offset.min(self.entries.len().saturating_sub(1));
+    }
+}
+
+/// Repository summary with its changed files; the files themselves are read
+/// from `App::repos` on every frame so refreshes show up immediately.
+pub struct DetailView {
+    pub path: PathBuf,
+    pub selected: usize,
+}
+
+pub struct DiffView {
+    pub path: PathBuf,
+    pub repo_name: String,
+    /// `None` shows the diff of the whole working tree.
+    pub file: Option<String>,
+    pub lines: Vec<DiffLine>,
+    pub loading: bool,
+    pub error: Option<String>,
+    pub scroll: usize,
+}
+
+impl DiffView {
+    fn scroll_to(&mut self, offset: usize) {
+        self.scroll = offset.min(self.lines.len().saturating_sub(1));
+    }
+
+    fn hunk_offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.kind == DiffLineKind::Hunk)
+            .map(|(idx, _)| idx)
+    }
+}
+
+pub struct BranchView {
+    pub path: PathBuf,
+    pub repo_name: String,
+    pub branches: Vec<BranchInfo>,
+    pub loading: bool,
+    pub error: Option<String>,
+    pub selected: usize,
+}
+
+impl BranchView {
+    pub fn selected_branch(&self) -> Option<&BranchInfo> {
+        self.branches.get(self.selected)
+    }
+}
+
+/// Multi-repository operation: a pre-flight report followed by per-repo results.
+pub struct BatchView {
+    pub branch: String,
+    pub rows: Vec<BatchRow>,
+    pub stage: BatchStage,
+    pub scroll: usize,
+}
+
+pub struct BatchRow {
+    pub path: PathBuf,
+    pub name: String,
+    pub plan: Option<CheckoutPlan>,
+    pub result: Option<Result<String, String>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BatchStage {
+    Preflight,
+    Review,
+    Running { done: usize, total: usize },
+    Done,
+}
+
+/// Prunable branches across all repositories, grouped by repository.
+pub struct PruneView {
+    pub groups: Vec<PruneGroup>,
+    pub stage: PruneStage,
+    /// Index into `rows()`.
+    pub cursor: usize,
+}
+
+pub struct PruneGroup {
+    pub path: PathBuf,
+    pub name: String,
+    pub default_branch: String,
+    pub items: Vec<PruneItem>,
+    pub error: Option<String>,
+    pub result: Option<Result<String, String>>,
+}
+
+pub struct PruneItem {
+    pub candidate: PruneCandidate,
+    pub selected: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PruneStage {
+    Scanning,
+    Review,
+    Confirm,
+    Running { done: usize, total: usize },
+    Done,
+}
+
+impl PruneView {
+    /// Flattened display rows: a header per group followed by its branches.
+    pub fn rows(&self) -> Vec<(usize, Option<usize>)> {
+        let mut rows = Vec::new();
+        for (group_idx, group) in self.groups.iter().enumerate() {
+            rows.push((group_idx, None));
+            rows.extend((0..group.items.len()).map(|item_idx| (group_idx, Some(item_idx))));
+        }
+        rows
+    }
+
+    pub fn selected_count(&self) -> (usize, usize) {
+        let mut branches = 0;
+        let mut repos = 0;
+        for group in &self.groups {
+            let count = group.items.iter().filter(|item| item.selected).count();
+            branches += count;
+            repos += usize::from(count > 0);
+        }
+        (branches, repos)
+    }
+}
+
+/// Output of a command run across repositories, in target order.
+pub struct ExecView {
+    pub command: String,
+    pub rows: Vec<ExecRow>,
+    pub done: bool,
+    pub scroll: usize,
+    /// Set to kill the running commands and skip the remaining repositories.
+    pub cancel: Arc<AtomicBool>,
+}
+
+pub struct ExecRow {
+    pub path: PathBuf,
+    pub name: String,
+    pub outcome: Option<ExecOutcome>,
+}
+
+impl ExecRow {
+    /// Captured output lines, stdout first.
+    pub fn output_lines(&self) -> Vec<(bool, &str)> {
+        match &self.outcome {
+            Some(ExecOutcome::Finished(output)) => output
+                .stdout
+                .lines()
+                .map(|line| (false, line))
+                .chain(output.stderr.lines().map(|line| (true, line)))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl ExecView {
+    /// Line offset of each repository's header in the rendered output.
+    pub fn row_offsets(&self) -> Vec<usize> {
+        let mut offset = 0;
+        self.rows
+            .iter()
+            .map(|row| {
+                let start = offset;
+                // Header, output and a blank separator line
+                offset += row.output_lines().len() + 2;
+                start
+            })
+            .collect()
+    }
+
+    fn line_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| row.output_lines().len() + 2)
+            .sum()
+    }
+}
+
 pub struct App {
-    pub root: PathBuf,
+    pub targets: ScanTargets,
+    /// Scan roots and repositories that do not exist, reported after each scan.
+    pub missing_targets: Vec<PathBuf>,
     pub repos: Vec<RepoState>,
     pub table_state: TableState,
     pub cmd_tx: Sender<WorkerCmd>,
@@ -39,14 +327,40 @@ pub struct App {
     pub search_mode: bool,
     pub search_query: String,
     pub sort_order: SortOrder,
+    pub panes: Vec<Pane>,
+    /// Repositories marked for batch operations.
+    pub marked: HashSet<PathBuf>,
+    pub prompt: Option<Prompt>,
+    pub launchers: Vec<Launcher>,
+    pub browse_hosts: Vec<HostConfig>,
+    pub exec_options: ExecOptions,
+    pub menu: Option<Menu>,
+    /// Launch waiting for the UI loop to hand over the terminal.
+    pub pending_launch: Option<LaunchRequest>,
+    pub mouse_enabled: bool,
+    pub hit_areas: HitAreas,
+    pub keymap: Keymap,
+    /// Keys typed so far of an unfinished multi-key binding.
+    pub pending_keys: Vec<KeyChord>,
+    /// When the last of `pending_keys` was typed.
+    pub pending_since: Instant,
+    pub palette: Option<Palette>,
+    /// Repository table columns in display order.
+    pub columns: Vec<ColumnSpec>,
+    pub column_picker: Option<ColumnPicker>,
+    /// Workspace manifest; declared repositories that are missing get placeholder rows.
+    pub manifest: Option<Manifest>,
+    /// Clones finished and started while a batch clone runs.
+    pub clone_progress: Option<(usize, usize)>,
 }
 
 impl App {
-    pub fn new(root: PathBuf, cmd_tx: Sender<WorkerCmd>) -> Self {
+    pub fn new(targets: ScanTargets, cmd_tx: Sender<WorkerCmd>, config: &Config) -> Self {
         let mut table_state = TableState::default();
         table_state.select(Some(0));
         Self {
-            root,
+            targets,
+            missing_targets: Vec::new(),
             repos: Vec::new(),
             table_state,
             cmd_tx,
@@ -60,65 +374,1287 @@ impl App {
             help_visible: false,
             search_mode: false,
             search_query: String::new(),
-            sort_order: SortOrder::Name,
+            sort_order: SortOrder::default(),
+            panes: Vec::new(),
+            marked: HashSet::new(),
+            prompt: None,
+            launchers: config.launchers(),
+            browse_hosts: config.browse.hosts.clone(),
+            exec_options: config.exec.clone(),
+            menu: None,
+            pending_launch: None,
+            mouse_enabled: config.mouse,
+            hit_areas: HitAreas::default(),
+            keymap: config.keymap().unwrap_or_default(),
+            pending_keys: Vec::new(),
+            pending_since: Instant::now(),
+            palette: None,
+            columns: config.columns().unwrap_or_else(|_| default_columns()),
+            column_picker: None,
+            manifest: None,
+            clone_progress: None,
+        }
+    }
+
+    /// Scan the roots and listed repositories. With `known` repositories
+    /// from an earlier run they are shown first and discovery continues in
+    /// the background.
+    pub fn request_scan(&mut self, known: Vec<RepoRef>) {
+        self.loading = true;
+        self.scan_progress = 0.0;
+        if let Err(err) = self.cmd_tx.send(WorkerCmd::Scan {
+            targets: self.targets.clone(),
+            known,
+        }) {
+            self.loading = false;
+            self.set_status(format!("Worker unavailable: {err}"));
+        }
+    }
+
+    /// Re-read every repository; `skip_cache` also reads those whose git
+    /// directory and working tree look unchanged.
+    pub fn request_refresh(&mut self, skip_cache: bool) {
+        let repos = self
+            .repos
+            .iter()
+            .filter(|repo| !repo.is_placeholder())
+            .map(RepoState::repo_ref)
+            .collect();
+        if let Err(err) = self.cmd_tx.send(WorkerCmd::Refresh { repos, skip_cache }) {
+            self.set_status(format!("Worker unavailable: {err}"));
+        }
+    }
+
+    pub fn request_confirm(&mut self, mut action: Action) {
+        if self.repos.is_empty() {
+            self.set_status("No repositories selected".to_string());
+            return;
+        }
+        if self.focused_placeholder() {
+            return;
+        }
+
+        if let Some(repo) = self.focused_repo() {
+            if repo.is_bare() {
+                if action.needs_worktree() {
+                    self.set_status(format!("{} needs a working tree", action.label()));
+                    return;
+                }
+                // A bare repository has no remote-tracking branches to fetch into
+                if matches!(action, Action::Fetch) {
+                    action = Action::UpdateMirror;
+                }
+            } else if matches!(action, Action::UpdateMirror) {
+                self.set_status("Update mirror needs a bare repository".to_string());
+                return;
+            }
+            // Validate that we have a remote before allowing push/pull
+            if action.needs_remote() && repo.remote_url == NO_REMOTE {
+                self.set_status("No remote configured for this repository".to_string());
+                return;
+            }
+        }
+
+        self.confirmation = Some(action);
+    }
+
+    pub fn perform_action(&mut self, action: Action) {
+        if let Some(repo) = self.focused_repo() {
+            let path = match (&action, &repo.worktree_of) {
+                // New worktrees go next to the main one, not inside a linked one
+                (Action::AddWorktree(_), Some(main)) => main.clone(),
+                _ => repo.path.clone(),
+            };
+            if let Err(err) = self.cmd_tx.send(WorkerCmd::Action { path, action }) {
+                self.set_status(format!("Worker unavailable: {err}"));
+            } else {
+                self.set_status("Running action...".to_string());
+            }
+        }
+    }
+
+    /// Whether the focused row is a repository that is not cloned yet,
+    /// reporting it in the status line.
+    fn focused_placeholder(&mut self) -> bool {
+        let Some(name) = self
+            .focused_repo()
+            .filter(|repo| repo.is_placeholder())
+            .map(|repo| repo.name.clone())
+        else {
+            return false;
+        };
+        self.set_status(format!("{name} is not cloned yet"));
+        true
+    }
+
+    /// Repository the current pane belongs to, or the table selection when no pane is open.
+    pub fn focused_repo(&self) -> Option<&RepoState> {
+        match self.panes.last() {
+            Some(pane) => {
+                let path = pane.path()?;
+                self.repos.iter().find(|repo| repo.path == path)
+            }
+            None => self.selected_repo(),
+        }
+    }
+
+    /// Key binding context of the table or the pane on top.
+    pub fn key_context(&self) -> KeyContext {
+        match self.panes.last() {
+            None => KeyContext::Table,
+            Some(Pane::Log(_)) => KeyContext::Log,
+            Some(Pane::Detail(_)) => KeyContext::Detail,
+            Some(Pane::Diff(_)) => KeyContext::Diff,
+            Some(Pane::Branches(_)) => KeyContext::Branches,
+            Some(Pane::Batch(_)) => KeyContext::Batch,
+            Some(Pane::Prune(_)) => KeyContext::Prune,
+            Some(Pane::Exec(_)) => KeyContext::Exec,
+        }
+    }
+
+    /// Look up `key` after the keys typed so far in `context`. A key that
+    /// does not continue the sequence is looked up again on its own.
+    pub fn lookup_key(&mut self, context: KeyContext, key: KeyChord) -> Lookup {
+        self.expire_pending_keys();
+        self.pending_keys.push(key);
+        self.pending_since = Instant::now();
+        let mut lookup = self.keymap.lookup(context, &self.pending_keys);
+        if lookup == Lookup::None && self.pending_keys.len() > 1 {
+            self.pending_keys = vec![key];
+            lookup = self.keymap.lookup(context, &self.pending_keys);
+        }
+        if lookup != Lookup::Prefix {
+            self.pending_keys.clear();
+        }
+        lookup
+    }
+
+    /// Drop an unfinished key sequence once its next key is overdue.
+    pub fn expire_pending_keys(&mut self) {
+        if self.pending_since.elapsed() >= KEY_SEQUENCE_TIMEOUT {
+            self.pending_keys.clear();
+        }
+    }
+
+    pub fn open_palette(&mut self) {
+        self.palette = Some(Palette {
+            query: String::new(),
+            selected: 0,
+        });
+    }
+
+    /// Commands that make sense for the current view, best match for the
+    /// palette query first.
+    pub fn palette_entries(&self) -> Vec<PaletteEntry> {
+        let Some(palette) = &self.palette else {
+            return Vec::new();
+        };
+        let context = self.key_context();
+        let repo = self.focused_repo().map(|repo| repo.name.clone());
+        let bare = self.focused_repo().is_some_and(RepoState::is_bare);
+        let mut commands = match self.panes.last() {
+            None => vec![
+                Command::Details,
+                Command::Log,
+                Command::Branches,
+                Command::Fetch,
+                Command::Pull,
+                Command::Push,
+                Command::Open,
+                Command::Browse,
+                Command::Copy,
+                Command::Mark,
+                Command::MarkAll,
+                Command::BatchCheckout,
+                Command::Exec,
+                Command::Prune,
+                Command::CloneMissing,
+                Command::AddWorktree,
+                Command::PruneWorktrees,
+                Command::Refresh,
+                Command::CycleSort,
+                Command::ReverseSort,
+                Command::Columns,
+                Command::Search,
+            ],
+            Some(pane) => {
+                let mut commands = match pane {
+                    Pane::Log(_) => vec![Command::CycleLogMode],
+                    Pane::Detail(_) => vec![
+                        Command::DiffFile,
+                        Command::DiffAll,
+                        Command::Log,
+                        Command::Branches,
+                    ],
+                    Pane::Exec(view) if !view.done => vec![
+                        Command::CancelExec,
+                        Command::NextSection,
+                        Command::PrevSection,
+                    ],
+                    Pane::Diff(_) | Pane::Exec(_) => {
+                        vec![Command::NextSection, Command::PrevSection]
+                    }
+                    Pane::Branches(_) => vec![Command::Checkout, Command::DeleteBranch],
+                    Pane::Batch(view) if view.stage == BatchStage::Review => {
+                        vec![Command::CheckoutPresent, Command::CheckoutCreate]
+                    }
+                    Pane::Batch(_) => Vec::new(),
+                    Pane::Prune(_) => vec![
+                        Command::ToggleSelection,
+                        Command::ToggleAll,
+                        Command::DeleteSelected,
+                    ],
+                };
+                if repo.is_some() {
+                    commands.extend([
+                        Command::Fetch,
+                        Command::Pull,
+                        Command::Push,
+                        Command::Open,
+                        Command::Browse,
+                        Command::Copy,
+                    ]);
+                }
+                commands.push(Command::Close);
+                commands
+            }
+        };
+        if self.manifest.is_none() {
+            commands.retain(|command| *command != Command::CloneMissing);
+        }
+        if bare {
+            // Bare repositories are updated as mirrors and have nothing to pull into
+            commands.retain(|command| *command != Command::Pull);
+            for command in commands.iter_mut() {
+                if *command == Command::Fetch {
+                    *command = Command::UpdateMirror;
+                }
+            }
+        }
+        if self.panes.is_empty() && !self.search_query.is_empty() {
+            commands.push(Command::ClearSearch);
+        }
+        commands.extend([Command::ToggleMouse, Command::Help, Command::Quit]);
+
+        let batch_scope = if self.marked.is_empty() {
+            format!("{} filtered repos", self.filtered_indices().len())
+        } else {
+            format!("{} marked repos", self.marked.len())
+        };
+        let entries = commands
+            .into_iter()
+            .map(|command| {
+                let scope = match command {
+                    Command::Details
+                    | Command::Log
+                    | Command::Branches
+                    | Command::Fetch
+                    | Command::Pull
+                    | Command::Push
+                    | Command::Open
+                    | Command::Browse
+                    | Command::Copy
+                    | Command::AddWorktree
+                    | Command::PruneWorktrees
+                    | Command::UpdateMirror
+                    | Command::Mark => repo.clone().unwrap_or_default(),
+                    Command::BatchCheckout | Command::Exec | Command::CloneMissing => {
+                        batch_scope.clone()
+                    }
+                    Command::MarkAll => format!("{} filtered repos", self.filtered_indices().len()),
+                    Command::Prune | Command::Refresh => "all repos".to_string(),
+                    _ => String::new(),
+                };
+                PaletteEntry {
+                    command,
+                    scope,
+                    keys: self.keymap.keys_for(context, command).join(" / "),
+                }
+            })
+            .collect();
+        filter_entries(entries, &palette.query)
+    }
+
+    pub fn move_palette_selection(&mut self, delta: isize) {
+        let last = self.palette_entries().len().saturating_sub(1);
+        if let Some(palette) = self.palette.as_mut() {
+            palette.selected = palette.selected.saturating_add_signed(delta).min(last);
+        }
+    }
+
+    pub fn palette_push_char(&mut self, c: char) {
+        if let Some(palette) = self.palette.as_mut() {
+            palette.query.push(c);
+            palette.selected = 0;
+        }
+    }
+
+    pub fn palette_pop_char(&mut self) {
+        if let Some(palette) = self.palette.as_mut() {
+            palette.query.pop();
+            palette.selected = 0;
+        }
+    }
+
+    /// Close the palette, returning the highlighted command.
+    pub fn take_palette_command(&mut self) -> Option<Command> {
+        let selected = self.palette.as_ref()?.selected;
+        let command = self
+            .palette_entries()
+            .get(selected)
+            .map(|entry| entry.command);
+        self.palette = None;
+        command
+    }
+
+    /// Re-read the status of one repository after it may have changed.
+    pub fn refresh_repo(&mut self, path: &Path) {
+        if let Some(repo) = self.repos.iter_mut().find(|repo| repo.path == path) {
+            let extras = status_extras(&self.columns);
+            if let Ok(mut status) = repo_status(&repo.repo_ref(), extras) {
+                status.submodule = repo
+                    .submodule
+                    .as_ref()
+                    .map(|submodule| Submodule::read(&submodule.superproject, path));
+                status.worktree_of = repo.worktree_of.take();
+                status.manifest = repo.manifest.take();
+                *repo = status;
+            }
+        }
+    }
+
+    pub fn open_launch_menu(&mut self) {
+        if self.focused_repo().is_none() {
+            self.set_status("No repositories selected".to_string());
+            return;
+        }
+        if self.focused_placeholder() {
+            return;
+        }
+        if self.launchers.is_empty() {
+            self.set_status("No launch actions configured".to_string());
+            return;
+        }
+        self.menu = Some(Menu {
+            kind: MenuKind::Launch,
+            selected: 0,
+        });
+    }
+
+    pub fn open_browse_menu(&mut self) {
+        let Some(repo) = self.focused_repo() else {
+            self.set_status("No repositories selected".to_string());
+            return;
+        };
+        let branch = current_branch(repo);
+        let default = default_branch(&repo.path);
+        match browse_links(
+            &repo.remote_url,
+            branch,
+            default.as_deref(),
+            &self.browse_hosts,
+        ) {
+            Ok(links) => {
+                self.menu = Some(Menu {
+                    kind: MenuKind::Browse(links),
+                    selected: 0,
+                })
+            }
+            Err(err) => self.set_status_with_type(err, StatusType::Error),
+        }
+    }
+
+    pub fn open_copy_menu(&mut self) {
+        let Some(repo) = self.focused_repo() else {
+            self.set_status("No repositories selected".to_string());
+            return;
+        };
+        let mut items = vec![('p', "Path", repo.path.display().to_string())];
+        if let Some(branch) = current_branch(repo) {
+            items.push(('b', "Branch", branch.to_string()));
+        }
+        if let Some(url) = &repo.origin_url {
+            items.push(('r', "Remote URL", url.clone()));
+        }
+        let error = repo
+            .error_message
+            .clone()
+            .or_else(|| (self.status_type == StatusType::Error).then(|| self.status_line.clone()));
+        if let Some(error) = error {
+            items.push(('e', "Error message", error));
+        }
+        self.menu = Some(Menu {
+            kind: MenuKind::Copy(items),
+            selected: 0,
+        });
+    }
+
+    /// Key, label and detail text of each entry in the open menu.
+    pub fn menu_items(&self) -> Vec<(char, String, String)> {
+        match self.menu.as_ref().map(|menu| &menu.kind) {
+            Some(MenuKind::Launch) => self
+                .launchers
+                .iter()
+                .map(|launcher| (launcher.key, launcher.name.clone(), String::new()))
+                .collect(),
+            Some(MenuKind::Browse(links)) => links
+                .iter()
+                .map(|(target, url)| (target.key(), target.label().to_string(), url.clone()))
+                .collect(),
+            Some(MenuKind::Copy(items)) => items
+                .iter()
+                .map(|(key, label, value)| (*key, label.to_string(), value.clone()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn move_menu_selection(&mut self, delta: isize) {
+        let last = self.menu_items().len().saturating_sub(1);
+        if let Some(menu) = self.menu.as_mut() {
+            menu.selected = menu.selected.saturating_add_signed(delta).min(last);
+        }
+    }
+
+    /// Run the menu entry for `key`; an uppercase key copies a browse link
+    /// instead of opening it.
+    pub fn activate_menu_key(&mut self, key: char) {
+        let items = self.menu_items();
+        if let Some(index) = items.iter().position(|(item_key, _, _)| *item_key == key) {
+            self.activate_menu(index, false);
+        } else if matches!(
+            self.menu,
+            Some(Menu {
+                kind: MenuKind::Browse(_),
+                ..
+            })
+        ) {
+            let lower = key.to_ascii_lowercase();
+            if let Some(index) = items.iter().position(|(item_key, _, _)| *item_key == lower) {
+                self.activate_menu(index, true);
+            }
+        }
+    }
+
+    /// Close the menu and act on the entry at `index`.
+    pub fn activate_menu(&mut self, index: usize, copy: bool) {
+        let Some(menu) = self.menu.take() else {
+            return;
+        };
+        match menu.kind {
+            MenuKind::Launch => self.launch(index),
+            MenuKind::Browse(links) => {
+                let Some((_, url)) = links.get(index) else {
+                    return;
+                };
+                if copy {
+                    self.copy(url);
+                } else if let Some(repo) = self.focused_repo() {
+                    self.pending_launch = Some(LaunchRequest::open_url(url, &repo.path));
+                }
+            }
+            MenuKind::Copy(items) => {
+                if let Some((_, _, value)) = items.get(index) {
+                    self.copy(value);
+                }
+            }
+        }
+    }
+
+    /// Copy `text` to the clipboard and confirm it in the status line.
+    pub fn copy(&mut self, text: &str) {
+        match copy_to_clipboard(text) {
+            Ok(()) => self.set_status_with_type(format!("Copied {text}"), StatusType::Success),
+            Err(err) => self.set_status_with_type(format!("Copy failed: {err}"), StatusType::Error),
+        }
+    }
+
+    /// Queue the launcher at `index` for the focused repository.
+    fn launch(&mut self, index: usize) {
+        let Some(launcher) = self.launchers.get(index) else {
+            return;
+        };
+        let Some(repo) = self.focused_repo() else {
+            self.set_status("No repositories selected".to_string());
+            return;
+        };
+        let branch = current_branch(repo).unwrap_or_default();
+        let remote = Remote {
+            url: repo.origin_url.as_deref().unwrap_or_default(),
+            display: if repo.remote_url == NO_REMOTE {
+                ""
+            } else {
+                repo.remote_url.as_str()
+            },
+        };
+        self.pending_launch = Some(launcher.request(&repo.path, branch, &remote));
+    }
+
+    pub fn open_detail(&mut self) {
+        let Some(repo) = self.selected_repo() else {
+            self.set_status("No repositories selected".to_string());
+            return;
+        };
+        let view = DetailView {
+            path: repo.path.clone(),
+            selected: 0,
+        };
+        self.panes.push(Pane::Detail(view));
+    }
+
+    /// Open the diff for the file selected in the detail pane, or for the
+    /// whole repository when `whole_repo` is set.
+    pub fn open_diff(&mut self, whole_repo: bool) {
+        let Some(Pane::Detail(detail)) = self.panes.last() else {
+            return;
+        };
+        let Some(repo) = self.repos.iter().find(|repo| repo.path == detail.path) else {
+            return;
+        };
+        if repo.is_bare() {
+            self.set_status("Bare repositories have no working tree".to_string());
+            return;
+        }
+        let change = if whole_repo {
+            None
+        } else {
+            match repo.changes.get(detail.selected) {
+                Some(change) => Some(change.clone()),
+                None => {
+                    self.set_status("No changed files".to_string());
+                    return;
+                }
+            }
+        };
+        let untracked = change.as_ref().is_some_and(|(code, _)| code == "??");
+        let file = change.map(|(_, file)| file);
+        let view = DiffView {
+            path: repo.path.clone(),
+            repo_name: repo.name.clone(),
+            file: file.clone(),
+            lines: Vec::new(),
+            loading: true,
+            error: None,
+            scroll: 0,
+        };
+        let cmd = WorkerCmd::Diff {
+            path: view.path.clone(),
+            file,
+            untracked,
+        };
+        self.panes.push(Pane::Diff(view));
+        if let Err(err) = self.cmd_tx.send(cmd) {
+            self.set_status(format!("Worker unavailable: {err}"));
+        }
+    }
+
+    pub fn apply_diff(
+        &mut self,
+        path: &Path,
+        file: Option<String>,
+        result: Result<Vec<DiffLine>, String>,
+    ) {
+        let Some(Pane::Diff(view)) = self.panes.last_mut() else {
+            return;
+        };
+        if view.path != path || view.file != file {
+            return;
+        }
+        view.loading = false;
+        match result {
+            Ok(lines) => view.lines = lines,
+            Err(err) => view.error = Some(err),
+        }
+    }
+
+    /// Jump to the next (`forward`) or previous section of the current pane:
+    /// hunks in a diff, repositories in command output.
+    pub fn jump_section(&mut self, forward: bool) {
+        let (offsets, scroll) = match self.panes.last_mut() {
+            Some(Pane::Diff(view)) => (view.hunk_offsets().collect(), &mut view.scroll),
+            Some(Pane::Exec(view)) => (view.row_offsets(), &mut view.scroll),
+            _ => return,
+        };
+        let current = *scroll;
+        let target: Option<usize> = if forward {
+            offsets.into_iter().find(|&offset| offset > current)
+        } else {
+            offsets.into_iter().rev().find(|&offset| offset < current)
+        };
+        if let Some(target) = target {
+            *scroll = target;
+        }
+    }
+
+    pub fn open_branches(&mut self) {
+        if self.focused_placeholder() {
+            return;
+        }
+        let Some(repo) = self.focused_repo() else {
+            self.set_status("No repositories selected".to_string());
+            return;
+        };
+        let view = BranchView {
+            path: repo.path.clone(),
+            repo_name: repo.name.clone(),
+            branches: Vec::new(),
+            loading: true,
+            error: None,
+            selected: 0,
+        };
+        self.panes.push(Pane::Branches(view));
+        self.request_branches();
+    }
+
+    /// Reload the branch pane, e.g. after a checkout or delete finished.
+    pub fn request_branches(&mut self) {
+        let Some(Pane::Branches(view)) = self.panes.last_mut() else {
+            return;
+        };
+        view.loading = true;
+        let cmd = WorkerCmd::Branches {
+            path: view.path.clone(),
+        };
+        if let Err(err) = self.cmd_tx.send(cmd) {
+            self.set_status(format!("Worker unavailable: {err}"));
+        }
+    }
+
+    pub fn apply_branches(&mut self, path: &Path, result: Result<Vec<BranchInfo>, String>) {
+        let Some(Pane::Branches(view)) = self.panes.last_mut() else {
+            return;
+        };
+        if view.path != path {
+            return;
+        }
+        view.loading = false;
+        match result {
+            Ok(branches) => {
+                view.selected = view.selected.min(branches.len().saturating_sub(1));
+                view.branches = branches;
+                view.error = None;
+            }
+            Err(err) => view.error = Some(err),
+        }
+    }
+
+    pub fn request_checkout(&mut self) {
+        let Some(Pane::Branches(view)) = self.panes.last() else {
+            return;
+        };
+        let Some(branch) = view.selected_branch() else {
+            return;
+        };
+        if branch.is_head {
+            self.set_status(format!("Already on {}", branch.name));
+            return;
+        }
+        let action = Action::Checkout {
+            branch: branch.name.clone(),
+            create: false,
+        };
+        // Untracked files survive a branch switch; tracked changes might not
+        let has_tracked_changes = self
+            .focused_repo()
+            .is_some_and(|repo| repo.changes.iter().any(|(code, _)| code != "??"));
+        if has_tracked_changes {
+            self.set_status_with_type(
+                "Working tree has uncommitted changes - commit or stash first".to_string(),
+                StatusType::Error,
+            );
+            return;
+        }
+        self.request_confirm(action);
+    }
+
+    pub fn request_delete_branch(&mut self) {
+        let Some(Pane::Branches(view)) = self.panes.last() else {
+            return;
+        };
+        let Some(branch) = view.selected_branch() else {
+            return;
+        };
+        if branch.is_head {
+            self.set_status("Cannot delete the checked out branch".to_string());
+            return;
+        }
+        if !branch.merged {
+            self.set_status_with_type(
+                format!("{} is not fully merged into HEAD", branch.name),
+                StatusType::Error,
+            );
+            return;
+        }
+        let action = Action::DeleteBranch(branch.name.clone());
+        self.request_confirm(action);
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let Some(path) = self.selected_repo().map(|repo| repo.path.clone()) else {
+            return;
+        };
+        if !self.marked.remove(&path) {
+            self.marked.insert(path);
+        }
+        self.next();
+    }
+
+    /// Mark every filtered repository, or clear all marks if all are marked already.
+    pub fn toggle_mark_all(&mut self) {
+        let filtered: Vec<PathBuf> = self
+            .filtered_indices()
+            .into_iter()
+            .map(|idx| self.repos[idx].path.clone())
+            .collect();
+        if filtered.iter().all(|path| self.marked.contains(path)) {
+            self.marked.clear();
+        } else {
+            self.marked.extend(filtered);
+        }
+    }
+
+    /// Repositories a batch operation applies to: the marked ones, or every
+    /// filtered repository when nothing is marked.
+    pub fn batch_targets(&self) -> Vec<&RepoState> {
+        let indices = self.filtered_indices();
+        if self.marked.is_empty() {
+            return indices.into_iter().map(|idx| &self.repos[idx]).collect();
+        }
+        self.repos
+            .iter()
+            .filter(|repo| self.marked.contains(&repo.path))
+            .collect()
+    }
+
+    pub fn open_prompt(&mut self, kind: PromptKind) {
+        let has_targets = match kind {
+            PromptKind::AddWorktree => self.focused_repo().is_some(),
+            PromptKind::BatchCheckout | PromptKind::Exec => !self.batch_targets().is_empty(),
+        };
+        if !has_targets {
+            self.set_status("No repositories selected".to_string());
+            return;
+        }
+        if kind == PromptKind::AddWorktree && self.focused_placeholder() {
+            return;
+        }
+        self.prompt = Some(Prompt {
+            kind,
+            input: String::new(),
+        });
+    }
+
+    pub fn submit_prompt(&mut self) {
+        let Some(prompt) = self.prompt.take() else {
+            return;
+        };
+        let input = prompt.input.trim().to_string();
+        if input.is_empty() {
+            self.set_status("Action canceled".to_string());
+            return;
+        }
+        match prompt.kind {
+            PromptKind::BatchCheckout => self.start_batch_checkout(input),
+            PromptKind::Exec => self.start_exec(input),
+            PromptKind::AddWorktree => self.perform_action(Action::AddWorktree(input)),
+        }
+    }
+
+    fn start_batch_checkout(&mut self, branch: String) {
+        let rows: Vec<BatchRow> = self
+            .batch_targets()
+            .into_iter()
+            .filter(|repo| !repo.is_bare() && !repo.is_placeholder())
+            .map(|repo| BatchRow {
+                path: repo.path.clone(),
+                name: repo.name.clone(),
+                plan: None,
+                result: None,
+            })
+            .collect();
+        if rows.is_empty() {
+            self.set_status("No repositories with a working tree selected".to_string());
+            return;
+        }
+        let cmd = WorkerCmd::CheckoutPreflight {
+            paths: rows.iter().map(|row| row.path.clone()).collect(),
+            branch: branch.clone(),
+        };
+        self.panes.push(Pane::Batch(BatchView {
+            branch,
+            rows,
+            stage: BatchStage::Preflight,
+            scroll: 0,
+        }));
+        if let Err(err) = self.cmd_tx.send(cmd) {
+            self.set_status(format!("Worker unavailable: {err}"));
+        }
+    }
+
+    fn start_exec(&mut self, command: String) {
+        if self.exec_view_mut().is_some() {
+            self.set_status("A command is still running".to_string());
+            return;
+        }
+        let rows: Vec<ExecRow> = self
+            .batch_targets()
+            .into_iter()
+            .filter(|repo| !repo.is_placeholder())
+            .map(|repo| ExecRow {
+                path: repo.path.clone(),
+                name: repo.name.clone(),
+                outcome: None,
+            })
+            .collect();
+        if rows.is_empty() {
+            self.set_status("No cloned repositories selected".to_string());
+            return;
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cmd = WorkerCmd::Exec {
+            paths: rows.iter().map(|row| row.path.clone()).collect(),
+            command: ExecCommand::Shell(command.clone()),
+            options: self.exec_options.clone(),
+            cancel: Arc::clone(&cancel),
+        };
+        self.panes.push(Pane::Exec(ExecView {
+            command,
+            rows,
+            done: false,
+            scroll: 0,
+            cancel,
+        }));
+        if let Err(err) = self.cmd_tx.send(cmd) {
+            self.set_status(format!("Worker unavailable: {err}"));
+        }
+    }
+
+    /// Kill the commands still running and skip the repositories left.
+    pub fn cancel_exec(&mut self) {
+        let Some(Pane::Exec(view)) = self.panes.last() else {
+            return;
+        };
+        if view.done {
+            return;
+        }
+        view.cancel.store(true, Ordering::Relaxed);
+        self.set_status("Cancelling command...".to_string());
+    }
+
+    pub fn apply_exec_output(&mut self, path: &Path, outcome: ExecOutcome) {
+        let Some(view) = self.exec_view_mut() else {
+            return;
+        };
+        if let Some(row) = view.rows.iter_mut().find(|row| row.path == path) {
+            row.outcome = Some(outcome);
+        }
+    }
+
+    pub fn apply_exec_complete(&mut self) {
+        let Some(view) = self.exec_view_mut() else {
+            return;
+        };
+        view.done = true;
+        let outcomes: Vec<(PathBuf, ExecOutcome)> = view
+            .rows
+            .iter()
+            .filter_map(|row| Some((row.path.clone(), row.outcome.clone()?)))
+            .collect();
+        let (succeeded, failed, skipped) = tally(&outcomes);
+        let status_type = if failed == 0 {
+            StatusType::Success
+        } else {
+            StatusType::Error
+        };
+        self.set_status_with_type(
+            format!("Command finished: {succeeded} succeeded, {failed} failed, {skipped} skipped"),
+            status_type,
+        );
+        self.request_refresh(false);
+    }
+
+    // Exec results keep arriving while other panes are stacked on top
+    fn exec_view_mut(&mut self) -> Option<&mut ExecView> {
+        self.panes.iter_mut().rev().find_map(|pane| match pane {
+            Pane::Exec(view) if !view.done => Some(view),
+            _ => None,
+        })
+    }
+
+    pub fn apply_checkout_preflight(&mut self, branch: &str, plans: Vec<(PathBuf, CheckoutPlan)>) {
+        let Some(Pane::Batch(view)) = self.panes.last_mut() else {
+            return;
+        };
+        if view.branch != branch || view.stage != BatchStage::Preflight {
+            return;
         }
+        for (path, plan) in plans {
+            if let Some(row) = view.rows.iter_mut().find(|row| row.path == path) {
+                row.plan = Some(plan);
+            }
+        }
+        view.stage = BatchStage::Review;
     }
 
-    pub fn request_scan(&mut self) {
-        self.loading = true;
-        self.scan_progress = 0.0;
-        if let Err(err) = self.cmd_tx.send(WorkerCmd::Scan {
-            root: self.root.clone(),
-        }) {
-            self.loading = false;
+    /// Run the reviewed batch checkout; missing branches are created only with `create`.
+    pub fn run_batch_checkout(&mut self, create: bool) {
+        let Some(Pane::Batch(view)) = self.panes.last_mut() else {
+            return;
+        };
+        if view.stage != BatchStage::Review {
+            return;
+        }
+        let jobs: Vec<(PathBuf, Action)> = view
+            .rows
+            .iter()
+            .filter_map(|row| {
+                let plan = row.plan.as_ref()?;
+                plan.is_actionable(create).then(|| {
+                    let action = Action::Checkout {
+                        branch: view.branch.clone(),
+                        create: *plan == CheckoutPlan::Missing,
+                    };
+                    (row.path.clone(), action)
+                })
+            })
+            .collect();
+        if jobs.is_empty() {
+            self.set_status("Nothing to check out".to_string());
+            return;
+        }
+        view.stage = BatchStage::Running {
+            done: 0,
+            total: jobs.len(),
+        };
+        if let Err(err) = self.cmd_tx.send(WorkerCmd::BatchAction { jobs }) {
             self.set_status(format!("Worker unavailable: {err}"));
         }
     }
 
-    pub fn request_refresh(&mut self) {
-        let repos = self
+    pub fn apply_batch_progress(&mut self, done: usize, total: usize) {
+        match self.panes.last_mut() {
+            Some(Pane::Batch(view)) if matches!(view.stage, BatchStage::Running { .. }) => {
+                view.stage = BatchStage::Running { done, total };
+            }
+            Some(Pane::Prune(view)) if matches!(view.stage, PruneStage::Running { .. }) => {
+                view.stage = PruneStage::Running { done, total };
+            }
+            _ => {}
+        }
+    }
+
+    pub fn apply_batch_result(&mut self, results: Vec<(PathBuf, Action, Result<String, String>)>) {
+        let ok = results
+            .iter()
+            .filter(|(_, _, result)| result.is_ok())
+            .count();
+        let failed = results.len() - ok;
+        match self.panes.last_mut() {
+            Some(Pane::Batch(view)) => {
+                for (path, _, result) in results {
+                    if let Some(row) = view.rows.iter_mut().find(|row| row.path == path) {
+                        row.result = Some(result);
+                    }
+                }
+                view.stage = BatchStage::Done;
+            }
+            Some(Pane::Prune(view)) => {
+                for (path, _, result) in results {
+                    if let Some(group) = view.groups.iter_mut().find(|group| group.path == path) {
+                        group.result = Some(result);
+                    }
+                }
+                view.stage = PruneStage::Done;
+            }
+            _ => {}
+        }
+        let status_type = if failed == 0 {
+            StatusType::Success
+        } else {
+            StatusType::Error
+        };
+        self.set_status_with_type(
+            format!("Batch finished: {ok} succeeded, {failed} failed"),
+            status_type,
+        );
+        self.request_refresh(false);
+    }
+
+    pub fn open_prune(&mut self) {
+        if self.repos.is_empty() {
+            self.set_status("No repositories selected".to_string());
+            return;
+        }
+        let groups: Vec<PruneGroup> = self
             .repos
             .iter()
-            .map(|repo| RepoRef {
+            .filter(|repo| !repo.is_placeholder())
+            .map(|repo| PruneGroup {
                 path: repo.path.clone(),
-                git_dir: repo.git_dir.clone(),
+                name: repo.name.clone(),
+                default_branch: String::new(),
+                items: Vec::new(),
+                error: None,
+                result: None,
             })
             .collect();
-        if let Err(err) = self.cmd_tx.send(WorkerCmd::Refresh { repos }) {
+        let paths = groups.iter().map(|group| group.path.clone()).collect();
+        self.panes.push(Pane::Prune(PruneView {
+            groups,
+            stage: PruneStage::Scanning,
+            cursor: 0,
+        }));
+        if let Err(err) = self.cmd_tx.send(WorkerCmd::PruneScan { paths }) {
             self.set_status(format!("Worker unavailable: {err}"));
         }
     }
 
-    pub fn request_confirm(&mut self, action: Action) {
-        if self.repos.is_empty() {
-            self.set_status("No repositories selected".to_string());
+    pub fn apply_prune_candidates(&mut self, results: Vec<(PathBuf, PruneScanResult)>) {
+        let Some(Pane::Prune(view)) = self.panes.last_mut() else {
+            return;
+        };
+        if view.stage != PruneStage::Scanning {
             return;
         }
+        for (path, result) in results {
+            let Some(group) = view.groups.iter_mut().find(|group| group.path == path) else {
+                continue;
+            };
+            match result {
+                Ok((default_branch, candidates)) => {
+                    group.default_branch =
+                        default_branch.unwrap_or_else(|| "unknown, merged not checked".to_string());
+                    // Merged branches are safe to delete; gone-only ones may hold unpushed work
+                    group.items = candidates
+                        .into_iter()
+                        .map(|candidate| PruneItem {
+                            selected: candidate.merged,
+                            candidate,
+                        })
+                        .collect();
+                }
+                Err(err) => group.error = Some(err),
+            }
+        }
+        // Only repositories with something to show stay listed
+        view.groups
+            .retain(|group| !group.items.is_empty() || group.error.is_some());
+        view.stage = PruneStage::Review;
+    }
 
-        // Validate that we have a remote before allowing push/pull
-        if let Some(repo) = self.selected_repo() {
-            if repo.remote_url == NO_REMOTE {
-                self.set_status("No remote configured for this repository".to_string());
-                return;
+    /// Toggle the branch under the cursor, or every branch of the repository
+    /// when the cursor is on its header.
+    pub fn toggle_prune_selection(&mut self) {
+        let Some(Pane::Prune(view)) = self.panes.last_mut() else {
+            return;
+        };
+        if view.stage != PruneStage::Review {
+            return;
+        }
+        let Some(&(group_idx, item_idx)) = view.rows().get(view.cursor) else {
+            return;
+        };
+        let group = &mut view.groups[group_idx];
+        match item_idx {
+            Some(item_idx) => group.items[item_idx].selected ^= true,
+            None => {
+                let select = !group.items.iter().all(|item| item.selected);
+                for item in &mut group.items {
+                    item.selected = select;
+                }
             }
         }
+    }
 
-        self.confirmation = Some(action);
+    pub fn toggle_prune_all(&mut self) {
+        let Some(Pane::Prune(view)) = self.panes.last_mut() else {
+            return;
+        };
+        if view.stage != PruneStage::Review {
+            return;
+        }
+        let select = !view
+            .groups
+            .iter()
+            .flat_map(|group| group.items.iter())
+            .all(|item| item.selected);
+        for item in view
+            .groups
+            .iter_mut()
+            .flat_map(|group| group.items.iter_mut())
+        {
+            item.selected = select;
+        }
     }
 
-    pub fn perform_action(&mut self, action: Action) {
-        if let Some(repo) = self.selected_repo() {
-            if let Err(err) = self.cmd_tx.send(WorkerCmd::Action {
-                path: repo.path.clone(),
-                action,
-            }) {
-                self.set_status(format!("Worker unavailable: {err}"));
-            } else {
-                self.set_status("Running action...".to_string());
+    pub fn request_prune_delete(&mut self) {
+        let Some(Pane::Prune(view)) = self.panes.last_mut() else {
+            return;
+        };
+        if view.stage != PruneStage::Review {
+            return;
+        }
+        if view.selected_count().0 == 0 {
+            self.set_status("No branches selected".to_string());
+            return;
+        }
+        view.stage = PruneStage::Confirm;
+    }
+
+    pub fn confirm_prune(&mut self, confirmed: bool) {
+        let Some(Pane::Prune(view)) = self.panes.last_mut() else {
+            return;
+        };
+        if view.stage != PruneStage::Confirm {
+            return;
+        }
+        if !confirmed {
+            view.stage = PruneStage::Review;
+            self.set_status("Action canceled".to_string());
+            return;
+        }
+        // One job per repository so deletions never race on the same refs
+        let jobs: Vec<(PathBuf, Action)> = view
+            .groups
+            .iter()
+            .filter_map(|group| {
+                let branches: Vec<String> = group
+                    .items
+                    .iter()
+                    .filter(|item| item.selected)
+                    .map(|item| item.candidate.branch.clone())
+                    .collect();
+                (!branches.is_empty())
+                    .then(|| (group.path.clone(), Action::PruneBranches(branches)))
+            })
+            .collect();
+        view.stage = PruneStage::Running {
+            done: 0,
+            total: jobs.len(),
+        };
+        if let Err(err) = self.cmd_tx.send(WorkerCmd::BatchAction { jobs }) {
+            self.set_status(format!("Worker unavailable: {err}"));
+        }
+    }
+
+    pub fn open_log(&mut self) {
+        if self.focused_placeholder() {
+            return;
+        }
+        let Some(repo) = self.focused_repo() else {
+            self.set_status("No repositories selected".to_string());
+            return;
+        };
+        let view = LogView {
+            path: repo.path.clone(),
+            repo_name: repo.name.clone(),
+            mode: LogMode::History,
+            entries: Vec::new(),
+            loading: true,
+            error: None,
+            scroll: 0,
+        };
+        self.panes.push(Pane::Log(view));
+        self.request_log();
+    }
+
+    pub fn cycle_log_mode(&mut self) {
+        if let Some(Pane::Log(view)) = self.panes.last_mut() {
+            view.mode = view.mode.next();
+            view.entries.clear();
+            view.loading = true;
+            view.error = None;
+            view.scroll = 0;
+            self.request_log();
+        }
+    }
+
+    fn request_log(&mut self) {
+        let Some(Pane::Log(view)) = self.panes.last() else {
+            return;
+        };
+        let cmd = WorkerCmd::Log {
+            path: view.path.clone(),
+            mode: view.mode,
+        };
+        if let Err(err) = self.cmd_tx.send(cmd) {
+            self.set_status(format!("Worker unavailable: {err}"));
+        }
+    }
+
+    pub fn apply_log(&mut self, path: &Path, mode: LogMode, result: Result<Vec<LogEntry>, String>) {
+        // Ignore results for a log that was closed or switched to another mode meanwhile
+        let Some(Pane::Log(view)) = self.panes.last_mut() else {
+            return;
+        };
+        if view.path != path || view.mode != mode {
+            return;
+        }
+        view.loading = false;
+        match result {
+            Ok(entries) => view.entries = entries,
+            Err(err) => view.error = Some(err),
+        }
+    }
+
+    pub fn close_pane(&mut self) {
+        // A closed command view can no longer be cancelled, so stop it now
+        if let Some(Pane::Exec(view)) = self.panes.pop() {
+            view.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn scroll_pane(&mut self, delta: isize) {
+        match self.panes.last_mut() {
+            Some(Pane::Log(view)) => view.scroll_to(view.scroll.saturating_add_signed(delta)),
+            Some(Pane::Diff(view)) => view.scroll_to(view.scroll.saturating_add_signed(delta)),
+            Some(Pane::Exec(view)) => {
+                view.scroll = view
+                    .scroll
+                    .saturating_add_signed(delta)
+                    .min(view.line_count().saturating_sub(1));
+            }
+            Some(Pane::Prune(view)) => {
+                view.cursor = view
+                    .cursor
+                    .saturating_add_signed(delta)
+                    .min(view.rows().len().saturating_sub(1));
+            }
+            Some(Pane::Batch(view)) => {
+                view.scroll = view
+                    .scroll
+                    .saturating_add_signed(delta)
+                    .min(view.rows.len().saturating_sub(1));
+            }
+            Some(Pane::Branches(view)) => {
+                view.selected = view
+                    .selected
+                    .saturating_add_signed(delta)
+                    .min(view.branches.len().saturating_sub(1));
             }
+            Some(Pane::Detail(view)) => {
+                let len = self
+                    .repos
+                    .iter()
+                    .find(|repo| repo.path == view.path)
+                    .map_or(0, |repo| repo.changes.len());
+                view.selected = view
+                    .selected
+                    .saturating_add_signed(delta)
+                    .min(len.saturating_sub(1));
+            }
+            None => {}
         }
     }
 
+    pub fn scroll_pane_to_start(&mut self) {
+        self.scroll_pane(isize::MIN);
+    }
+
+    pub fn scroll_pane_to_end(&mut self) {
+        self.scroll_pane(isize::MAX);
+    }
+
     pub fn request_quit(&mut self) {
         if let Err(err) = self.cmd_tx.send(WorkerCmd::Quit) {
             self.set_status(format!("Worker unavailable: {err}"));
@@ -192,6 +1728,51 @@ impl App {
         }
     }
 
+    /// Move the table selection by `delta` rows without wrapping around.
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = self.filtered_indices().len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.table_state.selected() {
+            Some(i) => i.saturating_add_signed(delta).min(len - 1),
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    /// Select the table row drawn at screen `row`, or sort when the header is clicked.
+    pub fn click_table(&mut self, column: u16, row: u16) {
+        let area = self.hit_areas.table;
+        if !area.contains(Position::new(column, row)) {
+            return;
+        }
+        if row == area.y {
+            if let Some((clicked, _, _)) = self
+                .hit_areas
+                .columns
+                .iter()
+                .find(|(_, x, width)| column >= *x && column < x + width)
+            {
+                self.sort_by_column(*clicked);
+            }
+            return;
+        }
+        let index = self.table_state.offset() + usize::from(row - area.y - 1);
+        if index < self.filtered_indices().len() {
+            self.table_state.select(Some(index));
+        }
+    }
+
+    pub fn toggle_mouse(&mut self) {
+        self.mouse_enabled = !self.mouse_enabled;
+        if self.mouse_enabled {
+            self.set_status("Mouse enabled".to_string());
+        } else {
+            self.set_status("Mouse disabled, terminal text selection available".to_string());
+        }
+    }
+
     pub fn selected_repo(&self) -> Option<&RepoState> {
         let indices = self.filtered_indices();
         self.table_state
@@ -210,58 +1791,310 @@ impl App {
         self.status_timestamp = Instant::now();
     }
 
-    pub fn sort_repos(&mut self) {
-        match self.sort_order {
-            SortOrder::Name => {
-                self.repos.sort_by(|a, b| a.name.cmp(&b.name));
-            }
-            SortOrder::Status => {
-                // Dirty repos first, then by name
-                self.repos
-                    .sort_by(|a, b| b.dirty.cmp(&a.dirty).then_with(|| a.name.cmp(&b.name)));
-            }
-            SortOrder::AheadBehind => {
-                // Repos with changes first (ahead or behind), then by name
-                self.repos.sort_by(|a, b| {
-                    let a_has_changes = has_ahead_or_behind(&a.ahead_behind);
-                    let b_has_changes = has_ahead_or_behind(&b.ahead_behind);
-                    b_has_changes
-                        .cmp(&a_has_changes)
-                        .then_with(|| a.name.cmp(&b.name))
-                });
+    /// Replace the repository list, keeping the selected repository selected.
+    pub fn set_repos(&mut self, repos: Vec<RepoState>) {
+        let selected = self.selected_repo().map(|repo| repo.path.clone());
+        self.repos = repos;
+        self.sort_and_reselect(selected);
+    }
+
+    pub fn finish_scan(&mut self, message: String) {
+        self.loading = false;
+        self.scan_progress = 1.0;
+        if self.missing_targets.is_empty() {
+            self.set_status_with_type(message, StatusType::Success);
+            return;
+        }
+        let missing: Vec<String> = self
+            .missing_targets
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        self.set_status_with_type(
+            format!("{message} (not found: {})", missing.join(", ")),
+            StatusType::Error,
+        );
+    }
+
+    /// Reconcile the table with a finished background discovery: drop
+    /// repositories that vanished and read the status of new ones.
+    pub fn apply_discovery(&mut self, repos: Vec<RepoRef>) {
+        let found: HashSet<&Path> = repos.iter().map(|repo| repo.path.as_path()).collect();
+        let before = self.repos.len();
+        self.repos
+            .retain(|repo| repo.is_placeholder() || found.contains(repo.path.as_path()));
+        let removed = before - self.repos.len();
+        let rows: HashSet<&Path> = self.repos.iter().map(|repo| repo.path.as_path()).collect();
+        self.marked.retain(|path| rows.contains(path.as_path()));
+        // A placeholder found on disk was cloned outside git-dash and needs its status read
+        let known: HashSet<PathBuf> = self
+            .repos
+            .iter()
+            .filter(|repo| !repo.is_placeholder())
+            .map(|repo| repo.path.clone())
+            .collect();
+        if removed > 0 {
+            self.sort_repos();
+        }
+
+        let added: Vec<RepoRef> = repos
+            .into_iter()
+            .filter(|repo| !known.contains(&repo.path))
+            .collect();
+        if added.is_empty() {
+            let message = match removed {
+                0 => "Scan complete".to_string(),
+                removed => format!("Scan complete: {removed} removed"),
+            };
+            self.finish_scan(message);
+            return;
+        }
+        self.set_status(format!(
+            "Found {} new repositories, reading status...",
+            added.len()
+        ));
+        if let Err(err) = self.cmd_tx.send(WorkerCmd::Status { repos: added }) {
+            self.loading = false;
+            self.set_status(format!("Worker unavailable: {err}"));
+        }
+    }
+
+    /// Reload the linked worktrees of the repository `path` belongs to.
+    pub fn request_worktrees(&mut self, path: &Path) {
+        let main = self
+            .repos
+            .iter()
+            .find(|repo| repo.path == path)
+            .and_then(|repo| repo.worktree_of.clone())
+            .unwrap_or_else(|| path.to_path_buf());
+        if let Err(err) = self.cmd_tx.send(WorkerCmd::Worktrees { main }) {
+            self.set_status(format!("Worker unavailable: {err}"));
+        }
+    }
+
+    /// Replace the linked worktrees listed under `main`.
+    pub fn apply_worktrees(&mut self, main: &Path, result: Result<Vec<RepoState>, String>) {
+        let worktrees = match result {
+            Ok(worktrees) => worktrees,
+            Err(err) => {
+                self.set_status_with_type(
+                    format!("Failed to list worktrees: {err}"),
+                    StatusType::Error,
+                );
+                return;
             }
-            SortOrder::LastFetch => {
-                // Most recently fetched first, then by name
-                self.repos.sort_by(|a, b| {
-                    a.last_fetch
-                        .cmp(&b.last_fetch)
-                        .then_with(|| a.name.cmp(&b.name))
-                });
+        };
+        let listed: HashSet<&Path> = worktrees.iter().map(|repo| repo.path.as_path()).collect();
+        self.repos.retain(|repo| {
+            repo.worktree_of.as_deref() != Some(main) || listed.contains(repo.path.as_path())
+        });
+        let present: HashSet<PathBuf> = self.repos.iter().map(|repo| repo.path.clone()).collect();
+        self.marked.retain(|path| present.contains(path));
+        self.merge_repos(worktrees);
+    }
+
+    /// Add or update repositories without touching the rest of the table.
+    pub fn merge_repos(&mut self, states: Vec<RepoState>) {
+        let selected = self.selected_repo().map(|repo| repo.path.clone());
+        for state in states {
+            match self.repos.iter_mut().find(|repo| repo.path == state.path) {
+                Some(existing) => *existing = state,
+                None => self.repos.push(state),
             }
         }
+        self.sort_and_reselect(selected);
+    }
+
+    /// Sort by the current order, keeping the selected repository selected.
+    pub fn sort_repos(&mut self) {
+        let selected = self.selected_repo().map(|repo| repo.path.clone());
+        self.sort_and_reselect(selected);
+    }
 
+    fn sort_and_reselect(&mut self, selected: Option<PathBuf>) {
+        self.apply_manifest();
+        let order = self.sort_order;
+        self.repos.sort_by(|a, b| order.compare(a, b));
+        group_children(&mut self.repos);
+
+        let position = selected.and_then(|path| {
+            self.filtered_indices()
+                .iter()
+                .position(|index| self.repos[*index].path == path)
+        });
         if self.repos.is_empty() {
             self.table_state.select(None);
         } else {
-            self.table_state.select(Some(0));
+            self.table_state.select(Some(position.unwrap_or(0)));
         }
     }
 
-    pub fn cycle_sort_order(&mut self) {
-        self.sort_order = match self.sort_order {
-            SortOrder::Name => SortOrder::Status,
-            SortOrder::Status => SortOrder::AheadBehind,
-            SortOrder::AheadBehind => SortOrder::LastFetch,
-            SortOrder::LastFetch => SortOrder::Name,
+    /// Mark rows as declared in the manifest or not, and keep a placeholder
+    /// row for each declared repository that is not cloned.
+    fn apply_manifest(&mut self) {
+        let Some(manifest) = &self.manifest else {
+            return;
+        };
+        let cloned: HashSet<PathBuf> = self
+            .repos
+            .iter()
+            .filter(|repo| !repo.is_placeholder())
+            .map(|repo| repo.path.clone())
+            .collect();
+        self.repos
+            .retain(|repo| !repo.is_placeholder() || !cloned.contains(&repo.path));
+        for repo in self.repos.iter_mut().filter(|repo| !repo.is_placeholder()) {
+            repo.manifest = match manifest.entry(&repo.path) {
+                Some(entry) => Some(ManifestMark::Declared(entry.clone())),
+                // Submodules and linked worktrees come with their parent row
+                None if repo.parent().is_some() || !repo.path.starts_with(&manifest.root) => None,
+                None => Some(ManifestMark::Undeclared),
+            };
+        }
+        let listed: HashSet<PathBuf> = self.repos.iter().map(|repo| repo.path.clone()).collect();
+        let missing = manifest
+            .repos
+            .iter()
+            .filter(|entry| !listed.contains(&entry.path))
+            .map(ManifestRepo::placeholder);
+        self.repos.extend(missing);
+    }
+
+    /// Clone the missing repositories among the batch targets: the marked
+    /// rows, or every filtered one when nothing is marked.
+    pub fn request_clone_missing(&mut self) {
+        if self.manifest.is_none() {
+            self.set_status("No manifest loaded".to_string());
+            return;
+        }
+        if self.clone_progress.is_some() {
+            self.set_status("A clone is still running".to_string());
+            return;
+        }
+        let repos: Vec<ManifestRepo> = self
+            .batch_targets()
+            .into_iter()
+            .filter_map(|repo| match &repo.manifest {
+                Some(ManifestMark::Missing(entry)) => Some(entry.clone()),
+                _ => None,
+            })
+            .collect();
+        if repos.is_empty() {
+            self.set_status("No missing repositories selected".to_string());
+            return;
+        }
+        let total = repos.len();
+        if let Err(err) = self.cmd_tx.send(WorkerCmd::Clone { repos }) {
+            self.set_status(format!("Worker unavailable: {err}"));
+            return;
+        }
+        self.clone_progress = Some((0, total));
+        self.set_status(format!("Cloning {total} repositories..."));
+    }
+
+    pub fn apply_clone_progress(&mut self, done: usize, total: usize) {
+        self.clone_progress = Some((done, total));
+    }
+
+    /// Replace placeholders with the cloned repositories and leave the
+    /// error on the ones that failed.
+    pub fn apply_clone_result(
+        &mut self,
+        results: Vec<(PathBuf, Result<String, String>)>,
+        states: Vec<RepoState>,
+    ) {
+        self.clone_progress = None;
+        let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+        for (path, result) in results {
+            let Err(err) = result else {
+                continue;
+            };
+            let placeholder = self
+                .repos
+                .iter_mut()
+                .find(|repo| repo.path == path && repo.is_placeholder());
+            if let Some(repo) = placeholder {
+                repo.error_message = Some(err);
+            }
+        }
+        let cloned = states.len();
+        self.merge_repos(states);
+        let status_type = if failed == 0 {
+            StatusType::Success
+        } else {
+            StatusType::Error
         };
+        self.set_status_with_type(
+            format!("Clone finished: {cloned} cloned, {failed} failed"),
+            status_type,
+        );
+    }
+
+    /// Sort by the next shown column, wrapping around to the first.
+    pub fn cycle_sort_order(&mut self) {
+        let columns: Vec<Column> = self.columns.iter().map(|spec| spec.column).collect();
+        if columns.is_empty() {
+            return;
+        }
+        let next = columns
+            .iter()
+            .position(|column| *column == self.sort_order.column)
+            .map_or(0, |index| (index + 1) % columns.len());
+        self.set_sort_order(SortOrder::new(columns[next]));
+    }
+
+    pub fn reverse_sort_order(&mut self) {
+        self.set_sort_order(self.sort_order.reversed());
+    }
+
+    /// Sort by `column`, or reverse the order if already sorted by it.
+    pub fn sort_by_column(&mut self, column: Column) {
+        if self.sort_order.column == column {
+            self.reverse_sort_order();
+        } else {
+            self.set_sort_order(SortOrder::new(column));
+        }
+    }
+
+    fn set_sort_order(&mut self, order: SortOrder) {
+        self.sort_order = order;
         self.sort_repos();
-        let sort_name = match self.sort_order {
-            SortOrder::Name => "Name",
-            SortOrder::Status => "Status (dirty first)",
-            SortOrder::AheadBehind => "Ahead/Behind",
-            SortOrder::LastFetch => "Last Fetch",
+        let direction = if order.descending {
+            "descending"
+        } else {
+            "ascending"
         };
-        self.set_status(format!("Sorted by: {}", sort_name));
+        self.set_status(format!("Sorted by: {} ({direction})", order.column.title()));
+    }
+
+    pub fn open_column_picker(&mut self) {
+        let rendered = self
+            .hit_areas
+            .columns
+            .iter()
+            .map(|(column, _, width)| (*column, *width))
+            .collect();
+        self.column_picker = Some(ColumnPicker::new(&self.columns, rendered));
+    }
+
+    /// Use the columns chosen in the picker for the rest of the session.
+    pub fn apply_column_picker(&mut self) {
+        if let Some(picker) = self.column_picker.take() {
+            let before = status_extras(&self.columns);
+            self.columns = picker.specs();
+            let extras = status_extras(&self.columns);
+            self.set_status(format!("Showing {} columns", self.columns.len()));
+            if extras != before {
+                if let Err(err) = self.cmd_tx.send(WorkerCmd::SetStatusExtras(extras)) {
+                    self.set_status(format!("Worker unavailable: {err}"));
+                    return;
+                }
+                // Newly shown columns need the fields read for every repository.
+                if !before.covers(extras) {
+                    self.request_refresh(false);
+                }
+            }
+        }
     }
 
     pub fn toggle_help(&mut self) {
@@ -277,7 +2110,13 @@ impl App {
         self.repos
             .iter()
             .enumerate()
-            .filter(|(_, repo)| repo.name.to_lowercase().contains(&query_lower))
+            .filter(|(_, repo)| {
+                repo.name.to_lowercase().contains(&query_lower)
+                    || repo
+                        .manifest_groups()
+                        .iter()
+                        .any(|group| group.to_lowercase() == query_lower)
+            })
             .map(|(idx, _)| idx)
             .collect()
     }
@@ -311,9 +2150,10 @@ impl App {
     }
 }
 
-fn has_ahead_or_behind(value: &str) -> bool {
-    match parse_ahead_behind(value) {
-        Some((ahead, behind)) => ahead > 0 || behind > 0,
-        None => false,
+/// Checked out branch name, `None` on a detached or unknown HEAD.
+fn current_branch(repo: &RepoState) -> Option<&str> {
+    match repo.branch.as_str() {
+        NO_BRANCH | DETACHED_BRANCH | "unknown" => None,
+        branch => Some(branch),
     }
 }
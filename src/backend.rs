@@ -0,0 +1,109 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::branches::{git_branches, BranchInfo};
+use crate::git::{git_fetch, git_pull, git_push};
+use crate::status::{git_remote_url, git_status, RepoState, StatusExtras};
+
+/// The git operations the dashboard needs for every repository. Network
+/// operations default to the `git` binary, which already handles
+/// credentials, hooks and transports.
+pub trait GitBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Status of the working tree, reading only the costly fields in `extras`.
+    fn status(
+        &self,
+        path: &Path,
+        git_dir: &Path,
+        extras: StatusExtras,
+    ) -> Result<RepoState, String>;
+
+    /// URL of the `origin` remote as configured.
+    fn remote_url(&self, path: &Path) -> Result<String, String>;
+
+    /// Local branches, most recently committed first.
+    fn branches(&self, path: &Path) -> Result<Vec<BranchInfo>, String>;
+
+    fn fetch(&self, path: &Path) -> Result<String, String> {
+        git_fetch(path)
+    }
+
+    fn pull(&self, path: &Path) -> Result<String, String> {
+        git_pull(path)
+    }
+
+    fn push(&self, path: &Path) -> Result<String, String> {
+        git_push(path)
+    }
+}
+
+/// Runs the `git` binary for everything.
+pub struct CliBackend;
+
+impl GitBackend for CliBackend {
+    fn name(&self) -> &'static str {
+        "cli"
+    }
+
+    fn status(
+        &self,
+        path: &Path,
+        git_dir: &Path,
+        extras: StatusExtras,
+    ) -> Result<RepoState, String> {
+        git_status(path, git_dir, extras)
+    }
+
+    fn remote_url(&self, path: &Path) -> Result<String, String> {
+        git_remote_url(path)
+    }
+
+    fn branches(&self, path: &Path) -> Result<Vec<BranchInfo>, String> {
+        git_branches(path)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackendKind {
+    #[default]
+    Cli,
+    /// In-process gitoxide; only available when built with the `gix` feature.
+    Gix,
+}
+
+impl BackendKind {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "cli" => Ok(BackendKind::Cli),
+            "gix" => Ok(BackendKind::Gix),
+            _ => Err(format!("unknown backend \"{name}\" (expected cli or gix)")),
+        }
+    }
+
+    pub fn create(self) -> Result<Box<dyn GitBackend>, String> {
+        match self {
+            BackendKind::Cli => Ok(Box::new(CliBackend)),
+            #[cfg(feature = "gix")]
+            BackendKind::Gix => Ok(Box::new(crate::gix_backend::GixBackend)),
+            #[cfg(not(feature = "gix"))]
+            BackendKind::Gix => {
+                Err("the gix backend is not available; rebuild with `--features gix`".to_string())
+            }
+        }
+    }
+}
+
+static BACKEND: OnceLock<Box<dyn GitBackend>> = OnceLock::new();
+
+pub fn init_backend(backend: Box<dyn GitBackend>) {
+    let _ = BACKEND.set(backend);
+}
+
+/// The active backend, the `git` binary until `init_backend` is called.
+pub fn backend() -> &'static dyn GitBackend {
+    BACKEND.get_or_init(|| Box::new(CliBackend)).as_ref()
+}
@@ -0,0 +1,203 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::backend;
+use crate::git::{run_git, GIT_STATUS_TIMEOUT, GIT_TIMEOUT};
+use crate::status::{git_last_commit, RepoState, StatusExtras, StatusSnapshot, DETACHED_BRANCH};
+
+/// Refs and remotes of a bare repository, shown in place of working tree state.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct BareRepo {
+    pub branches: usize,
+    pub tags: usize,
+    pub remotes: Vec<String>,
+    /// Created with `git clone --mirror`, so fetches replace all refs.
+    pub mirror: bool,
+}
+
+impl BareRepo {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let refs = run_git(
+            path,
+            &[
+                "for-each-ref",
+                "--format=%(refname)",
+                "refs/heads",
+                "refs/tags",
+            ],
+            GIT_STATUS_TIMEOUT,
+        )?;
+        let refs = String::from_utf8_lossy(&refs);
+        let remotes = git_remotes(path)?;
+        let mirror = remotes.iter().any(|remote| {
+            run_git(
+                path,
+                &["config", "--bool", &format!("remote.{remote}.mirror")],
+                GIT_STATUS_TIMEOUT,
+            )
+            .is_ok_and(|output| String::from_utf8_lossy(&output).trim() == "true")
+        });
+        Ok(Self {
+            branches: refs
+                .lines()
+                .filter(|r| r.starts_with("refs/heads/"))
+                .count(),
+            tags: refs.lines().filter(|r| r.starts_with("refs/tags/")).count(),
+            remotes,
+            mirror,
+        })
+    }
+
+    /// "3 branches, 12 tags".
+    pub fn describe_refs(&self) -> String {
+        let branches = match self.branches {
+            1 => "1 branch".to_string(),
+            count => format!("{count} branches"),
+        };
+        let tags = match self.tags {
+            1 => "1 tag".to_string(),
+            count => format!("{count} tags"),
+        };
+        format!("{branches}, {tags}")
+    }
+}
+
+/// Status of a bare repository. There is no working tree, so only HEAD,
+/// remotes and refs are read. The size is always read, as the details
+/// show it next to the refs.
+pub fn git_bare_status(path: &Path, extras: StatusExtras) -> Result<RepoState, String> {
+    let head = fs::read_to_string(path.join("HEAD")).map_err(|err| err.to_string())?;
+    let branch = match head.trim().strip_prefix("ref: refs/heads/") {
+        Some(branch) => branch.to_string(),
+        None => DETACHED_BRANCH.to_string(),
+    };
+    let snapshot = StatusSnapshot {
+        branch,
+        remote_url: backend().remote_url(path).ok(),
+        last_commit: extras
+            .last_commit
+            .then(|| git_last_commit(path).ok())
+            .flatten(),
+        ..StatusSnapshot::default()
+    };
+    let extras = StatusExtras {
+        size: true,
+        ..extras
+    };
+    let mut state = snapshot.into_repo_state(path, path, extras);
+    state.bare = Some(BareRepo::read(path)?);
+    Ok(state)
+}
+
+/// Bring every ref of a bare repository up to date with its remotes. Remotes
+/// without a fetch refspec, as left by `git clone --bare`, get branches and
+/// tags mapped onto the local ones.
+pub fn git_update_mirror(path: &Path) -> Result<String, String> {
+    let remotes = git_remotes(path)?;
+    if remotes.is_empty() {
+        return Err("No remote configured for this repository".to_string());
+    }
+    for remote in &remotes {
+        let refspecs = run_git(
+            path,
+            &["config", "--get-all", &format!("remote.{remote}.fetch")],
+            GIT_STATUS_TIMEOUT,
+        )
+        .unwrap_or_default();
+        if refspecs.is_empty() {
+            run_git(
+                path,
+                &[
+                    "fetch",
+                    "--prune",
+                    remote,
+                    "+refs/heads/*:refs/heads/*",
+                    "+refs/tags/*:refs/tags/*",
+                ],
+                GIT_TIMEOUT,
+            )?;
+        } else {
+            run_git(path, &["fetch", "--prune", remote], GIT_TIMEOUT)?;
+        }
+    }
+    Ok(match remotes.len() {
+        1 => format!("updated from {}", remotes[0]),
+        count => format!("updated from {count} remotes"),
+    })
+}
+
+fn git_remotes(path: &Path) -> Result<Vec<String>, String> {
+    let output = run_git(path, &["remote"], GIT_STATUS_TIMEOUT)?;
+    Ok(String::from_utf8_lossy(&output)
+        .lines()
+        .map(str::to_string)
+        .filter(|remote| !remote.is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::test_support::{git, TempDir};
+
+    #[test]
+    fn test_describe_refs() {
+        let bare = BareRepo {
+            branches: 1,
+            tags: 12,
+            ..BareRepo::default()
+        };
+        assert_eq!(bare.describe_refs(), "1 branch, 12 tags");
+        let bare = BareRepo {
+            branches: 3,
+            tags: 1,
+            ..BareRepo::default()
+        };
+        assert_eq!(bare.describe_refs(), "3 branches, 1 tag");
+    }
+
+    #[test]
+    fn test_bare_status_and_mirror_update() {
+        let dir = TempDir::new("bare");
+        let upstream = dir.join("upstream");
+        fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "-q", "-b", "main"]);
+        git(
+            &upstream,
+            &["commit", "-q", "--allow-empty", "-m", "initial"],
+        );
+        git(&upstream, &["tag", "v1"]);
+        let upstream_arg = upstream.to_str().unwrap();
+        git(&dir, &["clone", "-q", "--bare", upstream_arg, "plain.git"]);
+        git(
+            &dir,
+            &["clone", "-q", "--mirror", upstream_arg, "mirror.git"],
+        );
+
+        let plain: PathBuf = dir.join("plain.git");
+        let state = git_bare_status(&plain, StatusExtras::ALL).unwrap();
+        assert_eq!(state.branch, "main");
+        assert!(!state.dirty && state.changes.is_empty());
+        let bare = state.bare.unwrap();
+        assert_eq!((bare.branches, bare.tags), (1, 1));
+        assert_eq!(bare.remotes, ["origin"]);
+        assert!(!bare.mirror);
+        assert!(
+            git_bare_status(&dir.join("mirror.git"), StatusExtras::ALL)
+                .unwrap()
+                .bare
+                .unwrap()
+                .mirror
+        );
+
+        git(&upstream, &["branch", "feature"]);
+        for path in [plain, dir.join("mirror.git")] {
+            assert_eq!(git_update_mirror(&path).unwrap(), "updated from origin");
+            assert_eq!(BareRepo::read(&path).unwrap().branches, 2);
+        }
+    }
+}
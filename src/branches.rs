@@ -0,0 +1,397 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::git::{has_tracked_changes, run_git, GIT_STATUS_TIMEOUT};
+
+const FIELD_SEPARATOR: char = '\u{1f}';
+const BRANCH_FORMAT: &str = "--format=%(HEAD)%1f%(refname:short)%1f%(upstream:short)%1f%(upstream:track,nobracket)%1f%(committerdate:relative)%1f%(committerdate:unix)";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BranchInfo {
+    pub name: String,
+    pub is_head: bool,
+    /// Short upstream name, empty when none is configured.
+    pub upstream: String,
+    pub ahead: u32,
+    pub behind: u32,
+    /// The configured upstream branch no longer exists on the remote.
+    pub gone: bool,
+    /// Fully merged into HEAD, so `git branch -d` will succeed.
+    pub merged: bool,
+    pub last_commit: String,
+    pub last_commit_unix: i64,
+}
+
+/// Local branch that can likely be deleted: its upstream is gone or it is
+/// fully merged into the repository's default branch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PruneCandidate {
+    pub branch: String,
+    pub gone: bool,
+    pub merged: bool,
+    pub last_commit: String,
+}
+
+/// What checking out a branch by name would do in one repository.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CheckoutPlan {
+    AlreadyOn,
+    /// A local branch with that name exists.
+    Switch,
+    /// Only a remote-tracking branch exists; a local tracking branch is created.
+    Track,
+    /// The repository lacks the branch; it can only be created.
+    Missing,
+    /// Tracked files have uncommitted changes.
+    Dirty,
+    Fail(String),
+}
+
+impl CheckoutPlan {
+    pub fn label(&self) -> String {
+        match self {
+            CheckoutPlan::AlreadyOn => "already on branch".to_string(),
+            CheckoutPlan::Switch => "switch to local branch".to_string(),
+            CheckoutPlan::Track => "track remote branch".to_string(),
+            CheckoutPlan::Missing => "branch missing".to_string(),
+            CheckoutPlan::Dirty => "dirty working tree".to_string(),
+            CheckoutPlan::Fail(err) => format!("would fail: {err}"),
+        }
+    }
+
+    /// Whether a checkout would run for this plan; missing branches only with `create`.
+    pub fn is_actionable(&self, create: bool) -> bool {
+        match self {
+            CheckoutPlan::Switch | CheckoutPlan::Track => true,
+            CheckoutPlan::Missing => create,
+            _ => false,
+        }
+    }
+}
+
+pub fn plan_checkout(path: &Path, branch: &str) -> CheckoutPlan {
+    if let Err(err) = run_git(
+        path,
+        &["check-ref-format", "--branch", branch],
+        GIT_STATUS_TIMEOUT,
+    ) {
+        return CheckoutPlan::Fail(err.trim_start_matches("fatal: ").to_string());
+    }
+
+    // Detached or unborn HEAD has no symbolic ref
+    let current = run_git(
+        path,
+        &["symbolic-ref", "--quiet", "--short", "HEAD"],
+        GIT_STATUS_TIMEOUT,
+    )
+    .map(|output| String::from_utf8_lossy(&output).trim().to_string())
+    .unwrap_or_default();
+    if current == branch {
+        return CheckoutPlan::AlreadyOn;
+    }
+
+    match has_tracked_changes(path) {
+        Ok(true) => return CheckoutPlan::Dirty,
+        Ok(false) => {}
+        Err(err) => return CheckoutPlan::Fail(err),
+    }
+
+    let refs = match branch_refs(path, branch) {
+        Ok(refs) => refs,
+        Err(err) => return CheckoutPlan::Fail(err),
+    };
+    if refs.contains(&format!("refs/heads/{branch}")) {
+        CheckoutPlan::Switch
+    } else if refs.len() == 1 {
+        CheckoutPlan::Track
+    } else if refs.len() > 1 {
+        CheckoutPlan::Fail("branch exists on several remotes".to_string())
+    } else {
+        CheckoutPlan::Missing
+    }
+}
+
+/// Local and remote-tracking refs named exactly `branch`. Ref patterns
+/// also match refs below them, like `origin/team/{branch}`, so each remote
+/// is looked up by its full ref name.
+pub fn branch_refs(path: &Path, branch: &str) -> Result<Vec<String>, String> {
+    let remotes = run_git(path, &["remote"], GIT_STATUS_TIMEOUT)?;
+    let mut wanted = vec![format!("refs/heads/{branch}")];
+    wanted.extend(
+        String::from_utf8_lossy(&remotes)
+            .lines()
+            .filter(|remote| !remote.is_empty())
+            .map(|remote| format!("refs/remotes/{remote}/{branch}")),
+    );
+    let mut args = vec!["for-each-ref", "--format=%(refname)"];
+    args.extend(wanted.iter().map(String::as_str));
+    let output = run_git(path, &args, GIT_STATUS_TIMEOUT)?;
+    Ok(String::from_utf8_lossy(&output)
+        .lines()
+        .filter(|name| wanted.iter().any(|wanted| wanted == name))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Default branch of the repository: the target of `origin/HEAD`, falling
+/// back to a local `main` or `master`.
+pub fn default_branch(path: &Path) -> Option<String> {
+    if let Ok(output) = run_git(
+        path,
+        &[
+            "symbolic-ref",
+            "--quiet",
+            "--short",
+            "refs/remotes/origin/HEAD",
+        ],
+        GIT_STATUS_TIMEOUT,
+    ) {
+        let target = String::from_utf8_lossy(&output).trim().to_string();
+        if let Some(branch) = target.strip_prefix("origin/") {
+            return Some(branch.to_string());
+        }
+    }
+    let output = run_git(
+        path,
+        &[
+            "for-each-ref",
+            "--format=%(refname:short)",
+            "refs/heads/main",
+            "refs/heads/master",
+        ],
+        GIT_STATUS_TIMEOUT,
+    )
+    .ok()?;
+    String::from_utf8_lossy(&output)
+        .lines()
+        .next()
+        .map(str::to_string)
+}
+
+/// Returns the default branch used for the merge check and the candidates,
+/// never including the checked out or default branch. Without a default
+/// branch the merge check is skipped and only gone branches are listed.
+pub fn prune_candidates(path: &Path) -> Result<(Option<String>, Vec<PruneCandidate>), String> {
+    let branches = git_branches(path)?;
+    let default = default_branch(path);
+
+    let mut merged = HashSet::new();
+    if let Some(default) = &default {
+        let merged_ref = if branches.iter().any(|branch| &branch.name == default) {
+            default.clone()
+        } else {
+            format!("origin/{default}")
+        };
+        let merged_arg = format!("--merged={merged_ref}");
+        let output = run_git(
+            path,
+            &[
+                "for-each-ref",
+                &merged_arg,
+                "--format=%(refname:short)",
+                "refs/heads",
+            ],
+            GIT_STATUS_TIMEOUT,
+        )?;
+        merged.extend(String::from_utf8_lossy(&output).lines().map(str::to_string));
+    }
+
+    let candidates = branches
+        .into_iter()
+        .filter(|branch| !branch.is_head && Some(&branch.name) != default.as_ref())
+        .filter_map(|branch| {
+            let is_merged = merged.contains(&branch.name);
+            (branch.gone || is_merged).then_some(PruneCandidate {
+                branch: branch.name,
+                gone: branch.gone,
+                merged: is_merged,
+                last_commit: branch.last_commit,
+            })
+        })
+        .collect();
+    Ok((default, candidates))
+}
+
+pub fn git_branches(path: &Path) -> Result<Vec<BranchInfo>, String> {
+    let output = run_git(
+        path,
+        &["for-each-ref", BRANCH_FORMAT, "refs/heads"],
+        GIT_STATUS_TIMEOUT,
+    )?;
+    let mut branches = parse_branches(&String::from_utf8_lossy(&output));
+
+    // Unborn HEAD has nothing to be merged into
+    if let Ok(output) = run_git(
+        path,
+        &[
+            "for-each-ref",
+            "--merged=HEAD",
+            "--format=%(refname:short)",
+            "refs/heads",
+        ],
+        GIT_STATUS_TIMEOUT,
+    ) {
+        let stdout = String::from_utf8_lossy(&output);
+        let merged: HashSet<&str> = stdout.lines().collect();
+        for branch in &mut branches {
+            branch.merged = merged.contains(branch.name.as_str());
+        }
+    }
+
+    sort_by_recency(&mut branches);
+    Ok(branches)
+}
+
+/// Most recently committed first.
+pub fn sort_by_recency(branches: &mut [BranchInfo]) {
+    branches.sort_by(|a, b| {
+        b.last_commit_unix
+            .cmp(&a.last_commit_unix)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+fn parse_branches(output: &str) -> Vec<BranchInfo> {
+    output.lines().filter_map(parse_branch_line).collect()
+}
+
+fn parse_branch_line(line: &str) -> Option<BranchInfo> {
+    let mut parts = line.split(FIELD_SEPARATOR);
+    let is_head = parts.next()? == "*";
+    let name = parts.next()?.to_string();
+    if name.is_empty() {
+        return None;
+    }
+    let upstream = parts.next()?.to_string();
+    let (ahead, behind, gone) = parse_track(parts.next()?);
+    let last_commit = parts.next()?.to_string();
+    let last_commit_unix = parts.next()?.parse().unwrap_or(0);
+    Some(BranchInfo {
+        name,
+        is_head,
+        upstream,
+        ahead,
+        behind,
+        gone,
+        merged: false,
+        last_commit,
+        last_commit_unix,
+    })
+}
+
+/// Parse `%(upstream:track,nobracket)`: "ahead 1, behind 2", "gone" or empty.
+fn parse_track(track: &str) -> (u32, u32, bool) {
+    if track == "gone" {
+        return (0, 0, true);
+    }
+    let mut ahead = 0;
+    let mut behind = 0;
+    for part in track.split(", ") {
+        if let Some(value) = part.strip_prefix("ahead ") {
+            ahead = value.parse().unwrap_or(0);
+        } else if let Some(value) = part.strip_prefix("behind ") {
+            behind = value.parse().unwrap_or(0);
+        }
+    }
+    (ahead, behind, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{git, TempDir};
+    use std::fs;
+
+    #[test]
+    fn test_parse_track() {
+        assert_eq!(parse_track(""), (0, 0, false));
+        assert_eq!(parse_track("gone"), (0, 0, true));
+        assert_eq!(parse_track("ahead 3"), (3, 0, false));
+        assert_eq!(parse_track("behind 2"), (0, 2, false));
+        assert_eq!(parse_track("ahead 1, behind 4"), (1, 4, false));
+    }
+
+    #[test]
+    fn test_checkout_plan_actionable() {
+        assert!(CheckoutPlan::Switch.is_actionable(false));
+        assert!(CheckoutPlan::Track.is_actionable(false));
+        assert!(!CheckoutPlan::Missing.is_actionable(false));
+        assert!(CheckoutPlan::Missing.is_actionable(true));
+        assert!(!CheckoutPlan::Dirty.is_actionable(true));
+        assert!(!CheckoutPlan::AlreadyOn.is_actionable(true));
+    }
+
+    #[test]
+    fn test_parse_branch_line() {
+        let line = "*\u{1f}main\u{1f}origin/main\u{1f}ahead 1\u{1f}2 hours ago\u{1f}1700000000";
+        let branch = parse_branch_line(line).unwrap();
+        assert!(branch.is_head);
+        assert_eq!(branch.name, "main");
+        assert_eq!(branch.upstream, "origin/main");
+        assert_eq!((branch.ahead, branch.behind, branch.gone), (1, 0, false));
+        assert_eq!(branch.last_commit, "2 hours ago");
+        assert_eq!(branch.last_commit_unix, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_parse_branch_line_without_upstream() {
+        let line = " \u{1f}feature/x\u{1f}\u{1f}\u{1f}3 days ago\u{1f}1690000000";
+        let branch = parse_branch_line(line).unwrap();
+        assert!(!branch.is_head);
+        assert!(branch.upstream.is_empty());
+        assert!(!branch.gone);
+    }
+
+    #[test]
+    fn test_plan_matches_remote_branch_exactly() {
+        let dir = TempDir::new("branch-refs");
+        let upstream = dir.join("upstream");
+        let clone = dir.join("clone");
+        fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "-q", "-b", "main"]);
+        git(
+            &upstream,
+            &["commit", "-q", "--allow-empty", "-m", "initial"],
+        );
+        git(&upstream, &["branch", "team/login"]);
+        git(&dir, &["clone", "-q", upstream.to_str().unwrap(), "clone"]);
+
+        assert_eq!(plan_checkout(&clone, "login"), CheckoutPlan::Missing);
+        assert_eq!(plan_checkout(&clone, "team/login"), CheckoutPlan::Track);
+        assert_eq!(
+            branch_refs(&clone, "main").unwrap(),
+            ["refs/heads/main", "refs/remotes/origin/main"]
+        );
+    }
+
+    #[test]
+    fn test_prune_gone_branches_without_default_branch() {
+        let dir = TempDir::new("prune-no-default");
+        let upstream = dir.join("upstream");
+        let clone = dir.join("clone");
+        fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "-q", "-b", "trunk"]);
+        git(
+            &upstream,
+            &["commit", "-q", "--allow-empty", "-m", "initial"],
+        );
+        git(&upstream, &["branch", "feature"]);
+        git(&dir, &["clone", "-q", upstream.to_str().unwrap(), "clone"]);
+        git(&clone, &["remote", "set-head", "origin", "--delete"]);
+        git(
+            &clone,
+            &["branch", "-q", "--track", "feature", "origin/feature"],
+        );
+        git(&clone, &["branch", "-q", "topic"]);
+        git(&upstream, &["branch", "-q", "-D", "feature"]);
+        git(&clone, &["fetch", "-q", "--prune"]);
+
+        let (default, candidates) = prune_candidates(&clone).unwrap();
+        assert_eq!(default, None);
+        let names: Vec<_> = candidates
+            .iter()
+            .map(|c| (c.branch.as_str(), c.gone, c.merged))
+            .collect();
+        assert_eq!(names, [("feature", true, false)]);
+    }
+}
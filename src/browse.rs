@@ -0,0 +1,264 @@
+use serde::Deserialize;
+
+use crate::status::NO_REMOTE;
+
+/// Hosting service whose URL layout is known.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Forge {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Gitea,
+}
+
+impl Forge {
+    /// Guess the forge from the host name, e.g. `gitlab.example.com`.
+    fn detect(host: &str) -> Forge {
+        if host.contains("gitlab") {
+            Forge::GitLab
+        } else if host.contains("bitbucket") {
+            Forge::Bitbucket
+        } else if host.contains("gitea") || host.contains("forgejo") || host == "codeberg.org" {
+            Forge::Gitea
+        } else {
+            Forge::GitHub
+        }
+    }
+
+    fn template(self, target: BrowseTarget) -> &'static str {
+        match (self, target) {
+            (_, BrowseTarget::Home) => "https://{host}/{repo}",
+            (Forge::GitHub, BrowseTarget::Branch) => "https://{host}/{repo}/tree/{branch}",
+            (Forge::GitHub, BrowseTarget::Compare) => {
+                "https://{host}/{repo}/compare/{default}...{branch}"
+            }
+            (Forge::GitHub, BrowseTarget::PullRequest) => {
+                "https://{host}/{repo}/compare/{default}...{branch}?expand=1"
+            }
+            (Forge::GitLab, BrowseTarget::Branch) => "https://{host}/{repo}/-/tree/{branch}",
+            (Forge::GitLab, BrowseTarget::Compare) => {
+                "https://{host}/{repo}/-/compare/{default}...{branch}"
+            }
+            (Forge::GitLab, BrowseTarget::PullRequest) => {
+                "https://{host}/{repo}/-/merge_requests/new?merge_request[source_branch]={branch}&merge_request[target_branch]={default}"
+            }
+            (Forge::Bitbucket, BrowseTarget::Branch) => "https://{host}/{repo}/src/{branch}",
+            (Forge::Bitbucket, BrowseTarget::Compare) => {
+                "https://{host}/{repo}/branches/compare/{branch}%0D{default}"
+            }
+            (Forge::Bitbucket, BrowseTarget::PullRequest) => {
+                "https://{host}/{repo}/pull-requests/new?source={branch}&dest={default}"
+            }
+            (Forge::Gitea, BrowseTarget::Branch) => "https://{host}/{repo}/src/branch/{branch}",
+            (Forge::Gitea, BrowseTarget::Compare | BrowseTarget::PullRequest) => {
+                "https://{host}/{repo}/compare/{default}...{branch}"
+            }
+        }
+    }
+}
+
+/// URL layout for a self-hosted or otherwise unrecognised host. Templates may
+/// use `{host}`, `{repo}`, `{branch}` and `{default}`; any left out come from `forge`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HostConfig {
+    pub host: String,
+    pub forge: Option<Forge>,
+    pub home: Option<String>,
+    pub branch: Option<String>,
+    pub compare: Option<String>,
+    pub pull_request: Option<String>,
+}
+
+impl HostConfig {
+    fn template(&self, target: BrowseTarget) -> Option<&str> {
+        match target {
+            BrowseTarget::Home => self.home.as_deref(),
+            BrowseTarget::Branch => self.branch.as_deref(),
+            BrowseTarget::Compare => self.compare.as_deref(),
+            BrowseTarget::PullRequest => self.pull_request.as_deref(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BrowseTarget {
+    Home,
+    Branch,
+    Compare,
+    PullRequest,
+}
+
+impl BrowseTarget {
+    pub fn key(self) -> char {
+        match self {
+            BrowseTarget::Home => 'h',
+            BrowseTarget::Branch => 'b',
+            BrowseTarget::Compare => 'c',
+            BrowseTarget::PullRequest => 'p',
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BrowseTarget::Home => "Repository home",
+            BrowseTarget::Branch => "Current branch",
+            BrowseTarget::Compare => "Compare with default branch",
+            BrowseTarget::PullRequest => "New pull request",
+        }
+    }
+}
+
+/// Web URLs for a repository, built from its simplified remote URL
+/// (`host/owner/repo`). Branch links are left out on a detached HEAD and
+/// compare/pull request links when the branch is the default branch.
+pub fn browse_links(
+    remote: &str,
+    branch: Option<&str>,
+    default_branch: Option<&str>,
+    hosts: &[HostConfig],
+) -> Result<Vec<(BrowseTarget, String)>, String> {
+    let (host, repo) = split_remote(remote).ok_or_else(|| {
+        if remote == NO_REMOTE {
+            "No remote configured for this repository".to_string()
+        } else {
+            format!("Cannot build a web URL for {remote}")
+        }
+    })?;
+    let custom = hosts.iter().find(|config| config.host == host);
+    let forge = custom
+        .and_then(|config| config.forge)
+        .unwrap_or_else(|| Forge::detect(host));
+
+    let mut targets = vec![BrowseTarget::Home];
+    if let Some(branch) = branch {
+        targets.push(BrowseTarget::Branch);
+        if default_branch.is_some_and(|default| default != branch) {
+            targets.push(BrowseTarget::Compare);
+            targets.push(BrowseTarget::PullRequest);
+        }
+    }
+
+    let links = targets
+        .into_iter()
+        .map(|target| {
+            let template = custom
+                .and_then(|config| config.template(target))
+                .unwrap_or_else(|| forge.template(target));
+            let url = template
+                .replace("{host}", host)
+                .replace("{repo}", repo)
+                .replace("{branch}", &encode_ref(branch.unwrap_or_default()))
+                .replace("{default}", &encode_ref(default_branch.unwrap_or_default()));
+            (target, url)
+        })
+        .collect();
+    Ok(links)
+}
+
+/// Split `host[:port]/owner/repo` into the host without port and the repository path.
+fn split_remote(remote: &str) -> Option<(&str, &str)> {
+    let remote = remote
+        .strip_prefix("http://")
+        .or_else(|| remote.strip_prefix("https://"))
+        .unwrap_or(remote);
+    let (host, repo) = remote.split_once('/')?;
+    let host = host.split(':').next().unwrap_or(host);
+    let repo = repo.trim_end_matches(".git").trim_end_matches('/');
+    if host.is_empty() || !repo.contains('/') || remote.contains("://") {
+        return None;
+    }
+    Some((host, repo))
+}
+
+/// Percent-encode a branch name for use in a URL, keeping `/` separators.
+fn encode_ref(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(links: &[(BrowseTarget, String)], target: BrowseTarget) -> &str {
+        &links.iter().find(|(t, _)| *t == target).unwrap().1
+    }
+
+    #[test]
+    fn test_github_links() {
+        let links =
+            browse_links("github.com/user/repo", Some("feat/x"), Some("main"), &[]).unwrap();
+        assert_eq!(
+            url(&links, BrowseTarget::Home),
+            "https://github.com/user/repo"
+        );
+        assert_eq!(
+            url(&links, BrowseTarget::Branch),
+            "https://github.com/user/repo/tree/feat/x"
+        );
+        assert_eq!(
+            url(&links, BrowseTarget::PullRequest),
+            "https://github.com/user/repo/compare/main...feat/x?expand=1"
+        );
+    }
+
+    #[test]
+    fn test_gitlab_detected_from_host() {
+        let links = browse_links(
+            "gitlab.example.com/group/sub/repo",
+            Some("fix"),
+            Some("main"),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            url(&links, BrowseTarget::Compare),
+            "https://gitlab.example.com/group/sub/repo/-/compare/main...fix"
+        );
+    }
+
+    #[test]
+    fn test_default_branch_has_no_compare() {
+        let links = browse_links("github.com/user/repo", Some("main"), Some("main"), &[]).unwrap();
+        assert_eq!(links.len(), 2);
+        let links = browse_links("github.com/user/repo", None, Some("main"), &[]).unwrap();
+        assert_eq!(links.len(), 1);
+    }
+
+    #[test]
+    fn test_custom_host_overrides_templates() {
+        let hosts = vec![HostConfig {
+            host: "git.corp".to_string(),
+            forge: Some(Forge::Gitea),
+            home: Some("https://code.corp/{repo}".to_string()),
+            branch: None,
+            compare: None,
+            pull_request: None,
+        }];
+        let links = browse_links("git.corp:2222/team/app", Some("a b"), None, &hosts).unwrap();
+        assert_eq!(
+            url(&links, BrowseTarget::Home),
+            "https://code.corp/team/app"
+        );
+        assert_eq!(
+            url(&links, BrowseTarget::Branch),
+            "https://git.corp/team/app/src/branch/a%20b"
+        );
+    }
+
+    #[test]
+    fn test_unusable_remotes() {
+        assert!(browse_links(NO_REMOTE, None, None, &[]).is_err());
+        assert!(browse_links("file:///srv/repo.git", None, None, &[]).is_err());
+    }
+}
@@ -0,0 +1,463 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::discovery::{common_dir, RepoRef, ScanTargets};
+use crate::logger::log_debug;
+use crate::status::{RepoState, StatusExtras};
+
+const CACHE_VERSION: u32 = 3;
+
+pub const STATUS_CACHE_FILE: &str = "status.toml";
+pub const REPO_LIST_FILE: &str = "repos.toml";
+
+/// Working trees with more entries than this are always re-read; walking them
+/// would cost about as much as running status.
+const WORKTREE_ENTRY_LIMIT: usize = 20_000;
+
+/// File systems stamp mtimes from a coarse clock (FAT even rounds to two
+/// seconds), so anything modified this close to a status read counts as a
+/// change.
+const MTIME_SLACK: Duration = Duration::from_secs(2);
+
+/// Modification times of the git directory files that change whenever HEAD,
+/// the index or any ref moves, keyed by path relative to the git directory.
+pub type Stamps = BTreeMap<String, i64>;
+
+/// Repository status from earlier runs, persisted so startup can show it
+/// before a scan finishes and so unchanged repositories skip `git status`.
+pub struct StatusCache {
+    path: Option<PathBuf>,
+    entries: BTreeMap<PathBuf, CacheEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CacheEntry {
+    stamps: Stamps,
+    /// When status was read; the working tree must not have changed since.
+    checked_at: SystemTime,
+    /// Costly fields that were read along with the status.
+    extras: StatusExtras,
+    state: RepoState,
+}
+
+impl CacheEntry {
+    /// Record `state`, read at `checked_at` with the git directory at `stamps`.
+    /// Stamps taken before reading make any concurrent change invalidate the
+    /// entry on the next lookup.
+    pub fn new(
+        stamps: Stamps,
+        checked_at: SystemTime,
+        extras: StatusExtras,
+        state: RepoState,
+    ) -> Self {
+        Self {
+            stamps,
+            checked_at,
+            extras,
+            state,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct CacheFile {
+    version: u32,
+    #[serde(default)]
+    repos: Vec<CacheEntry>,
+}
+
+impl StatusCache {
+    /// Read the cache file; a missing, outdated or unreadable file gives an
+    /// empty cache. `None` keeps the cache in memory only.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_deref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| match toml::from_str::<CacheFile>(&contents) {
+                Ok(file) if file.version == CACHE_VERSION => Some(file.repos),
+                Ok(_) => None,
+                Err(err) => {
+                    log_debug(&format!("Ignoring status cache: {}", err.message()));
+                    None
+                }
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| (entry.state.git_dir.clone(), entry))
+            .collect();
+        Self { path, entries }
+    }
+
+    /// Cached states of repositories below the roots of `targets` or
+    /// listed in it, marked stale.
+    pub fn states_in(&self, targets: &ScanTargets) -> Vec<RepoState> {
+        self.entries
+            .values()
+            .filter(|entry| targets.contains(&entry.state.path))
+            .map(|entry| RepoState {
+                stale: true,
+                ..entry.state.clone()
+            })
+            .collect()
+    }
+
+    /// The cached state if neither the git directory nor the working tree
+    /// changed since it was read.
+    /// Entries read without some of the wanted `extras` are missed.
+    pub fn lookup(&self, repo: &RepoRef, extras: StatusExtras) -> Option<RepoState> {
+        let entry = self.entries.get(&repo.git_dir)?;
+        if entry.state.path != repo.path
+            || !entry.extras.covers(extras)
+            || entry.stamps != git_dir_stamps(&repo.git_dir)
+            || worktree_changed_since(&repo.path, entry.checked_at)
+        {
+            return None;
+        }
+        Some(entry.state.clone())
+    }
+
+    pub fn insert(&mut self, entry: CacheEntry) {
+        self.entries.insert(entry.state.git_dir.clone(), entry);
+    }
+
+    /// Drop the entry of `git_dir` so its status is read again.
+    pub fn remove(&mut self, git_dir: &Path) {
+        self.entries.remove(git_dir);
+    }
+
+    /// Write the cache, dropping repositories that no longer exist.
+    pub fn save(&mut self) -> Result<(), String> {
+        self.entries.retain(|git_dir, _| git_dir.exists());
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            repos: self.entries.values().cloned().collect(),
+        };
+        write_atomic(
+            path,
+            &toml::to_string(&file).map_err(|err| err.to_string())?,
+        )
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct RepoListFile {
+    version: u32,
+    #[serde(default)]
+    roots: Vec<RootRepos>,
+}
+
+/// Repositories discovered below one scan root.
+#[derive(Deserialize, Serialize)]
+struct RootRepos {
+    root: PathBuf,
+    repos: Vec<RepoRef>,
+}
+
+fn read_repo_lists(path: &Path) -> RepoListFile {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| toml::from_str::<RepoListFile>(&contents).ok())
+        .filter(|file| file.version == CACHE_VERSION)
+        .unwrap_or_default()
+}
+
+/// Repositories found below `root` by the last discovery, skipping any that
+/// have since disappeared.
+pub fn load_repo_list(path: Option<&Path>, root: &Path) -> Vec<RepoRef> {
+    let Some(path) = path else {
+        return Vec::new();
+    };
+    read_repo_lists(path)
+        .roots
+        .into_iter()
+        .find(|entry| entry.root == root)
+        .map(|entry| entry.repos)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|repo| repo.git_dir.exists())
+        .collect()
+}
+
+/// Replace the remembered repositories of `root`, keeping other roots.
+pub fn save_repo_list(path: Option<&Path>, root: &Path, repos: &[RepoRef]) -> Result<(), String> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let mut file = read_repo_lists(path);
+    file.version = CACHE_VERSION;
+    file.roots.retain(|entry| entry.root != root);
+    file.roots.push(RootRepos {
+        root: root.to_path_buf(),
+        repos: repos.to_vec(),
+    });
+    write_atomic(
+        path,
+        &toml::to_string(&file).map_err(|err| err.to_string())?,
+    )
+}
+
+fn write_atomic(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    // Write then rename so a crash never leaves a truncated cache
+    let temp = path.with_extension("toml.tmp");
+    fs::write(&temp, contents).map_err(|err| err.to_string())?;
+    fs::rename(&temp, path).map_err(|err| err.to_string())
+}
+
+/// `$XDG_CACHE_HOME/git-dash/<name>`, falling back to `~/.cache`.
+pub fn cache_path(name: &str) -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("git-dash").join(name))
+}
+
+/// Stamps of `HEAD`, the index, `FETCH_HEAD`, `packed-refs` and every
+/// directory below `refs`. Refs are updated by renaming a lock file into
+/// place, which bumps the mtime of the directory holding them.
+pub fn git_dir_stamps(git_dir: &Path) -> Stamps {
+    let mut stamps = Stamps::new();
+    for name in ["HEAD", "index", "FETCH_HEAD"] {
+        insert_stamp(&mut stamps, name.to_string(), &git_dir.join(name));
+    }
+    let common = common_dir(git_dir);
+    // Upstream and remote URL live in the config
+    insert_stamp(&mut stamps, "config".to_string(), &common.join("config"));
+    insert_stamp(
+        &mut stamps,
+        "packed-refs".to_string(),
+        &common.join("packed-refs"),
+    );
+    let mut stack = vec![PathBuf::from("refs")];
+    while let Some(relative) = stack.pop() {
+        let dir = common.join(&relative);
+        insert_stamp(&mut stamps, relative.to_string_lossy().into_owned(), &dir);
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                stack.push(relative.join(entry.file_name()));
+            }
+        }
+    }
+    stamps
+}
+
+fn insert_stamp(stamps: &mut Stamps, key: String, path: &Path) {
+    if let Some(nanos) = fs::symlink_metadata(path)
+        .ok()
+        .and_then(|meta| mtime_nanos(&meta))
+    {
+        stamps.insert(key, nanos);
+    }
+}
+
+fn mtime_nanos(metadata: &fs::Metadata) -> Option<i64> {
+    let modified = metadata.modified().ok()?;
+    let age = modified.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+    i64::try_from(age.as_nanos()).ok()
+}
+
+/// Whether any file or directory of the working tree was modified at or
+/// after `since`. Skips `.git` and nested repositories; gives up (reporting
+/// a change) on unreadable directories or very large trees.
+fn worktree_changed_since(path: &Path, since: SystemTime) -> bool {
+    let since = since.checked_sub(MTIME_SLACK).unwrap_or(since);
+    let changed = |metadata: &fs::Metadata| metadata.modified().map_or(true, |time| time >= since);
+    let mut stack = vec![path.to_path_buf()];
+    let mut seen = 0usize;
+    while let Some(dir) = stack.pop() {
+        match fs::symlink_metadata(&dir) {
+            Ok(metadata) if !changed(&metadata) => {}
+            _ => return true,
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return true;
+        };
+        for entry in entries.flatten() {
+            seen += 1;
+            if seen > WORKTREE_ENTRY_LIMIT {
+                return true;
+            }
+            if entry.file_name() == ".git" {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                return true;
+            };
+            if metadata.is_dir() {
+                // A nested repository's contents are not part of this status
+                if !entry.path().join(".git").exists() {
+                    stack.push(entry.path());
+                }
+            } else if changed(&metadata) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::NO_UPSTREAM;
+    use crate::test_support::{git, TempDir};
+
+    fn state(repo: &RepoRef) -> RepoState {
+        RepoState {
+            path: repo.path.clone(),
+            git_dir: repo.git_dir.clone(),
+            name: "repo".to_string(),
+            branch: "main".to_string(),
+            dirty: true,
+            ahead_behind: "+1/-0".to_string(),
+            change_summary: "M:1".to_string(),
+            remote_url: "github.com/a/b".to_string(),
+            origin_url: Some("git@github.com:a/b.git".to_string()),
+            last_fetch: None,
+            changes: vec![("M".to_string(), "a.txt".to_string())],
+            error_message: None,
+            stash_count: 0,
+            last_commit: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            upstream: NO_UPSTREAM.to_string(),
+            size: Some(1024),
+            submodule: None,
+            worktree_of: None,
+            bare: None,
+            manifest: None,
+            stale: false,
+        }
+    }
+
+    /// A committed repository with its cache entry recorded a moment ago.
+    fn cached_repo(dir: &Path) -> (RepoRef, StatusCache) {
+        let path = dir.join("repo");
+        fs::create_dir_all(&path).unwrap();
+        git(&path, &["init", "-q", "-b", "main"]);
+        fs::write(path.join("a.txt"), "a\n").unwrap();
+        git(&path, &["add", "."]);
+        git(&path, &["commit", "-q", "-m", "initial"]);
+        let repo = RepoRef::new(path.clone(), path.join(".git"));
+        // Past the slack window of the files written above
+        let checked_at = SystemTime::now() + MTIME_SLACK * 2;
+        let mut cache = StatusCache::load(Some(dir.join("status.toml")));
+        let extras = StatusExtras {
+            stashes: true,
+            ..StatusExtras::default()
+        };
+        let entry = CacheEntry::new(
+            git_dir_stamps(&repo.git_dir),
+            checked_at,
+            extras,
+            state(&repo),
+        );
+        cache.insert(entry);
+        (repo, cache)
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = TempDir::new("cache-round-trip");
+        let (repo, mut cache) = cached_repo(&dir);
+        cache.save().unwrap();
+
+        let loaded = StatusCache::load(Some(dir.join("status.toml")));
+        let cached = loaded
+            .lookup(&repo, StatusExtras::default())
+            .expect("unchanged repo is cached");
+        assert_eq!(cached.changes, state(&repo).changes);
+        assert_eq!(cached.last_commit, state(&repo).last_commit);
+        assert!(!cached.stale);
+        // Read without the size, so a table showing it misses
+        assert!(loaded.lookup(&repo, StatusExtras::ALL).is_none());
+
+        let stale = loaded.states_in(&ScanTargets::single(dir.to_path_buf()));
+        assert_eq!(stale.len(), 1);
+        assert!(stale[0].stale);
+        let elsewhere = ScanTargets::single(dir.join("elsewhere"));
+        assert!(loaded.states_in(&elsewhere).is_empty());
+        let listed = ScanTargets {
+            repos: vec![repo.path.clone()],
+            ..elsewhere
+        };
+        assert_eq!(loaded.states_in(&listed).len(), 1);
+    }
+
+    #[test]
+    fn test_worktree_edit_invalidates() {
+        let dir = TempDir::new("cache-worktree");
+        let (repo, cache) = cached_repo(&dir);
+        assert!(cache.lookup(&repo, StatusExtras::default()).is_some());
+        let file = fs::File::options()
+            .write(true)
+            .open(repo.path.join("a.txt"))
+            .unwrap();
+        file.set_modified(SystemTime::now() + MTIME_SLACK * 3)
+            .unwrap();
+        assert!(cache.lookup(&repo, StatusExtras::default()).is_none());
+    }
+
+    #[test]
+    fn test_git_dir_change_invalidates() {
+        let dir = TempDir::new("cache-git-dir");
+        let (repo, cache) = cached_repo(&dir);
+        // Step past the coarse clock tick of the stamps
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        git(&repo.path, &["branch", "topic"]);
+        assert!(cache.lookup(&repo, StatusExtras::default()).is_none());
+    }
+
+    #[test]
+    fn test_config_change_invalidates() {
+        let dir = TempDir::new("cache-config");
+        let (repo, cache) = cached_repo(&dir);
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        git(
+            &repo.path,
+            &["remote", "add", "origin", "https://example.com/a.git"],
+        );
+        assert!(cache.lookup(&repo, StatusExtras::default()).is_none());
+    }
+
+    #[test]
+    fn test_repo_list_per_root() {
+        let dir = TempDir::new("cache-repo-list");
+        let path = dir.join("repos.toml");
+        let repo = |name: &str| RepoRef::new(dir.join(name), dir.join(name).join(".git"));
+        for name in ["a", "b"] {
+            fs::create_dir_all(repo(name).git_dir).unwrap();
+        }
+        save_repo_list(Some(&path), &dir, &[repo("a"), repo("b"), repo("gone")]).unwrap();
+        save_repo_list(Some(&path), Path::new("/elsewhere"), &[repo("a")]).unwrap();
+
+        assert_eq!(load_repo_list(Some(&path), &dir), [repo("a"), repo("b")]);
+        assert_eq!(
+            load_repo_list(Some(&path), Path::new("/elsewhere")),
+            [repo("a")]
+        );
+        assert!(load_repo_list(Some(&path), Path::new("/unknown")).is_empty());
+        assert!(load_repo_list(None, &dir).is_empty());
+    }
+
+    #[test]
+    fn test_outdated_or_corrupt_cache_is_empty() {
+        let dir = TempDir::new("cache-corrupt");
+        let path = dir.join("status.toml");
+        fs::write(&path, "version = 0\n").unwrap();
+        assert!(StatusCache::load(Some(path.clone())).entries.is_empty());
+        fs::write(&path, "not toml [").unwrap();
+        assert!(StatusCache::load(Some(path)).entries.is_empty());
+    }
+}
@@ -0,0 +1,659 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+
+use crate::status::{
+    parse_ahead_behind, RepoState, StatusExtras, NO_BRANCH, NO_REMOTE, NO_UPSTREAM,
+};
+
+/// Gap between table columns, matching the table's column spacing.
+pub const COLUMN_SPACING: u16 = 1;
+
+/// A column of the repository table.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    Name,
+    Branch,
+    Dirty,
+    AheadBehind,
+    Changes,
+    Remote,
+    LastFetch,
+    Stashes,
+    LastCommit,
+    Upstream,
+    Size,
+    Path,
+}
+
+impl Column {
+    pub const ALL: [Column; 12] = [
+        Column::Name,
+        Column::Branch,
+        Column::Dirty,
+        Column::AheadBehind,
+        Column::Changes,
+        Column::Remote,
+        Column::LastFetch,
+        Column::Stashes,
+        Column::LastCommit,
+        Column::Upstream,
+        Column::Size,
+        Column::Path,
+    ];
+
+    /// Columns shown when the config does not list any.
+    pub const DEFAULT: [Column; 7] = [
+        Column::Name,
+        Column::Branch,
+        Column::Dirty,
+        Column::AheadBehind,
+        Column::Changes,
+        Column::Remote,
+        Column::LastFetch,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Name => "Repository",
+            Column::Branch => "Branch",
+            Column::Dirty => "Dirty",
+            Column::AheadBehind => "Ahead/Behind",
+            Column::Changes => "Changes",
+            Column::Remote => "Remote",
+            Column::LastFetch => "Last Fetch",
+            Column::Stashes => "Stashes",
+            Column::LastCommit => "Last Commit",
+            Column::Upstream => "Upstream",
+            Column::Size => "Size",
+            Column::Path => "Path",
+        }
+    }
+
+    /// Narrowest width the column is shown at without a configured width.
+    fn min_width(self) -> u16 {
+        match self {
+            Column::Name => 12,
+            Column::Branch => 8,
+            Column::Dirty => 7,
+            Column::AheadBehind => 12,
+            Column::Changes => 10,
+            Column::Remote => 12,
+            Column::LastFetch => 10,
+            Column::Stashes => 7,
+            Column::LastCommit => 11,
+            Column::Upstream => 10,
+            Column::Size => 6,
+            Column::Path => 12,
+        }
+    }
+
+    /// Share of the space left over after every column got its minimum width.
+    fn weight(self) -> u16 {
+        match self {
+            Column::Name => 18,
+            Column::Branch => 10,
+            Column::Dirty => 8,
+            Column::AheadBehind => 12,
+            Column::Changes => 22,
+            Column::Remote => 20,
+            Column::LastFetch => 10,
+            Column::Stashes => 6,
+            Column::LastCommit => 10,
+            Column::Upstream => 14,
+            Column::Size => 8,
+            Column::Path => 20,
+        }
+    }
+
+    /// Columns with the lowest priority are hidden first on narrow terminals.
+    fn priority(self) -> u8 {
+        match self {
+            Column::Name => 100,
+            Column::Dirty => 90,
+            Column::Branch => 80,
+            Column::AheadBehind => 70,
+            Column::Changes => 60,
+            Column::LastCommit => 45,
+            Column::LastFetch => 40,
+            Column::Path => 35,
+            Column::Remote => 30,
+            Column::Upstream => 25,
+            Column::Stashes => 20,
+            Column::Size => 10,
+        }
+    }
+}
+
+impl Column {
+    /// Columns whose most useful order puts large values first, e.g. the
+    /// dirty or most recently fetched repositories.
+    fn descending_first(self) -> bool {
+        matches!(
+            self,
+            Column::Dirty
+                | Column::AheadBehind
+                | Column::Changes
+                | Column::LastFetch
+                | Column::Stashes
+                | Column::LastCommit
+                | Column::Size
+        )
+    }
+
+    /// Value the column sorts by; `None` for missing values.
+    fn sort_key(self, repo: &RepoState) -> Option<SortKey> {
+        let text = |value: &str| Some(SortKey::Text(value.to_lowercase()));
+        let time = |value: Option<SystemTime>| {
+            value
+                .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|age| SortKey::Number(age.as_secs()))
+        };
+        match self {
+            Column::Name => text(&repo.name),
+            Column::Branch if repo.branch == NO_BRANCH => None,
+            Column::Branch => text(&repo.branch),
+            Column::Dirty => Some(SortKey::Number(u64::from(repo.dirty))),
+            Column::AheadBehind => parse_ahead_behind(&repo.ahead_behind)
+                .map(|(ahead, behind)| SortKey::Number(u64::from(ahead) + u64::from(behind))),
+            Column::Changes if repo.error_message.is_some() => None,
+            Column::Changes => Some(SortKey::Number(repo.changes.len() as u64)),
+            Column::Remote if repo.remote_url == NO_REMOTE => None,
+            Column::Remote => text(&repo.remote_url),
+            Column::LastFetch => time(repo.last_fetch),
+            Column::Stashes => Some(SortKey::Number(repo.stash_count as u64)),
+            Column::LastCommit => time(repo.last_commit),
+            Column::Upstream if repo.upstream == NO_UPSTREAM => None,
+            Column::Upstream => text(&repo.upstream),
+            Column::Size => repo.size.map(SortKey::Number),
+            Column::Path => text(&repo.path.to_string_lossy()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Number(u64),
+    Text(String),
+}
+
+/// Column the repository table is sorted by, and in which direction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SortOrder {
+    pub column: Column,
+    pub descending: bool,
+}
+
+impl SortOrder {
+    /// Sort by `column` in its most useful direction.
+    pub fn new(column: Column) -> Self {
+        Self {
+            column,
+            descending: column.descending_first(),
+        }
+    }
+
+    pub fn reversed(self) -> Self {
+        Self {
+            descending: !self.descending,
+            ..self
+        }
+    }
+
+    /// Arrow shown after the sorted column's title.
+    pub fn indicator(self) -> &'static str {
+        if self.descending {
+            "▼"
+        } else {
+            "▲"
+        }
+    }
+
+    /// Order of two repositories. Missing values go last in either
+    /// direction; ties are broken by name and then path so the order is
+    /// total and re-sorting never shuffles equal rows.
+    pub fn compare(self, a: &RepoState, b: &RepoState) -> Ordering {
+        let primary = match (self.column.sort_key(a), self.column.sort_key(b)) {
+            (Some(a), Some(b)) if self.descending => b.cmp(&a),
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        primary
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+            .then_with(|| a.path.cmp(&b.path))
+    }
+}
+
+/// Move submodules and linked worktrees right below the repository they
+/// belong to, keeping the sorted order among the children of one repository.
+pub fn group_children(repos: &mut Vec<RepoState>) {
+    let present: HashSet<PathBuf> = repos.iter().map(|repo| repo.path.clone()).collect();
+    let is_child = |repo: &RepoState| repo.parent().is_some_and(|parent| present.contains(parent));
+    if !repos.iter().any(is_child) {
+        return;
+    }
+    let (mut children, top): (Vec<_>, Vec<_>) = repos.drain(..).partition(is_child);
+    for repo in top {
+        place_with_children(repo, &mut children, repos);
+    }
+}
+
+fn place_with_children(repo: RepoState, children: &mut Vec<RepoState>, out: &mut Vec<RepoState>) {
+    let path = repo.path.clone();
+    out.push(repo);
+    let (own, rest): (Vec<_>, Vec<_>) = children
+        .drain(..)
+        .partition(|child| child.parent() == Some(path.as_path()));
+    *children = rest;
+    for child in own {
+        place_with_children(child, children, out);
+    }
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        SortOrder::new(Column::Name)
+    }
+}
+
+/// A column entry in the config: a column name, or a table with the name
+/// and a fixed `width` or a `min` width.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ColumnEntry {
+    Name(Column),
+    Full {
+        column: Column,
+        width: Option<u16>,
+        min: Option<u16>,
+    },
+}
+
+/// A visible column and its width constraints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColumnSpec {
+    pub column: Column,
+    /// Fixed width; the column neither grows nor shrinks.
+    pub width: Option<u16>,
+    pub min: u16,
+}
+
+impl ColumnSpec {
+    pub fn new(column: Column) -> Self {
+        Self {
+            column,
+            width: None,
+            min: column.min_width(),
+        }
+    }
+
+    fn min_width(&self) -> u16 {
+        self.width.unwrap_or(self.min)
+    }
+}
+
+impl From<&ColumnEntry> for ColumnSpec {
+    fn from(entry: &ColumnEntry) -> Self {
+        match entry {
+            ColumnEntry::Name(column) => ColumnSpec::new(*column),
+            ColumnEntry::Full { column, width, min } => ColumnSpec {
+                column: *column,
+                width: *width,
+                min: min.unwrap_or_else(|| column.min_width()),
+            },
+        }
+    }
+}
+
+pub fn default_columns() -> Vec<ColumnSpec> {
+    Column::DEFAULT.into_iter().map(ColumnSpec::new).collect()
+}
+
+/// Costly status fields that the `columns` display and so must be read.
+pub fn status_extras(columns: &[ColumnSpec]) -> StatusExtras {
+    let shown = |wanted| columns.iter().any(|spec| spec.column == wanted);
+    StatusExtras {
+        size: shown(Column::Size),
+        stashes: shown(Column::Stashes),
+        last_commit: shown(Column::LastCommit),
+    }
+}
+
+/// Columns from the config, or the default set when none are listed.
+pub fn column_specs(entries: &[ColumnEntry]) -> Result<Vec<ColumnSpec>, String> {
+    if entries.is_empty() {
+        return Ok(default_columns());
+    }
+    let specs: Vec<ColumnSpec> = entries.iter().map(ColumnSpec::from).collect();
+    for (index, spec) in specs.iter().enumerate() {
+        if specs[..index]
+            .iter()
+            .any(|other| other.column == spec.column)
+        {
+            return Err(format!(
+                "column \"{}\" is listed twice",
+                spec.column.title()
+            ));
+        }
+        if spec.width == Some(0) {
+            return Err(format!("column \"{}\" has zero width", spec.column.title()));
+        }
+    }
+    Ok(specs)
+}
+
+/// Fit the columns into `available` cells: hide the lowest priority columns
+/// until the minimum widths fit, then share the rest by weight among the
+/// columns without a fixed width. Returns each shown column with its width.
+pub fn layout_columns(specs: &[ColumnSpec], available: u16) -> Vec<(Column, u16)> {
+    let mut shown: Vec<ColumnSpec> = specs.to_vec();
+    let needed = |shown: &[ColumnSpec]| -> u32 {
+        let widths: u32 = shown.iter().map(|spec| u32::from(spec.min_width())).sum();
+        widths + u32::from(COLUMN_SPACING) * shown.len().saturating_sub(1) as u32
+    };
+    while shown.len() > 1 && needed(&shown) > u32::from(available) {
+        let lowest = shown
+            .iter()
+            .enumerate()
+            .min_by_key(|(index, spec)| (spec.column.priority(), usize::MAX - index))
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+        shown.remove(lowest);
+    }
+
+    let spare = u32::from(available).saturating_sub(needed(&shown));
+    let total_weight: u32 = shown
+        .iter()
+        .filter(|spec| spec.width.is_none())
+        .map(|spec| u32::from(spec.column.weight()))
+        .sum();
+    let mut widths: Vec<(Column, u16)> = shown
+        .iter()
+        .map(|spec| {
+            let extra = match spec.width {
+                Some(_) => 0,
+                None if total_weight > 0 => spare * u32::from(spec.column.weight()) / total_weight,
+                None => 0,
+            };
+            (spec.column, spec.min_width().saturating_add(extra as u16))
+        })
+        .collect();
+
+    // Rounding leftovers go to the first growable column.
+    let used: u32 = widths
+        .iter()
+        .map(|(_, width)| u32::from(*width))
+        .sum::<u32>()
+        + u32::from(COLUMN_SPACING) * widths.len().saturating_sub(1) as u32;
+    if let Some(index) = shown.iter().position(|spec| spec.width.is_none()) {
+        let leftover = u32::from(available).saturating_sub(used) as u16;
+        widths[index].1 = widths[index].1.saturating_add(leftover);
+    }
+    widths
+}
+
+/// Column picker dialog: every column, shown ones first in display order.
+pub struct ColumnPicker {
+    pub entries: Vec<(ColumnSpec, bool)>,
+    pub selected: usize,
+    /// Widths the columns were last drawn with, the starting point for resizing.
+    rendered: Vec<(Column, u16)>,
+}
+
+impl ColumnPicker {
+    pub fn new(specs: &[ColumnSpec], rendered: Vec<(Column, u16)>) -> Self {
+        let mut entries: Vec<(ColumnSpec, bool)> = specs.iter().map(|spec| (*spec, true)).collect();
+        for column in Column::ALL {
+            if !specs.iter().any(|spec| spec.column == column) {
+                entries.push((ColumnSpec::new(column), false));
+            }
+        }
+        Self {
+            entries,
+            selected: 0,
+            rendered,
+        }
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.entries.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Show or hide the highlighted column. The name column always stays.
+    pub fn toggle(&mut self) {
+        if let Some((spec, shown)) = self.entries.get_mut(self.selected) {
+            if spec.column != Column::Name {
+                *shown = !*shown;
+            }
+        }
+    }
+
+    /// Move the highlighted column earlier or later in the display order.
+    pub fn move_entry(&mut self, delta: isize) {
+        let target = self.selected.saturating_add_signed(delta);
+        if target < self.entries.len() && target != self.selected {
+            self.entries.swap(self.selected, target);
+            self.selected = target;
+        }
+    }
+
+    /// Give the highlighted column a fixed width, starting from its drawn width.
+    pub fn resize(&mut self, delta: i16) {
+        if let Some((spec, _)) = self.entries.get_mut(self.selected) {
+            let drawn = self
+                .rendered
+                .iter()
+                .find(|(column, _)| *column == spec.column)
+                .map(|(_, width)| *width);
+            let current = spec.width.or(drawn).unwrap_or(spec.min);
+            let width = current.saturating_add_signed(delta).max(1);
+            spec.width = Some(width);
+        }
+    }
+
+    /// Let the highlighted column size itself again.
+    pub fn reset_width(&mut self) {
+        if let Some((spec, _)) = self.entries.get_mut(self.selected) {
+            spec.width = None;
+        }
+    }
+
+    pub fn specs(&self) -> Vec<ColumnSpec> {
+        self.entries
+            .iter()
+            .filter(|(_, shown)| *shown)
+            .map(|(spec, _)| *spec)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::status::NO_AHEAD_BEHIND;
+    use crate::submodule::Submodule;
+
+    fn repo(name: &str) -> RepoState {
+        RepoState {
+            path: PathBuf::from("/src").join(name),
+            git_dir: PathBuf::from("/src").join(name).join(".git"),
+            name: name.to_string(),
+            branch: "main".to_string(),
+            dirty: false,
+            ahead_behind: NO_AHEAD_BEHIND.to_string(),
+            change_summary: String::new(),
+            remote_url: NO_REMOTE.to_string(),
+            origin_url: None,
+            last_fetch: None,
+            changes: Vec::new(),
+            error_message: None,
+            stash_count: 0,
+            last_commit: None,
+            upstream: NO_UPSTREAM.to_string(),
+            size: None,
+            submodule: None,
+            worktree_of: None,
+            bare: None,
+            manifest: None,
+            stale: false,
+        }
+    }
+
+    fn sorted(repos: &mut [RepoState], order: SortOrder) -> Vec<&str> {
+        repos.sort_by(|a, b| order.compare(a, b));
+        repos.iter().map(|repo| repo.name.as_str()).collect()
+    }
+
+    fn defaults() -> Vec<ColumnSpec> {
+        default_columns()
+    }
+
+    #[test]
+    fn test_layout_fills_available_width() {
+        let layout = layout_columns(&defaults(), 120);
+        assert_eq!(layout.len(), 7);
+        let used: u16 = layout.iter().map(|(_, width)| width).sum::<u16>() + 6;
+        assert_eq!(used, 120);
+    }
+
+    #[test]
+    fn test_layout_collapses_low_priority_columns() {
+        let layout = layout_columns(&defaults(), 50);
+        let columns: Vec<Column> = layout.iter().map(|(column, _)| *column).collect();
+        assert_eq!(
+            columns,
+            vec![
+                Column::Name,
+                Column::Branch,
+                Column::Dirty,
+                Column::AheadBehind
+            ]
+        );
+        assert_eq!(layout_columns(&defaults(), 5).len(), 1);
+    }
+
+    #[test]
+    fn test_fixed_width_does_not_grow() {
+        let specs = vec![
+            ColumnSpec {
+                column: Column::Name,
+                width: Some(20),
+                min: 12,
+            },
+            ColumnSpec::new(Column::Path),
+        ];
+        assert_eq!(
+            layout_columns(&specs, 100),
+            vec![(Column::Name, 20), (Column::Path, 79)]
+        );
+    }
+
+    #[test]
+    fn test_column_specs_rejects_duplicates() {
+        let entries = vec![
+            ColumnEntry::Name(Column::Name),
+            ColumnEntry::Name(Column::Name),
+        ];
+        assert!(column_specs(&entries).is_err());
+    }
+
+    #[test]
+    fn test_picker_toggles_and_reorders() {
+        let mut picker = ColumnPicker::new(&defaults(), Vec::new());
+        picker.toggle();
+        assert_eq!(picker.specs().len(), 7, "name column cannot be hidden");
+        picker.selected = 7;
+        picker.toggle();
+        picker.move_entry(-1);
+        let columns: Vec<Column> = picker.specs().iter().map(|spec| spec.column).collect();
+        assert_eq!(columns[6], Column::Stashes);
+        assert_eq!(columns[7], Column::LastFetch);
+    }
+
+    #[test]
+    fn test_status_extras_follow_shown_columns() {
+        assert_eq!(status_extras(&defaults()), StatusExtras::default());
+        let mut specs = defaults();
+        specs.push(ColumnSpec::new(Column::Stashes));
+        let extras = status_extras(&specs);
+        assert!(extras.stashes && !extras.size && !extras.last_commit);
+    }
+
+    #[test]
+    fn test_sort_last_fetch_by_time() {
+        let fetched = |name, secs| RepoState {
+            last_fetch: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+            ..repo(name)
+        };
+        // "9m" would sort after "10d" when comparing the formatted ages.
+        let mut repos = vec![fetched("old", 100), repo("never"), fetched("new", 9_000)];
+        let order = SortOrder::new(Column::LastFetch);
+        assert_eq!(sorted(&mut repos, order), ["new", "old", "never"]);
+        assert_eq!(
+            sorted(&mut repos, order.reversed()),
+            ["old", "new", "never"]
+        );
+    }
+
+    #[test]
+    fn test_sort_ties_break_by_name() {
+        let dirty = |name| RepoState {
+            dirty: true,
+            ..repo(name)
+        };
+        let mut repos = vec![repo("b"), dirty("d"), repo("a"), dirty("C")];
+        let order = SortOrder::new(Column::Dirty);
+        assert_eq!(sorted(&mut repos, order), ["C", "d", "a", "b"]);
+        assert_eq!(sorted(&mut repos, order.reversed()), ["a", "b", "C", "d"]);
+    }
+
+    #[test]
+    fn test_children_follow_parent() {
+        let child = |name: &str, superproject: &str| RepoState {
+            submodule: Some(Submodule {
+                superproject: PathBuf::from("/src").join(superproject),
+                recorded: None,
+                checked_out: None,
+            }),
+            ..repo(name)
+        };
+        let mut repos = vec![
+            repo("app"),
+            child("b-lib", "app"),
+            child("nested", "a-lib"),
+            RepoState {
+                worktree_of: Some(PathBuf::from("/src/tool")),
+                ..repo("feature")
+            },
+            repo("tool"),
+            child("a-lib", "app"),
+        ];
+        let order = SortOrder::default();
+        repos.sort_by(|a, b| order.compare(a, b));
+        group_children(&mut repos);
+        let names: Vec<_> = repos.iter().map(|repo| repo.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["app", "a-lib", "nested", "b-lib", "tool", "feature"]
+        );
+    }
+
+    #[test]
+    fn test_picker_resizes_from_drawn_width() {
+        let mut picker = ColumnPicker::new(&defaults(), vec![(Column::Name, 30)]);
+        picker.resize(2);
+        assert_eq!(picker.specs()[0].width, Some(32));
+        picker.reset_width();
+        assert_eq!(picker.specs()[0].width, None);
+    }
+}
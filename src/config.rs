@@ -0,0 +1,329 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use serde::Deserialize;
+
+use crate::backend::BackendKind;
+use crate::browse::HostConfig;
+use crate::columns::{column_specs, ColumnEntry, ColumnSpec};
+use crate::discovery::{DiscoveryOptions, ScanTargets};
+use crate::exec::ExecOptions;
+use crate::keymap::{KeyChord, KeyContext, KeyOverrides, Keymap, Lookup};
+use crate::launch::{default_launchers, Launcher};
+use crate::theme::{resolve_theme, Theme, ThemeConfig};
+
+/// User settings read from `config.toml`; every section is optional.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Capture mouse events; turning it off keeps the terminal's own text selection.
+    pub mouse: bool,
+    /// Launch actions offered by the "open with" menu; replaces the defaults when set.
+    pub launch: Vec<Launcher>,
+    pub browse: BrowseConfig,
+    /// Key binding overrides per context, e.g. `[keys.table] pull = "P"`.
+    pub keys: KeyOverrides,
+    /// Name of a built-in theme or one defined under `[themes]`.
+    pub theme: String,
+    /// User themes, each based on a built-in theme with some styles replaced.
+    pub themes: BTreeMap<String, ThemeConfig>,
+    /// Repository table columns in display order; replaces the defaults when set.
+    pub columns: Vec<ColumnEntry>,
+    /// How git is accessed: `cli` runs the git binary, `gix` reads in-process.
+    pub backend: BackendKind,
+    pub discovery: DiscoveryOptions,
+    pub exec: ExecOptions,
+    /// Directories scanned when no path is given on the command line.
+    pub roots: Vec<PathBuf>,
+    /// Repositories listed as they are, in addition to `roots`.
+    pub repos: Vec<PathBuf>,
+    /// Workspace manifest declaring the repositories that should exist.
+    pub manifest: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            mouse: true,
+            launch: Vec::new(),
+            browse: BrowseConfig::default(),
+            keys: KeyOverrides::new(),
+            theme: "dark".to_string(),
+            themes: BTreeMap::new(),
+            columns: Vec::new(),
+            backend: BackendKind::default(),
+            discovery: DiscoveryOptions::default(),
+            exec: ExecOptions::default(),
+            roots: Vec::new(),
+            repos: Vec::new(),
+            manifest: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BrowseConfig {
+    /// URL templates for hosts that are not recognised by name.
+    pub hosts: Vec<HostConfig>,
+}
+
+impl Config {
+    /// Default key bindings with the `[keys]` overrides applied.
+    pub fn keymap(&self) -> Result<Keymap, String> {
+        Keymap::with_overrides(&self.keys)
+    }
+
+    /// The selected theme; `no_color` forces monochrome.
+    pub fn theme(&self, no_color: bool) -> Result<Theme, String> {
+        resolve_theme(&self.theme, &self.themes, no_color)
+    }
+
+    pub fn columns(&self) -> Result<Vec<ColumnSpec>, String> {
+        column_specs(&self.columns)
+    }
+
+    /// The configured `roots` and `repos` with `~` expanded; `None` when
+    /// neither is set.
+    pub fn scan_targets(&self) -> Option<ScanTargets> {
+        if self.roots.is_empty() && self.repos.is_empty() {
+            return None;
+        }
+        let expand = |paths: &[PathBuf]| paths.iter().map(|path| expand_home(path)).collect();
+        Some(ScanTargets {
+            roots: expand(&self.roots),
+            repos: expand(&self.repos),
+        })
+    }
+
+    /// The configured manifest with `~` expanded.
+    pub fn manifest_path(&self) -> Option<PathBuf> {
+        self.manifest.as_deref().map(expand_home)
+    }
+
+    pub fn launchers(&self) -> Vec<Launcher> {
+        if self.launch.is_empty() {
+            default_launchers()
+        } else {
+            self.launch.clone()
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/git-dash/config.toml`, falling back to `~/.config`.
+pub fn config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("git-dash").join("config.toml"))
+}
+
+/// `path` with a leading `~` replaced by the home directory.
+fn expand_home(path: &Path) -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    match (path.strip_prefix("~"), home) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Load the config file, returning defaults when it does not exist.
+pub fn load_config(path: Option<&Path>) -> Result<Config, String> {
+    let Some(path) = path else {
+        return Ok(Config::default());
+    };
+    match fs::read_to_string(path) {
+        Ok(contents) => parse_config(&contents)
+            .map_err(|err| format!("Invalid config {}: {err}", path.display())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+        Err(err) => Err(format!("Failed to read {}: {err}", path.display())),
+    }
+}
+
+fn parse_config(contents: &str) -> Result<Config, String> {
+    let config: Config = toml::from_str(contents).map_err(|err| err.message().to_string())?;
+    let keymap = config.keymap()?;
+    let mut keys = Vec::new();
+    for launcher in &config.launch {
+        launcher.validate()?;
+        if keys.contains(&launcher.key) {
+            return Err(format!("launch key '{}' is used twice", launcher.key));
+        }
+        // Menu bindings are looked up first and would hide the launcher
+        let chord = KeyChord::from(KeyEvent::from(KeyCode::Char(launcher.key)));
+        if keymap.lookup(KeyContext::Menu, &[chord]) != Lookup::None {
+            return Err(format!(
+                "launch key '{}' is bound in the menu key context",
+                launcher.key
+            ));
+        }
+        keys.push(launcher.key);
+    }
+    config.theme(false)?;
+    config.columns()?;
+    if config.discovery.threads == Some(0) {
+        return Err("discovery.threads must be at least 1".to_string());
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::NestedRepos;
+
+    #[test]
+    fn test_empty_config_uses_default_launchers() {
+        let config = parse_config("").unwrap();
+        assert!(config.mouse);
+        assert!(config.launch.is_empty());
+        assert_eq!(config.launchers().len(), default_launchers().len());
+    }
+
+    #[test]
+    fn test_parse_launchers() {
+        let config = parse_config(
+            r#"
+            [[launch]]
+            key = "v"
+            name = "VS Code"
+            command = "code {path}"
+            terminal = false
+            "#,
+        )
+        .unwrap();
+        let launchers = config.launchers();
+        assert_eq!(launchers.len(), 1);
+        assert_eq!(launchers[0].key, 'v');
+        assert!(!launchers[0].terminal);
+    }
+
+    #[test]
+    fn test_rejects_launch_keys_bound_in_menu() {
+        let launcher = "[[launch]]\nkey = \"j\"\nname = \"jj\"\ncommand = \"jj\"\n";
+        let err = parse_config(launcher).unwrap_err();
+        assert!(err.contains("menu"), "{err}");
+        let rebound = format!("{launcher}[keys.menu]\ndown = \"Down\"\n");
+        assert!(parse_config(&rebound).is_ok());
+    }
+
+    #[test]
+    fn test_parse_browse_hosts() {
+        let config = parse_config(
+            r#"
+            [[browse.hosts]]
+            host = "git.corp"
+            forge = "gitlab"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.browse.hosts[0].host, "git.corp");
+        assert!(parse_config("[[browse.hosts]]\nhost = \"x\"\nforge = \"svn\"").is_err());
+    }
+
+    #[test]
+    fn test_rejects_conflicting_keys() {
+        assert!(parse_config("[keys.table]\nrefresh = \"p\"").is_err());
+        assert!(parse_config("[keys.table]\nrefresh = \"F5\"").is_ok());
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        let config = parse_config(
+            r#"
+            theme = "mine"
+            [themes.mine]
+            base = "color-blind"
+            warning = { fg = "yellow", modifiers = ["bold"] }
+            "#,
+        )
+        .unwrap();
+        assert!(config.theme(false).is_ok());
+        assert!(parse_config("theme = \"missing\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        let config = parse_config(
+            r#"columns = ["name", { column = "path", width = 30 }, { column = "stashes", min = 4 }]"#,
+        )
+        .unwrap();
+        let columns = config.columns().unwrap();
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[1].width, Some(30));
+        assert_eq!(columns[2].min, 4);
+        assert!(parse_config(r#"columns = ["name", "size", "size"]"#).is_err());
+        assert!(parse_config(r#"columns = ["colour"]"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_backend() {
+        assert_eq!(parse_config("").unwrap().backend, BackendKind::Cli);
+        assert_eq!(
+            parse_config("backend = \"gix\"").unwrap().backend,
+            BackendKind::Gix
+        );
+        assert!(parse_config("backend = \"libgit2\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_discovery() {
+        assert_eq!(parse_config("").unwrap().discovery.threads, None);
+        let config = parse_config("[discovery]\nthreads = 4").unwrap();
+        assert_eq!(config.discovery.thread_count(), 4);
+        assert!(config.discovery.follow_symlinks && !config.discovery.one_filesystem);
+        let config =
+            parse_config("[discovery]\nfollow_symlinks = false\none_filesystem = true").unwrap();
+        assert!(!config.discovery.follow_symlinks && config.discovery.one_filesystem);
+        let config = parse_config("[discovery]\nnested = \"submodules\"").unwrap();
+        assert_eq!(config.discovery.nested, NestedRepos::Submodules);
+        assert!(parse_config("[discovery]\nnested = \"deep\"").is_err());
+        assert!(parse_config("[discovery]\nthreads = 0").is_err());
+        assert!(parse_config("[discovery]\njobs = 4").is_err());
+    }
+
+    #[test]
+    fn test_parse_exec() {
+        let config = parse_config("").unwrap();
+        assert!(!config.exec.fail_fast);
+        assert_eq!(config.exec.timeout, 600);
+        let config = parse_config("[exec]\nfail_fast = true\ntimeout = 0").unwrap();
+        assert!(config.exec.fail_fast);
+        assert_eq!(config.exec.timeout, 0);
+    }
+
+    #[test]
+    fn test_parse_roots_and_repos() {
+        assert_eq!(parse_config("").unwrap().scan_targets(), None);
+        let config = parse_config(
+            r#"
+            roots = ["~/work", "/srv/oss"]
+            repos = ["~/notes"]
+            "#,
+        )
+        .unwrap();
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+        let targets = config.scan_targets().unwrap();
+        assert_eq!(
+            targets.roots,
+            [home.join("work"), PathBuf::from("/srv/oss")]
+        );
+        assert_eq!(targets.repos, [home.join("notes")]);
+        assert_eq!(config.manifest_path(), None);
+        let config = parse_config("manifest = \"~/meta/repos.toml\"").unwrap();
+        assert_eq!(config.manifest_path(), Some(home.join("meta/repos.toml")));
+        assert_eq!(
+            expand_home(Path::new("~other/x")),
+            PathBuf::from("~other/x")
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_fields_and_empty_commands() {
+        assert!(parse_config("colour = \"red\"").is_err());
+        assert!(parse_config("[[launch]]\nkey = \"v\"\nname = \"x\"\ncommand = \" \"").is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use std::fs;
+use std::path::Path;
+
+use crate::git::{run_git, GIT_TIMEOUT};
+
+/// Diffs longer than this are truncated to keep rendering responsive.
+pub const DIFF_LINE_LIMIT: usize = 20_000;
+
+// Untracked files larger than this are not inlined into the diff view.
+const UNTRACKED_BYTES_LIMIT: u64 = 512 * 1024;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DiffLineKind {
+    /// "Staged changes" / "Unstaged changes" separators inserted by git-dash.
+    Section,
+    /// `diff --git`, `index`, `---` and `+++` lines.
+    FileHeader,
+    Hunk,
+    Added,
+    Removed,
+    Context,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+}
+
+/// Load staged and unstaged changes for a repository, or a single file when
+/// `file` is set. Untracked files are shown as fully added.
+pub fn git_diff(path: &Path, file: Option<&str>, untracked: bool) -> Result<Vec<DiffLine>, String> {
+    if let (Some(file), true) = (file, untracked) {
+        return untracked_diff(path, file);
+    }
+
+    let mut lines = Vec::new();
+    for (label, cached) in [("Staged changes", true), ("Unstaged changes", false)] {
+        let mut args = vec!["diff", "--no-color", "--no-ext-diff"];
+        if cached {
+            args.push("--cached");
+        }
+        args.push("--");
+        if let Some(file) = file {
+            args.push(file);
+        }
+        let output = run_git(path, &args, GIT_TIMEOUT)?;
+        let section = parse_diff(&String::from_utf8_lossy(&output));
+        if section.is_empty() {
+            continue;
+        }
+        lines.push(DiffLine {
+            kind: DiffLineKind::Section,
+            text: label.to_string(),
+        });
+        lines.extend(section);
+    }
+    truncate(&mut lines);
+    Ok(lines)
+}
+
+fn untracked_diff(path: &Path, file: &str) -> Result<Vec<DiffLine>, String> {
+    let full_path = path.join(file);
+    let metadata = fs::metadata(&full_path).map_err(|err| err.to_string())?;
+    let mut lines = vec![
+        DiffLine {
+            kind: DiffLineKind::Section,
+            text: "Untracked file".to_string(),
+        },
+        DiffLine {
+            kind: DiffLineKind::FileHeader,
+            text: format!("+++ b/{file}"),
+        },
+    ];
+    if metadata.is_dir() {
+        lines.push(DiffLine {
+            kind: DiffLineKind::Context,
+            text: "(untracked directory)".to_string(),
+        });
+        return Ok(lines);
+    }
+    if metadata.len() > UNTRACKED_BYTES_LIMIT {
+        lines.push(DiffLine {
+            kind: DiffLineKind::Context,
+            text: format!("(file too large to display: {} bytes)", metadata.len()),
+        });
+        return Ok(lines);
+    }
+    let bytes = fs::read(&full_path).map_err(|err| err.to_string())?;
+    if bytes.contains(&0) {
+        lines.push(DiffLine {
+            kind: DiffLineKind::Context,
+            text: "(binary file)".to_string(),
+        });
+        return Ok(lines);
+    }
+    let content = String::from_utf8_lossy(&bytes);
+    lines.push(DiffLine {
+        kind: DiffLineKind::Hunk,
+        text: format!("@@ -0,0 +1,{} @@", content.lines().count()),
+    });
+    lines.extend(content.lines().map(|line| DiffLine {
+        kind: DiffLineKind::Added,
+        text: format!("+{line}"),
+    }));
+    truncate(&mut lines);
+    Ok(lines)
+}
+
+fn truncate(lines: &mut Vec<DiffLine>) {
+    if lines.len() > DIFF_LINE_LIMIT {
+        let hidden = lines.len() - DIFF_LINE_LIMIT;
+        lines.truncate(DIFF_LINE_LIMIT);
+        lines.push(DiffLine {
+            kind: DiffLineKind::Section,
+            text: format!("… {hidden} more lines not shown"),
+        });
+    }
+}
+
+fn parse_diff(output: &str) -> Vec<DiffLine> {
+    let mut in_hunk = false;
+    output
+        .lines()
+        .map(|line| {
+            let kind = if line.starts_with("diff --git ") {
+                in_hunk = false;
+                DiffLineKind::FileHeader
+            } else if line.starts_with("@@") {
+                in_hunk = true;
+                DiffLineKind::Hunk
+            } else if !in_hunk {
+                DiffLineKind::FileHeader
+            } else if line.starts_with('+') {
+                DiffLineKind::Added
+            } else if line.starts_with('-') {
+                DiffLineKind::Removed
+            } else {
+                DiffLineKind::Context
+            };
+            DiffLine {
+                kind,
+                text: line.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::{git_status, StatusExtras};
+    use crate::test_support::{git, TempDir};
+
+    #[test]
+    fn test_parse_diff_kinds() {
+        let output = "diff --git a/f.txt b/f.txt\nindex 1..2 100644\n--- a/f.txt\n+++ b/f.txt\n@@ -1,2 +1,2 @@\n keep\n-old\n+new\n";
+        let kinds: Vec<_> = parse_diff(output).into_iter().map(|l| l.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiffLineKind::FileHeader,
+                DiffLineKind::FileHeader,
+                DiffLineKind::FileHeader,
+                DiffLineKind::FileHeader,
+                DiffLineKind::Hunk,
+                DiffLineKind::Context,
+                DiffLineKind::Removed,
+                DiffLineKind::Added,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_diff_header_after_hunk() {
+        let output = "diff --git a/a b/a\n--- a/a\n+++ b/a\n@@ -1 +1 @@\n-x\n+y\ndiff --git a/b b/b\n--- a/b\n+++ b/b\n";
+        let lines = parse_diff(output);
+        assert_eq!(lines[6].kind, DiffLineKind::FileHeader);
+        assert_eq!(lines[7].kind, DiffLineKind::FileHeader);
+        assert_eq!(lines[8].kind, DiffLineKind::FileHeader);
+    }
+
+    #[test]
+    fn test_diff_of_paths_git_quotes() {
+        let dir = TempDir::new("diff-quoted");
+        fs::write(dir.join("a b.txt"), "old\n").unwrap();
+        git(&dir, &["init", "-q", "-b", "main"]);
+        git(&dir, &["add", "."]);
+        git(&dir, &["commit", "-q", "-m", "initial"]);
+        fs::write(dir.join("a b.txt"), "new\n").unwrap();
+        fs::write(dir.join("ä c.txt"), "added\n").unwrap();
+
+        let git_dir = dir.join(".git");
+        let status = git_status(&dir, &git_dir, StatusExtras::default()).unwrap();
+        assert_eq!(
+            status.changes,
+            [
+                ("M".to_string(), "a b.txt".to_string()),
+                ("??".to_string(), "ä c.txt".to_string()),
+            ]
+        );
+        let modified = git_diff(&dir, Some("a b.txt"), false).unwrap();
+        assert!(modified.iter().any(|line| line.text == "+new"));
+        let untracked = git_diff(&dir, Some("ä c.txt"), true).unwrap();
+        assert!(untracked.iter().any(|line| line.text == "+added"));
+    }
+}
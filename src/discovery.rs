@@ -1,61 +1,582 @@
+use std::collections::{HashSet, VecDeque};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
 
-#[derive(Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct RepoRef {
     pub path: PathBuf,
     pub git_dir: PathBuf,
+    /// Repository that has this one as a submodule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superproject: Option<PathBuf>,
+    /// Main worktree of the repository when this is a linked worktree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_worktree: Option<PathBuf>,
+}
+
+impl RepoRef {
+    pub fn new(path: PathBuf, git_dir: PathBuf) -> Self {
+        Self {
+            path,
+            git_dir,
+            superproject: None,
+            main_worktree: None,
+        }
+    }
+
+    /// A bare repository is its own git directory.
+    pub fn is_bare(&self) -> bool {
+        self.path == self.git_dir
+    }
+}
+
+/// What the walk does with repositories inside other repositories.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NestedRepos {
+    /// Stop at the outermost repository.
+    #[default]
+    Skip,
+    /// Keep descending and list every repository found.
+    All,
+    /// Stop at the outermost repository but list its submodules, and
+    /// theirs, under it.
+    Submodules,
+}
+
+/// How the directory walk runs; read from the `[discovery]` config section.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscoveryOptions {
+    /// Directories read in parallel; defaults to the number of CPUs, at most 16.
+    pub threads: Option<usize>,
+    /// Descend into symlinked directories, on by default. Directories
+    /// reached twice are only read once, so links back up the tree cannot
+    /// loop.
+    pub follow_symlinks: bool,
+    /// Skip directories on a different filesystem than the root.
+    pub one_filesystem: bool,
+    pub nested: NestedRepos,
+}
+
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            threads: None,
+            follow_symlinks: true,
+            one_filesystem: false,
+            nested: NestedRepos::default(),
+        }
+    }
+}
+
+impl DiscoveryOptions {
+    pub fn thread_count(&self) -> usize {
+        self.threads.unwrap_or_else(|| {
+            thread::available_parallelism()
+                .map(|n| n.get().min(16))
+                .unwrap_or(4)
+        })
+    }
+}
+
+/// What to scan: directories to walk and repositories listed by path.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScanTargets {
+    pub roots: Vec<PathBuf>,
+    pub repos: Vec<PathBuf>,
+}
+
+impl ScanTargets {
+    pub fn single(root: PathBuf) -> Self {
+        Self {
+            roots: vec![root],
+            repos: Vec::new(),
+        }
+    }
+
+    /// Canonical paths, so that cached lists and statuses match between
+    /// runs, and the paths that do not exist.
+    pub fn resolve(self) -> (Self, Vec<PathBuf>) {
+        let mut missing = Vec::new();
+        let mut resolve = |paths: Vec<PathBuf>| {
+            let mut resolved: Vec<PathBuf> = Vec::new();
+            for path in paths {
+                match fs::canonicalize(&path) {
+                    Ok(path) if !resolved.contains(&path) => resolved.push(path),
+                    Ok(_) => {}
+                    Err(_) => missing.push(path),
+                }
+            }
+            resolved
+        };
+        let targets = Self {
+            roots: resolve(self.roots),
+            repos: resolve(self.repos),
+        };
+        (targets, missing)
+    }
+
+    /// Root a repository was found under: the deepest one containing `path`.
+    /// `None` for listed repositories outside every root.
+    pub fn root_of(&self, path: &Path) -> Option<&Path> {
+        self.roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .map(PathBuf::as_path)
+    }
+
+    /// Whether `path` is below a root or listed.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.root_of(path).is_some() || self.repos.iter().any(|repo| repo == path)
+    }
 }
 
-pub fn discover_repos_with_progress<F>(root: &Path, mut on_progress: F) -> Vec<RepoRef>
+/// How often the walk reports progress.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+/// How long an idle walker waits before looking for work again.
+const IDLE_WAIT: Duration = Duration::from_millis(1);
+
+/// `discover_repos` with the default options.
+// Library API; the binary always passes the configured options
+#[allow(dead_code)]
+pub fn discover_repos_with_progress<F>(root: &Path, on_progress: F) -> Vec<RepoRef>
 where
     F: FnMut(usize, usize) -> bool,
 {
-    let mut repos = Vec::new();
-    let mut stack = vec![root.to_path_buf()];
-    let mut visited = 0usize;
+    discover_repos(root, &DiscoveryOptions::default(), on_progress)
+}
+
+/// Find the repositories below `root`, sorted by path. The walk does not
+/// descend into repositories. `on_progress` runs on the calling thread with
+/// the number of directories visited and still to visit, once before the
+/// walk starts; returning `false` stops the walk and returns what was found
+/// so far.
+pub fn discover_repos<F>(
+    root: &Path,
+    options: &DiscoveryOptions,
+    mut on_progress: F,
+) -> Vec<RepoRef>
+where
+    F: FnMut(usize, usize) -> bool,
+{
+    let threads = options.thread_count().max(1);
+    let walk = Walk::new(root, options, threads);
+    if !on_progress(walk.visited(), walk.pending()) {
+        walk.stop();
+    }
+    // Every walker holds a sender, so the channel disconnects once all are done
+    let (alive_tx, alive_rx) = mpsc::channel::<()>();
+    let mut repos: Vec<RepoRef> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|index| {
+                let alive = alive_tx.clone();
+                let walk = &walk;
+                scope.spawn(move || {
+                    let _alive = alive;
+                    walk.run(index)
+                })
+            })
+            .collect();
+        drop(alive_tx);
+        while let Err(RecvTimeoutError::Timeout) = alive_rx.recv_timeout(PROGRESS_INTERVAL) {
+            if !walk.stopped() && !on_progress(walk.visited(), walk.pending()) {
+                walk.stop();
+            }
+        }
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    if !walk.stopped() {
+        on_progress(walk.visited(), 0);
+    }
+    let mut seen = dedupe(&mut repos);
+    link_worktrees(&mut repos, &mut seen);
+    repos
+}
+
+/// Walk every root of `targets` in turn and add the listed repositories.
+/// `on_progress` gets the index of the root being walked along with the
+/// counts `discover_repos` reports; returning `false` skips the remaining
+/// roots. Returns the repositories of each root and all of them merged,
+/// every repository listed once.
+pub fn discover_targets<F>(
+    targets: &ScanTargets,
+    options: &DiscoveryOptions,
+    mut on_progress: F,
+) -> (Vec<Vec<RepoRef>>, Vec<RepoRef>)
+where
+    F: FnMut(usize, usize, usize) -> bool,
+{
+    let mut per_root = Vec::new();
+    for (index, root) in targets.roots.iter().enumerate() {
+        let mut stopped = false;
+        per_root.push(discover_repos(root, options, |visited, remaining| {
+            stopped = !on_progress(index, visited, remaining);
+            !stopped
+        }));
+        if stopped {
+            break;
+        }
+    }
+    let listed = targets.repos.iter().filter_map(|path| repo_at(path));
+    let merged = merge_repo_lists(per_root.iter().flatten().cloned().chain(listed));
+    (per_root, merged)
+}
+
+/// The repository at `path` itself, working tree or bare, for repositories
+/// listed by path rather than found by a walk.
+pub fn repo_at(path: &Path) -> Option<RepoRef> {
+    let dot_git = path.join(".git");
+    let git_dir = if dot_git.exists() {
+        resolve_git_dir(path, &dot_git).ok()?
+    } else if path.join("HEAD").is_file()
+        && path.join("objects").is_dir()
+        && path.join("refs").is_dir()
+    {
+        path.to_path_buf()
+    } else {
+        return None;
+    };
+    Some(RepoRef {
+        main_worktree: main_worktree(&git_dir),
+        ..RepoRef::new(path.to_path_buf(), git_dir)
+    })
+}
+
+/// Repositories from several roots and listed paths, sorted by path with
+/// each repository once.
+pub fn merge_repo_lists(repos: impl IntoIterator<Item = RepoRef>) -> Vec<RepoRef> {
+    let mut repos: Vec<RepoRef> = repos.into_iter().collect();
+    dedupe(&mut repos);
+    repos
+}
+
+/// Sort by path and drop repositories listed before under another path.
+/// Returns the canonical git directories kept.
+fn dedupe(repos: &mut Vec<RepoRef>) -> HashSet<PathBuf> {
+    repos.sort_by(|a, b| a.path.cmp(&b.path));
+    // The same repository can be reachable through more than one path
+    let mut seen = HashSet::new();
+    repos.retain(|repo| seen.insert(canonical(&repo.git_dir)));
+    seen
+}
+
+/// Point linked worktrees at their main worktree and add the linked
+/// worktrees of found repositories that the walk did not reach. `seen`
+/// holds the canonical git directories already listed.
+fn link_worktrees(repos: &mut Vec<RepoRef>, seen: &mut HashSet<PathBuf>) {
+    for repo in repos.iter_mut() {
+        repo.main_worktree = main_worktree(&repo.git_dir);
+    }
+    let mut outside = Vec::new();
+    for repo in repos.iter().filter(|repo| repo.main_worktree.is_none()) {
+        for worktree in linked_worktrees(&repo.path, &repo.git_dir) {
+            if seen.insert(canonical(&worktree.git_dir)) {
+                outside.push(worktree);
+            }
+        }
+    }
+    if !outside.is_empty() {
+        repos.extend(outside);
+        repos.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+}
+
+/// Main worktree of a linked worktree's git directory: the directory
+/// holding the common `.git`, or the common directory of a bare repository.
+/// `None` for a repository's own git directory.
+pub fn main_worktree(git_dir: &Path) -> Option<PathBuf> {
+    let common = canonical(&common_dir(git_dir));
+    if common == canonical(git_dir) {
+        return None;
+    }
+    match common.file_name().and_then(|name| name.to_str()) {
+        Some(".git") => common.parent().map(Path::to_path_buf),
+        _ => Some(common),
+    }
+}
+
+/// Linked worktrees registered in `git_dir/worktrees` whose directory
+/// still exists, as `git worktree list` reports them.
+pub fn linked_worktrees(main: &Path, git_dir: &Path) -> Vec<RepoRef> {
+    let Ok(entries) = fs::read_dir(git_dir.join("worktrees")) else {
+        return Vec::new();
+    };
+    let mut worktrees: Vec<RepoRef> = entries
+        .flatten()
+        .filter_map(|entry| {
+            // `gitdir` holds the path of the `.git` file in the worktree,
+            // relative to this directory with `worktree.useRelativePaths`
+            let contents = fs::read_to_string(entry.path().join("gitdir")).ok()?;
+            let recorded = Path::new(contents.trim());
+            let dot_git = if recorded.is_relative() {
+                canonical(&entry.path().join(recorded))
+            } else {
+                recorded.to_path_buf()
+            };
+            if !dot_git.is_file() {
+                return None;
+            }
+            Some(RepoRef {
+                main_worktree: Some(main.to_path_buf()),
+                ..RepoRef::new(dot_git.parent()?.to_path_buf(), entry.path())
+            })
+        })
+        .collect();
+    worktrees.sort_by(|a, b| a.path.cmp(&b.path));
+    worktrees
+}
+
+/// Git directory shared by all worktrees; linked worktrees point to it
+/// through their `commondir` file.
+pub fn common_dir(git_dir: &Path) -> PathBuf {
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(contents) => git_dir.join(contents.trim()),
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Shared state of a parallel walk. Each walker takes directories from the
+/// back of its own queue and steals from the front of the others' when it
+/// runs dry.
+struct Walk {
+    follow_symlinks: bool,
+    nested: NestedRepos,
+    /// Device of the root when the walk stays on one filesystem.
+    device: Option<u64>,
+    /// Directories already queued, by (device, inode).
+    seen: Mutex<HashSet<(u64, u64)>>,
+    queues: Vec<Mutex<VecDeque<PathBuf>>>,
+    /// Directories queued or being read; the walk is over when this is zero.
+    pending: AtomicUsize,
+    visited: AtomicUsize,
+    stop: AtomicBool,
+}
+
+impl Walk {
+    fn new(root: &Path, options: &DiscoveryOptions, threads: usize) -> Self {
+        let queues: Vec<_> = (0..threads).map(|_| Mutex::new(VecDeque::new())).collect();
+        queues[0].lock().unwrap().push_back(root.to_path_buf());
+        let root_id = fs::metadata(root)
+            .ok()
+            .and_then(|metadata| dir_id(&metadata));
+        Self {
+            follow_symlinks: options.follow_symlinks,
+            nested: options.nested,
+            device: root_id
+                .filter(|_| options.one_filesystem)
+                .map(|(device, _)| device),
+            seen: Mutex::new(root_id.into_iter().collect()),
+            queues,
+            pending: AtomicUsize::new(1),
+            visited: AtomicUsize::new(0),
+            stop: AtomicBool::new(false),
+        }
+    }
+
+    fn visited(&self) -> usize {
+        self.visited.load(Ordering::SeqCst)
+    }
+
+    fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+
+    fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+
+    fn next(&self, index: usize) -> Option<PathBuf> {
+        if let Some(dir) = self.queues[index].lock().unwrap().pop_back() {
+            return Some(dir);
+        }
+        let count = self.queues.len();
+        (1..count).find_map(|offset| {
+            self.queues[(index + offset) % count]
+                .lock()
+                .unwrap()
+                .pop_front()
+        })
+    }
+
+    fn run(&self, index: usize) -> Vec<RepoRef> {
+        let mut repos = Vec::new();
+        while !self.stopped() {
+            let Some(dir) = self.next(index) else {
+                if self.pending() == 0 {
+                    break;
+                }
+                thread::sleep(IDLE_WAIT);
+                continue;
+            };
+            let subdirs = self.visit_dir(dir, &mut repos);
+            // Count the children before finishing the parent so that
+            // `pending` only reaches zero when nothing is left anywhere
+            self.pending.fetch_add(subdirs.len(), Ordering::SeqCst);
+            self.queues[index].lock().unwrap().extend(subdirs);
+            self.visited.fetch_add(1, Ordering::SeqCst);
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+        }
+        repos
+    }
 
-    while let Some(dir) = stack.pop() {
-        visited += 1;
+    /// Record `dir` if it is a repository and return the subdirectories
+    /// that still need a visit; none inside a repository unless nested
+    /// repositories are wanted.
+    fn visit_dir(&self, dir: PathBuf, repos: &mut Vec<RepoRef>) -> Vec<PathBuf> {
         let Ok(entries) = fs::read_dir(&dir) else {
-            continue;
+            return Vec::new();
         };
-        let mut is_repo = false;
-        let mut subdirs = Vec::new();
-
+        let mut git_path = None;
+        let mut bare_layout = [false; 3];
+        let mut candidates = Vec::new();
         for entry in entries.flatten() {
             let path = entry.path();
-            if path.file_name().and_then(|name| name.to_str()) == Some(".git") {
-                is_repo = true;
-                if let Ok(git_dir) = resolve_git_dir(&dir, &path) {
-                    repos.push(RepoRef {
-                        path: dir.clone(),
-                        git_dir,
-                    });
-                }
-                break;
+            let name = path.file_name().and_then(|name| name.to_str());
+            if name == Some(".git") {
+                git_path = Some(path);
+                continue;
             }
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_dir() {
-                    subdirs.push(path);
-                }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            // A bare repository has HEAD next to objects and refs
+            match name {
+                Some("HEAD") => bare_layout[0] = file_type.is_file(),
+                Some("objects") => bare_layout[1] = file_type.is_dir(),
+                Some("refs") => bare_layout[2] = file_type.is_dir(),
+                _ => {}
+            }
+            let metadata = if file_type.is_symlink() && self.follow_symlinks {
+                fs::metadata(&path)
+            } else if file_type.is_dir() {
+                entry.metadata()
+            } else {
+                continue;
+            };
+            if let Some(metadata) = metadata.ok().filter(|metadata| metadata.is_dir()) {
+                candidates.push((path, metadata));
             }
         }
 
-        if is_repo {
-            continue;
+        if let Some(git_path) = git_path {
+            let Ok(git_dir) = resolve_git_dir(&dir, &git_path) else {
+                return Vec::new();
+            };
+            if self.nested == NestedRepos::Submodules {
+                add_submodules(&dir, repos);
+            }
+            repos.push(RepoRef::new(dir, git_dir));
+            if self.nested != NestedRepos::All {
+                return Vec::new();
+            }
+        } else if bare_layout == [true; 3] {
+            // Never descend into a bare repository: its directories are
+            // git internals, not working trees
+            repos.push(RepoRef::new(dir.clone(), dir));
+            return Vec::new();
         }
+        candidates
+            .into_iter()
+            .filter(|(_, metadata)| self.claim(metadata))
+            .map(|(path, _)| path)
+            .collect()
+    }
 
-        for subdir in subdirs {
-            stack.push(subdir);
+    /// Whether a directory should be visited: it is on the root's
+    /// filesystem if required and has not been reached before.
+    fn claim(&self, metadata: &fs::Metadata) -> bool {
+        let Some(id) = dir_id(metadata) else {
+            return true;
+        };
+        if self.device.is_some_and(|device| device != id.0) {
+            return false;
         }
+        self.seen.lock().unwrap().insert(id)
+    }
+}
 
-        if (visited.is_multiple_of(20) || stack.is_empty()) && !on_progress(visited, stack.len()) {
-            return repos;
-        }
+#[cfg(unix)]
+fn dir_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Add the checked out submodules listed in `.gitmodules` of `superproject`,
+/// and recursively their own.
+fn add_submodules(superproject: &Path, repos: &mut Vec<RepoRef>) {
+    let Ok(contents) = fs::read_to_string(superproject.join(".gitmodules")) else {
+        return;
+    };
+    for relative in gitmodules_paths(&contents) {
+        let path = superproject.join(relative);
+        let Ok(git_dir) = resolve_git_dir(&path, &path.join(".git")) else {
+            // Not initialised
+            continue;
+        };
+        add_submodules(&path, repos);
+        repos.push(RepoRef {
+            superproject: Some(superproject.to_path_buf()),
+            ..RepoRef::new(path, git_dir)
+        });
     }
+}
 
-    repos
+/// The `path` of every submodule in a `.gitmodules` file. Paths that are
+/// absolute or leave the superproject are dropped, so a crafted file cannot
+/// claim repositories elsewhere.
+fn gitmodules_paths(contents: &str) -> Vec<&str> {
+    let mut in_submodule = false;
+    let mut paths = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_submodule = line.starts_with("[submodule ");
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if !in_submodule || !key.trim().eq_ignore_ascii_case("path") {
+            continue;
+        }
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+        if !value.is_empty()
+            && Path::new(value)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            paths.push(value);
+        }
+    }
+    paths
 }
 
 pub fn resolve_git_dir(repo_root: &Path, git_path: &Path) -> Result<PathBuf, String> {
@@ -75,3 +596,26 @@ pub fn resolve_git_dir(repo_root: &Path, git_path: &Path) -> Result<PathBuf, Str
         Ok(path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitmodules_paths() {
+        let contents = r#"
+[core]
+	path = not-a-submodule
+[submodule "lib"]
+	path = vendor/lib
+	url = https://example.com/lib.git
+[submodule "spaced"]
+	path = "docs site"
+[submodule "escape"]
+	path = ../elsewhere
+[submodule "absolute"]
+	path = /srv/repo
+"#;
+        assert_eq!(gitmodules_paths(contents), ["vendor/lib", "docs site"]);
+    }
+}
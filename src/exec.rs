@@ -0,0 +1,456 @@
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::discovery::{discover_targets, DiscoveryOptions, ScanTargets};
+use crate::logger::log_debug;
+use crate::worker::run_parallel;
+
+/// Captured output beyond this many bytes per stream is dropped.
+const OUTPUT_LIMIT: usize = 256 * 1024;
+
+/// `[exec]` settings shared by `git-dash exec` and the TUI command prompt.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExecOptions {
+    /// Skip repositories not yet started after the first failure.
+    pub fail_fast: bool,
+    /// Seconds before a command is killed; 0 lets it run indefinitely.
+    pub timeout: u64,
+}
+
+impl Default for ExecOptions {
+    fn default() -> Self {
+        Self {
+            fail_fast: false,
+            timeout: 600,
+        }
+    }
+}
+
+impl ExecOptions {
+    fn time_limit(&self) -> Option<Duration> {
+        (self.timeout > 0).then(|| Duration::from_secs(self.timeout))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ExecCommand {
+    /// Command line interpreted by `sh -c`, as typed in the TUI prompt.
+    Shell(String),
+    /// Program and arguments executed directly, as given after `exec --`.
+    Args(Vec<String>),
+}
+
+impl ExecCommand {
+    pub fn display(&self) -> String {
+        match self {
+            ExecCommand::Shell(line) => line.clone(),
+            ExecCommand::Args(args) => args.join(" "),
+        }
+    }
+
+    fn build(&self) -> Option<Command> {
+        match self {
+            ExecCommand::Shell(line) => {
+                let mut command = Command::new("sh");
+                command.arg("-c").arg(line);
+                Some(command)
+            }
+            ExecCommand::Args(args) => {
+                let (program, rest) = args.split_first()?;
+                let mut command = Command::new(program);
+                command.args(rest);
+                Some(command)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExecOutput {
+    /// `None` when the process was terminated by a signal.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub enum ExecOutcome {
+    /// The command exited, or was killed after a timeout or cancel with the
+    /// output it wrote until then.
+    Finished(ExecOutput),
+    /// The command could not be started or waited for.
+    Error(String),
+    /// Not run because an earlier repository failed with fail-fast, or
+    /// because the run was cancelled.
+    Skipped,
+}
+
+impl ExecOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self, ExecOutcome::Finished(output) if output.exit_code == Some(0))
+    }
+
+    /// Short result such as "exit 0 (1.2s)", "killed" or "skipped".
+    pub fn summary(&self) -> String {
+        match self {
+            ExecOutcome::Finished(output) => {
+                let secs = output.elapsed.as_secs_f64();
+                match output.exit_code {
+                    Some(code) => format!("exit {code} ({secs:.1}s)"),
+                    None => format!("killed ({secs:.1}s)"),
+                }
+            }
+            ExecOutcome::Error(err) => format!("error: {err}"),
+            ExecOutcome::Skipped => "skipped".to_string(),
+        }
+    }
+}
+
+/// Counts of succeeded, failed and skipped repositories.
+pub fn tally(outcomes: &[(PathBuf, ExecOutcome)]) -> (usize, usize, usize) {
+    let skipped = outcomes
+        .iter()
+        .filter(|(_, outcome)| matches!(outcome, ExecOutcome::Skipped))
+        .count();
+    let succeeded = outcomes
+        .iter()
+        .filter(|(_, outcome)| outcome.is_success())
+        .count();
+    (succeeded, outcomes.len() - succeeded - skipped, skipped)
+}
+
+/// Run `command` in `path`, killing it and every process it started once
+/// `time_limit` has passed or `cancel` is set.
+pub fn run_command(
+    path: &Path,
+    command: &ExecCommand,
+    time_limit: Option<Duration>,
+    cancel: &AtomicBool,
+) -> ExecOutcome {
+    let start = Instant::now();
+    let Some(mut builder) = command.build() else {
+        return ExecOutcome::Error("empty command".to_string());
+    };
+    // A group of its own, so a kill also reaches the processes it started
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        builder.process_group(0);
+    }
+    let child = builder
+        .current_dir(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) => return ExecOutcome::Error(err.to_string()),
+    };
+
+    let stdout = child
+        .stdout
+        .take()
+        .map(|stream| thread::spawn(move || read_limited(stream)));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|stream| thread::spawn(move || read_limited(stream)));
+    let status = wait_until(&mut child, start, time_limit, cancel);
+
+    log_debug(&format!(
+        "exec path={} command={:?} elapsed_ms={}",
+        path.display(),
+        command.display(),
+        start.elapsed().as_millis()
+    ));
+    let (status, killed) = match status {
+        Ok(result) => result,
+        Err(err) => return ExecOutcome::Error(err),
+    };
+    let mut stderr = stderr
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+    if let Some(reason) = killed {
+        if !stderr.is_empty() && !stderr.ends_with('\n') {
+            stderr.push('\n');
+        }
+        stderr.push_str(&format!("… {reason}"));
+    }
+    ExecOutcome::Finished(ExecOutput {
+        exit_code: status.code(),
+        stdout: stdout
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default(),
+        stderr,
+        elapsed: start.elapsed(),
+    })
+}
+
+/// Wait for `child` to exit, or kill it; the reason for a kill comes
+/// along with the exit status.
+fn wait_until(
+    child: &mut Child,
+    start: Instant,
+    time_limit: Option<Duration>,
+    cancel: &AtomicBool,
+) -> Result<(ExitStatus, Option<String>), String> {
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok((status, None)),
+            Ok(None) => {
+                let stop = if cancel.load(Ordering::Relaxed) {
+                    Some("cancelled".to_string())
+                } else {
+                    time_limit
+                        .filter(|limit| start.elapsed() >= *limit)
+                        .map(|limit| format!("timed out after {}s", limit.as_secs()))
+                };
+                if let Some(reason) = stop {
+                    kill_group(child);
+                    let status = child.wait().map_err(|err| err.to_string())?;
+                    return Ok((status, Some(reason)));
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+}
+
+/// Kill `child` and, on Unix, the rest of its process group, closing the
+/// pipes that the readers wait on.
+fn kill_group(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let group = format!("-{}", child.id());
+        let _ = Command::new("kill")
+            .args(["-KILL", "--", &group])
+            .stderr(Stdio::null())
+            .status();
+    }
+    let _ = child.kill();
+}
+
+fn read_limited(mut stream: impl Read) -> String {
+    let mut buf = Vec::new();
+    let _ = (&mut stream)
+        .take(OUTPUT_LIMIT as u64 + 1)
+        .read_to_end(&mut buf);
+    // Drain the rest so the command does not block on a full pipe
+    let _ = io::copy(&mut stream, &mut io::sink());
+    let truncated = buf.len() > OUTPUT_LIMIT;
+    buf.truncate(OUTPUT_LIMIT);
+    let mut text = String::from_utf8_lossy(&buf).into_owned();
+    if truncated {
+        text.push_str("\n… output truncated");
+    }
+    text
+}
+
+/// Run `command` in every repository with at most `jobs` running at once.
+/// `on_result` is called as each repository finishes and returns `false` to
+/// cancel the remaining work, which is reported through the returned flag.
+/// Setting `cancel` kills the running commands and skips the rest; with
+/// fail-fast, repositories not yet started after the first failure are
+/// skipped too.
+pub fn run_in_repos<F>(
+    paths: Vec<PathBuf>,
+    command: &ExecCommand,
+    jobs: usize,
+    options: &ExecOptions,
+    cancel: &AtomicBool,
+    on_result: F,
+) -> (Vec<(PathBuf, ExecOutcome)>, bool)
+where
+    F: Fn(&Path, &ExecOutcome) -> bool + Sync,
+{
+    let failed = AtomicBool::new(false);
+    let cancelled = AtomicBool::new(false);
+    let time_limit = options.time_limit();
+    run_parallel(
+        paths,
+        jobs,
+        |path| {
+            let outcome = if cancel.load(Ordering::Relaxed)
+                || options.fail_fast && failed.load(Ordering::Relaxed)
+            {
+                ExecOutcome::Skipped
+            } else {
+                run_command(&path, command, time_limit, cancel)
+            };
+            if !outcome.is_success() && !matches!(outcome, ExecOutcome::Skipped) {
+                failed.store(true, Ordering::Relaxed);
+            }
+            if !on_result(&path, &outcome) {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+            (path, outcome)
+        },
+        |_| !cancelled.load(Ordering::Relaxed),
+    )
+}
+
+/// Entry point for `git-dash exec`: run the command in every repository
+/// of `targets`, print each repository's output as it finishes and a
+/// summary at the end. Returns the process exit code.
+pub fn run_cli(
+    targets: &ScanTargets,
+    discovery: &DiscoveryOptions,
+    command: &ExecCommand,
+    jobs: usize,
+    options: &ExecOptions,
+) -> i32 {
+    let (_, repos) = discover_targets(targets, discovery, |_, _, _| true);
+    let paths: Vec<PathBuf> = repos.into_iter().map(|repo| repo.path).collect();
+    if paths.is_empty() {
+        let roots: Vec<String> = targets
+            .roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect();
+        eprintln!("No Git repositories found under {}", roots.join(", "));
+        return 1;
+    }
+
+    let cancel = AtomicBool::new(false);
+    let (outcomes, _) = run_in_repos(paths, command, jobs, options, &cancel, |path, outcome| {
+        let root = targets.root_of(path).unwrap_or(Path::new(""));
+        print_outcome(root, path, outcome);
+        true
+    });
+    let (succeeded, failed, skipped) = tally(&outcomes);
+    println!("{succeeded} succeeded, {failed} failed, {skipped} skipped");
+    if failed > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+fn print_outcome(root: &Path, path: &Path, outcome: &ExecOutcome) {
+    use std::io::Write;
+
+    let display = path.strip_prefix(root).unwrap_or(path);
+    let display = if display.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        display
+    };
+    // Hold the lock so output of parallel repositories does not interleave
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "==> {} [{}]", display.display(), outcome.summary());
+    match outcome {
+        ExecOutcome::Finished(output) => {
+            let _ = stdout.write_all(output.stdout.as_bytes());
+            if !output.stdout.is_empty() && !output.stdout.ends_with('\n') {
+                let _ = writeln!(stdout);
+            }
+            let _ = stdout.flush();
+            let mut stderr = std::io::stderr().lock();
+            let _ = stderr.write_all(output.stderr.as_bytes());
+            if !output.stderr.is_empty() && !output.stderr.ends_with('\n') {
+                let _ = writeln!(stderr);
+            }
+        }
+        ExecOutcome::Error(_) | ExecOutcome::Skipped => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished(exit_code: Option<i32>) -> ExecOutcome {
+        ExecOutcome::Finished(ExecOutput {
+            exit_code,
+            stdout: String::new(),
+            stderr: String::new(),
+            elapsed: Duration::from_millis(1500),
+        })
+    }
+
+    #[test]
+    fn test_outcome_summary() {
+        assert_eq!(finished(Some(0)).summary(), "exit 0 (1.5s)");
+        assert_eq!(finished(None).summary(), "killed (1.5s)");
+        assert_eq!(ExecOutcome::Skipped.summary(), "skipped");
+    }
+
+    #[test]
+    fn test_tally() {
+        let outcomes = vec![
+            (PathBuf::from("a"), finished(Some(0))),
+            (PathBuf::from("b"), finished(Some(2))),
+            (
+                PathBuf::from("c"),
+                ExecOutcome::Error("not found".to_string()),
+            ),
+            (PathBuf::from("d"), ExecOutcome::Skipped),
+        ];
+        assert_eq!(tally(&outcomes), (1, 2, 1));
+    }
+
+    #[test]
+    fn test_empty_command() {
+        let cancel = AtomicBool::new(false);
+        let outcome = run_command(
+            Path::new("."),
+            &ExecCommand::Args(Vec::new()),
+            None,
+            &cancel,
+        );
+        assert!(matches!(outcome, ExecOutcome::Error(_)));
+    }
+
+    #[test]
+    fn test_timeout_kills_command() {
+        let cancel = AtomicBool::new(false);
+        // The piped `sleep` is a grandchild that holds stdout open
+        let command = ExecCommand::Shell("echo partial; sleep 30 | cat".to_string());
+        let outcome = run_command(
+            Path::new("."),
+            &command,
+            Some(Duration::from_secs(1)),
+            &cancel,
+        );
+        let ExecOutcome::Finished(output) = outcome else {
+            panic!("killed command keeps its output");
+        };
+        assert_eq!(output.exit_code, None);
+        assert_eq!(output.stdout, "partial\n");
+        assert!(output.stderr.ends_with("timed out after 1s"));
+        assert!(output.elapsed < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_output_is_limited() {
+        let output = read_limited(io::repeat(b'x').take(OUTPUT_LIMIT as u64 * 4));
+        assert!(output.starts_with(&"x".repeat(OUTPUT_LIMIT)));
+        assert!(output.ends_with("output truncated"));
+    }
+
+    #[test]
+    fn test_cancel_skips_remaining_repos() {
+        let cancel = AtomicBool::new(true);
+        let paths = vec![PathBuf::from("."), PathBuf::from(".")];
+        let command = ExecCommand::Shell("true".to_string());
+        let (outcomes, _) = run_in_repos(
+            paths,
+            &command,
+            1,
+            &ExecOptions::default(),
+            &cancel,
+            |_, _| true,
+        );
+        assert_eq!(tally(&outcomes), (0, 0, 2));
+    }
+}
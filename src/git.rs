@@ -33,12 +33,17 @@ pub fn friendly_error(raw: &str) -> String {
     if raw.contains("refusing to merge unrelated histories") {
         return "Cannot merge - histories are unrelated".to_string();
     }
-    if raw.contains("would be overwritten by merge") {
+    if raw.contains("would be overwritten by merge")
+        || raw.contains("would be overwritten by checkout")
+    {
         return "Local changes would be overwritten - commit or stash first".to_string();
     }
     if raw.contains("divergent branches") || raw.contains("have diverged") {
         return "Local and remote branches have diverged".to_string();
     }
+    if raw.contains("not fully merged") {
+        return "Branch is not fully merged - merge it first".to_string();
+    }
     if raw.contains("everything up-to-date") {
         return "Already up to date".to_string();
     }
@@ -79,11 +84,58 @@ pub fn git_pull(path: &Path) -> Result<String, String> {
     Ok(String::from_utf8_lossy(&output).trim().to_string())
 }
 
+/// Update remote-tracking branches of all remotes, pruning deleted ones.
+pub fn git_fetch(path: &Path) -> Result<String, String> {
+    let output = run_git(path, &["fetch", "--all", "--prune"], GIT_TIMEOUT)?;
+    Ok(String::from_utf8_lossy(&output).trim().to_string())
+}
+
 pub fn git_push(path: &Path) -> Result<String, String> {
     let output = run_git(path, &["push"], GIT_TIMEOUT)?;
     Ok(String::from_utf8_lossy(&output).trim().to_string())
 }
 
+/// Switch to `branch`, creating it from HEAD when `create` is set. A branch
+/// that only exists on a remote is checked out as a tracking branch. Refuses
+/// when tracked files have uncommitted changes.
+pub fn git_checkout(path: &Path, branch: &str, create: bool) -> Result<String, String> {
+    if has_tracked_changes(path)? {
+        return Err("Working tree has uncommitted changes - commit or stash first".to_string());
+    }
+    if create {
+        run_git(path, &["switch", "--create", branch], GIT_TIMEOUT)?;
+        Ok(format!("created and switched to {branch}"))
+    } else {
+        run_git(path, &["switch", branch], GIT_TIMEOUT)?;
+        Ok(format!("switched to {branch}"))
+    }
+}
+
+/// Delete a local branch with `git branch -d`, which refuses unmerged branches.
+pub fn git_delete_branch(path: &Path, branch: &str) -> Result<String, String> {
+    let output = run_git(path, &["branch", "-d", branch], GIT_TIMEOUT)?;
+    Ok(String::from_utf8_lossy(&output).trim().to_string())
+}
+
+/// Force-delete several local branches in one `git branch -D` call. Used by
+/// the prune workflow, where every branch was explicitly reviewed.
+pub fn git_prune_branches(path: &Path, branches: &[String]) -> Result<String, String> {
+    let mut args = vec!["branch", "-D", "--"];
+    args.extend(branches.iter().map(String::as_str));
+    run_git(path, &args, GIT_TIMEOUT)?;
+    Ok(format!("deleted {}", branches.join(", ")))
+}
+
+/// Whether tracked files differ from HEAD; untracked files are ignored.
+pub fn has_tracked_changes(path: &Path) -> Result<bool, String> {
+    let output = run_git(
+        path,
+        &["status", "--porcelain", "--untracked-files=no"],
+        GIT_STATUS_TIMEOUT,
+    )?;
+    Ok(!output.is_empty())
+}
+
 pub fn run_git(path: &Path, args: &[&str], timeout: Duration) -> Result<Vec<u8>, String> {
     let start = Instant::now();
 
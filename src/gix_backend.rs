@@ -0,0 +1,426 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use gix::bstr::BString;
+use gix::diff::index::ChangeRef;
+use gix::dir::entry::{Kind, Status};
+use gix::refs::FullNameRef;
+use gix::remote::Direction;
+use gix::status::index_worktree;
+use gix::status::plumbing::index_as_worktree::{Change, EntryStatus};
+
+use crate::backend::GitBackend;
+use crate::branches::{sort_by_recency, BranchInfo};
+use crate::status::{RepoState, StatusExtras, StatusSnapshot, DETACHED_BRANCH};
+
+/// Reads status, remotes and branches in-process with gitoxide instead of
+/// spawning `git` for each repository. Network operations still use the
+/// `git` binary.
+pub struct GixBackend;
+
+impl GitBackend for GixBackend {
+    fn name(&self) -> &'static str {
+        "gix"
+    }
+
+    fn status(
+        &self,
+        path: &Path,
+        git_dir: &Path,
+        extras: StatusExtras,
+    ) -> Result<RepoState, String> {
+        let repo = open(path)?;
+        let head_name = repo.head_name().map_err(|err| err.to_string())?;
+        let head_id = repo.head_id().ok().map(|id| id.detach());
+
+        let mut snapshot = StatusSnapshot {
+            branch: match &head_name {
+                Some(name) => name.shorten().to_string(),
+                None => DETACHED_BRANCH.to_string(),
+            },
+            changes: changes(&repo)?,
+            remote_url: origin_url(&repo),
+            last_commit: extras
+                .last_commit
+                .then(|| repo.head_commit().ok())
+                .flatten()
+                .and_then(|commit| commit.time().ok())
+                .and_then(|time| u64::try_from(time.seconds).ok())
+                .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+            ..StatusSnapshot::default()
+        };
+        snapshot.dirty = !snapshot.changes.is_empty();
+        if let Some(name) = &head_name {
+            if let Some(tracking) = tracking_ref(&repo, name.as_ref()) {
+                snapshot.upstream = Some(tracking.shorten().to_string());
+                if let (Some(head), Some(upstream)) = (head_id, resolve(&repo, tracking.as_ref())) {
+                    snapshot.ahead_behind = Some(ahead_behind(&repo, head, upstream)?);
+                }
+            }
+        }
+        Ok(snapshot.into_repo_state(path, git_dir, extras))
+    }
+
+    fn remote_url(&self, path: &Path) -> Result<String, String> {
+        origin_url(&open(path)?).ok_or_else(|| "missing remote".to_string())
+    }
+
+    fn branches(&self, path: &Path) -> Result<Vec<BranchInfo>, String> {
+        let repo = open(path)?;
+        let head_name = repo.head_name().map_err(|err| err.to_string())?;
+        let head_id = repo.head_id().ok().map(|id| id.detach());
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |age| age.as_secs() as i64);
+
+        let references = repo.references().map_err(|err| err.to_string())?;
+        let mut branches = Vec::new();
+        for reference in references
+            .local_branches()
+            .map_err(|err| err.to_string())?
+            .flatten()
+        {
+            let name = reference.name().to_owned();
+            let Some(tip) = reference.try_id().map(|id| id.detach()) else {
+                continue;
+            };
+            let mut branch = BranchInfo {
+                name: name.shorten().to_string(),
+                is_head: head_name.as_ref() == Some(&name),
+                upstream: String::new(),
+                ahead: 0,
+                behind: 0,
+                gone: false,
+                merged: false,
+                last_commit: String::new(),
+                last_commit_unix: 0,
+            };
+            if let Some(tracking) = tracking_ref(&repo, name.as_ref()) {
+                branch.upstream = tracking.shorten().to_string();
+                match resolve(&repo, tracking.as_ref()) {
+                    Some(upstream) => {
+                        (branch.ahead, branch.behind) = ahead_behind(&repo, tip, upstream)?;
+                    }
+                    None => branch.gone = true,
+                }
+            }
+            // Unborn HEAD has nothing to be merged into
+            if let Some(head) = head_id {
+                branch.merged = tip == head
+                    || repo
+                        .merge_base(tip, head)
+                        .is_ok_and(|base| base.detach() == tip);
+            }
+            if let Ok(time) = repo
+                .find_commit(tip)
+                .map_err(|err| err.to_string())
+                .and_then(|commit| commit.time().map_err(|err| err.to_string()))
+            {
+                branch.last_commit_unix = time.seconds;
+                branch.last_commit = relative_date(time.seconds, now);
+            }
+            branches.push(branch);
+        }
+        sort_by_recency(&mut branches);
+        Ok(branches)
+    }
+}
+
+fn open(path: &Path) -> Result<gix::Repository, String> {
+    gix::open(path).map_err(|err| err.to_string())
+}
+
+fn origin_url(repo: &gix::Repository) -> Option<String> {
+    let url = repo
+        .config_snapshot()
+        .string("remote.origin.url")?
+        .to_string();
+    let url = url.trim().to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+/// Remote-tracking ref of a local branch, e.g. `refs/remotes/origin/main`,
+/// whether or not it exists.
+fn tracking_ref(repo: &gix::Repository, branch: &FullNameRef) -> Option<gix::refs::FullName> {
+    repo.branch_remote_tracking_ref_name(branch, Direction::Fetch)?
+        .ok()
+        .map(|name| name.into_owned())
+}
+
+fn resolve(repo: &gix::Repository, name: &FullNameRef) -> Option<gix::ObjectId> {
+    let mut reference = repo.try_find_reference(name).ok()??;
+    reference.peel_to_id().ok().map(|id| id.detach())
+}
+
+/// Commits only reachable from `local`, and commits only reachable from
+/// `upstream`.
+fn ahead_behind(
+    repo: &gix::Repository,
+    local: gix::ObjectId,
+    upstream: gix::ObjectId,
+) -> Result<(u32, u32), String> {
+    let count = |tip: gix::ObjectId, hidden: gix::ObjectId| -> Result<u32, String> {
+        let walk = repo
+            .rev_walk([tip])
+            .with_hidden([hidden])
+            .all()
+            .map_err(|err| err.to_string())?;
+        Ok(walk.filter(Result::is_ok).count() as u32)
+    };
+    Ok((count(local, upstream)?, count(upstream, local)?))
+}
+
+/// Changed paths with the same short codes as `git status --porcelain=2`:
+/// the index code wins over the worktree code, untracked paths come last
+/// and untracked directories end in `/`.
+fn changes(repo: &gix::Repository) -> Result<Vec<(String, String)>, String> {
+    let mut tracked: BTreeMap<BString, (Option<char>, Option<char>)> = BTreeMap::new();
+    let mut untracked = Vec::new();
+
+    let status = repo
+        .status(gix::progress::Discard)
+        .map_err(|err| err.to_string())?
+        .into_iter(Vec::<BString>::new())
+        .map_err(|err| err.to_string())?;
+    for item in status {
+        match item.map_err(|err| err.to_string())? {
+            gix::status::Item::TreeIndex(change) => {
+                let (code, location) = match &change {
+                    ChangeRef::Addition { location, .. } => ('A', location),
+                    ChangeRef::Deletion { location, .. } => ('D', location),
+                    ChangeRef::Modification { location, .. } => ('M', location),
+                    ChangeRef::Rewrite { location, copy, .. } => {
+                        (if *copy { 'C' } else { 'R' }, location)
+                    }
+                };
+                let entry = tracked.entry(location.as_ref().to_owned()).or_default();
+                if entry.0 != Some('U') {
+                    entry.0 = Some(code);
+                }
+            }
+            gix::status::Item::IndexWorktree(index_worktree::Item::Modification {
+                rela_path,
+                status,
+                ..
+            }) => {
+                let entry = tracked.entry(rela_path).or_default();
+                match status {
+                    EntryStatus::Conflict { .. } => entry.0 = Some('U'),
+                    EntryStatus::Change(Change::Removed) => entry.1 = Some('D'),
+                    EntryStatus::Change(Change::Type { .. }) => entry.1 = Some('T'),
+                    EntryStatus::Change(_) => entry.1 = Some('M'),
+                    EntryStatus::IntentToAdd => entry.1 = Some('A'),
+                    EntryStatus::NeedsUpdate(_) => {}
+                }
+            }
+            gix::status::Item::IndexWorktree(index_worktree::Item::DirectoryContents {
+                entry,
+                ..
+            })
+            | gix::status::Item::IndexWorktree(index_worktree::Item::Rewrite {
+                dirwalk_entry: entry,
+                ..
+            }) => {
+                if entry.status != Status::Untracked {
+                    continue;
+                }
+                let mut path = entry.rela_path.to_string();
+                if matches!(entry.disk_kind, Some(Kind::Directory | Kind::Repository)) {
+                    path.push('/');
+                }
+                untracked.push(("??".to_string(), path));
+            }
+        }
+    }
+
+    let mut changes: Vec<(String, String)> = tracked
+        .into_iter()
+        .filter_map(|(path, codes)| match codes {
+            (Some(code), _) | (None, Some(code)) => Some((code.to_string(), path.to_string())),
+            (None, None) => None,
+        })
+        .collect();
+    untracked.sort();
+    changes.extend(untracked);
+    Ok(changes)
+}
+
+/// Commit age in the words of `git log --date=relative`, e.g. "3 weeks ago".
+fn relative_date(unix: i64, now: i64) -> String {
+    fn ago(count: i64, unit: &str) -> String {
+        let plural = if count == 1 { "" } else { "s" };
+        format!("{count} {unit}{plural} ago")
+    }
+
+    let mut diff = now - unix;
+    if diff < 0 {
+        return "in the future".to_string();
+    }
+    if diff < 90 {
+        return ago(diff, "second");
+    }
+    diff = (diff + 30) / 60;
+    if diff < 90 {
+        return ago(diff, "minute");
+    }
+    diff = (diff + 30) / 60;
+    if diff < 36 {
+        return ago(diff, "hour");
+    }
+    diff = (diff + 12) / 24;
+    if diff < 14 {
+        return ago(diff, "day");
+    }
+    if diff < 70 {
+        return ago((diff + 3) / 7, "week");
+    }
+    if diff < 365 {
+        return ago((diff + 15) / 30, "month");
+    }
+    if diff < 1825 {
+        let total_months = (diff * 12 * 2 + 365) / (365 * 2);
+        let (years, months) = (total_months / 12, total_months % 12);
+        if months == 0 {
+            return ago(years, "year");
+        }
+        let years_plural = if years == 1 { "" } else { "s" };
+        return format!("{years} year{years_plural}, {}", ago(months, "month"));
+    }
+    ago((diff + 183) / 365, "year")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::CliBackend;
+    use crate::test_support::{git, TempDir};
+    use std::fs;
+
+    /// A clone with local and upstream commits, staged, unstaged and
+    /// untracked changes, a stash and a branch whose upstream is gone.
+    fn fixture(name: &str) -> (TempDir, std::path::PathBuf) {
+        let root = TempDir::new(&format!("gix-{name}"));
+        let origin = root.join("origin.git");
+        let seed = root.join("seed");
+        let clone = root.join("clone");
+        fs::create_dir_all(&seed).unwrap();
+        git(&root, &["init", "-q", "--bare", "-b", "main", "origin.git"]);
+        git(&seed, &["init", "-q", "-b", "main"]);
+        fs::write(seed.join("a.txt"), "a\n").unwrap();
+        fs::write(seed.join("b.txt"), "b\n").unwrap();
+        git(&seed, &["add", "."]);
+        git(&seed, &["commit", "-q", "-m", "initial"]);
+        git(
+            &seed,
+            &["remote", "add", "origin", origin.to_str().unwrap()],
+        );
+        git(&seed, &["push", "-q", "origin", "main", "main:feature"]);
+        git(&root, &["clone", "-q", origin.to_str().unwrap(), "clone"]);
+
+        git(&seed, &["commit", "-q", "--allow-empty", "-m", "upstream"]);
+        git(&seed, &["push", "-q", "origin", "main"]);
+        git(&clone, &["fetch", "-q"]);
+        git(&clone, &["commit", "-q", "--allow-empty", "-m", "local"]);
+        git(
+            &clone,
+            &["branch", "-q", "--track", "feature", "origin/feature"],
+        );
+        git(&clone, &["branch", "-q", "merged-topic", "HEAD~1"]);
+        git(&seed, &["push", "-q", "origin", ":feature"]);
+        git(&clone, &["fetch", "-q", "--prune"]);
+
+        fs::write(clone.join("a.txt"), "stashed\n").unwrap();
+        git(&clone, &["stash", "-q"]);
+        fs::write(clone.join("a.txt"), "changed\n").unwrap();
+        fs::write(clone.join("c.txt"), "c\n").unwrap();
+        git(&clone, &["add", "c.txt"]);
+        git(&clone, &["rm", "-q", "b.txt"]);
+        fs::write(clone.join("notes.txt"), "n\n").unwrap();
+        fs::create_dir_all(clone.join("scratch")).unwrap();
+        fs::write(clone.join("scratch/x.txt"), "x\n").unwrap();
+        (root, clone)
+    }
+
+    #[test]
+    fn test_status_matches_cli() {
+        let (_root, clone) = fixture("status");
+        let git_dir = clone.join(".git");
+        let cli = CliBackend
+            .status(&clone, &git_dir, StatusExtras::ALL)
+            .unwrap();
+        let gix = GixBackend
+            .status(&clone, &git_dir, StatusExtras::ALL)
+            .unwrap();
+
+        assert_eq!(gix.branch, cli.branch);
+        assert_eq!(gix.upstream, cli.upstream);
+        assert_eq!(gix.ahead_behind, cli.ahead_behind);
+        assert_eq!(gix.ahead_behind, "+1/-1");
+        assert_eq!(gix.dirty, cli.dirty);
+        assert_eq!(gix.changes, cli.changes);
+        assert_eq!(gix.change_summary, cli.change_summary);
+        assert_eq!(gix.remote_url, cli.remote_url);
+        assert_eq!(gix.last_commit, cli.last_commit);
+        assert_eq!(gix.stash_count, 1);
+        assert_eq!(gix.origin_url, cli.origin_url);
+        assert_eq!(GixBackend.remote_url(&clone), CliBackend.remote_url(&clone));
+        assert!(GixBackend.remote_url(&clone).is_ok());
+    }
+
+    #[test]
+    fn test_branches_match_cli() {
+        let (_root, clone) = fixture("branches");
+        let mut cli = CliBackend.branches(&clone).unwrap();
+        let mut gix = GixBackend.branches(&clone).unwrap();
+        // Relative dates depend on when each backend ran
+        for branch in cli.iter_mut().chain(gix.iter_mut()) {
+            branch.last_commit.clear();
+        }
+        assert_eq!(gix, cli);
+        assert!(gix
+            .iter()
+            .any(|branch| branch.name == "feature" && branch.gone));
+        assert!(gix
+            .iter()
+            .any(|branch| branch.name == "merged-topic" && branch.merged));
+    }
+
+    #[test]
+    fn test_clean_and_detached_match_cli() {
+        let (_root, clone) = fixture("detached");
+        git(&clone, &["reset", "-q", "--hard"]);
+        git(&clone, &["clean", "-q", "-fd"]);
+        git(&clone, &["checkout", "-q", "--detach"]);
+        let git_dir = clone.join(".git");
+        let cli = CliBackend
+            .status(&clone, &git_dir, StatusExtras::ALL)
+            .unwrap();
+        let gix = GixBackend
+            .status(&clone, &git_dir, StatusExtras::ALL)
+            .unwrap();
+        assert_eq!(gix.branch, DETACHED_BRANCH);
+        assert_eq!(gix.branch, cli.branch);
+        assert_eq!(gix.upstream, cli.upstream);
+        assert_eq!(gix.ahead_behind, cli.ahead_behind);
+        assert!(!gix.dirty && !cli.dirty);
+        assert_eq!(gix.changes, cli.changes);
+
+        git(&clone, &["remote", "remove", "origin"]);
+        assert!(GixBackend.remote_url(&clone).is_err());
+        assert!(CliBackend.remote_url(&clone).is_err());
+    }
+
+    #[test]
+    fn test_relative_date() {
+        let now = 1_700_000_000;
+        assert_eq!(relative_date(now - 1, now), "1 second ago");
+        assert_eq!(relative_date(now - 120, now), "2 minutes ago");
+        assert_eq!(relative_date(now - 3 * 3600, now), "3 hours ago");
+        assert_eq!(relative_date(now - 5 * 86400, now), "5 days ago");
+        assert_eq!(relative_date(now - 30 * 86400, now), "4 weeks ago");
+        assert_eq!(relative_date(now - 200 * 86400, now), "7 months ago");
+        assert_eq!(relative_date(now - 400 * 86400, now), "1 year, 1 month ago");
+        assert_eq!(relative_date(now - 3000 * 86400, now), "8 years ago");
+    }
+}
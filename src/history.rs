@@ -0,0 +1,134 @@
+use std::path::Path;
+
+use crate::git::{run_git, GIT_TIMEOUT};
+
+/// Maximum number of commits loaded into the log pane.
+pub const LOG_LIMIT: usize = 500;
+
+// Unit separator keeps subjects with arbitrary punctuation parseable.
+const FIELD_SEPARATOR: char = '\u{1f}';
+const LOG_FORMAT: &str = "--format=%h%x1f%an%x1f%ar%x1f%D%x1f%s";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LogMode {
+    /// Commits reachable from HEAD.
+    History,
+    /// Commits on the upstream that HEAD does not have yet (what a pull brings in).
+    Incoming,
+    /// Commits on HEAD that the upstream does not have yet (what a push sends).
+    Outgoing,
+}
+
+impl LogMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            LogMode::History => "History",
+            LogMode::Incoming => "Incoming",
+            LogMode::Outgoing => "Outgoing",
+        }
+    }
+
+    pub fn next(self) -> LogMode {
+        match self {
+            LogMode::History => LogMode::Incoming,
+            LogMode::Incoming => LogMode::Outgoing,
+            LogMode::Outgoing => LogMode::History,
+        }
+    }
+
+    fn revision_range(self) -> &'static str {
+        match self {
+            LogMode::History => "HEAD",
+            LogMode::Incoming => "HEAD..@{upstream}",
+            LogMode::Outgoing => "@{upstream}..HEAD",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEntry {
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    pub refs: String,
+    pub subject: String,
+}
+
+pub fn git_log(path: &Path, mode: LogMode) -> Result<Vec<LogEntry>, String> {
+    let limit = format!("--max-count={LOG_LIMIT}");
+    let args = [
+        "log",
+        "--no-color",
+        limit.as_str(),
+        LOG_FORMAT,
+        mode.revision_range(),
+        "--",
+    ];
+    let output = run_git(path, &args, GIT_TIMEOUT).map_err(|err| log_error(mode, &err))?;
+    Ok(parse_log(&String::from_utf8_lossy(&output)))
+}
+
+fn log_error(mode: LogMode, raw: &str) -> String {
+    if mode != LogMode::History && raw.contains("upstream") {
+        return "No upstream configured for this branch".to_string();
+    }
+    if raw.contains("does not have any commits") || raw.contains("unknown revision") {
+        return "No commits yet".to_string();
+    }
+    raw.to_string()
+}
+
+fn parse_log(output: &str) -> Vec<LogEntry> {
+    output.lines().filter_map(parse_log_line).collect()
+}
+
+fn parse_log_line(line: &str) -> Option<LogEntry> {
+    let mut parts = line.splitn(5, FIELD_SEPARATOR);
+    let hash = parts.next()?.to_string();
+    if hash.is_empty() {
+        return None;
+    }
+    Some(LogEntry {
+        hash,
+        author: parts.next()?.to_string(),
+        date: parts.next()?.to_string(),
+        refs: parts.next()?.to_string(),
+        subject: parts.next()?.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_line() {
+        let line = "abc1234\u{1f}Jane Doe\u{1f}2 days ago\u{1f}HEAD -> main, origin/main\u{1f}Fix: handle a|b";
+        assert_eq!(
+            parse_log_line(line),
+            Some(LogEntry {
+                hash: "abc1234".to_string(),
+                author: "Jane Doe".to_string(),
+                date: "2 days ago".to_string(),
+                refs: "HEAD -> main, origin/main".to_string(),
+                subject: "Fix: handle a|b".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_log_skips_malformed_lines() {
+        let output = "abc1234\u{1f}Jane\u{1f}now\u{1f}\u{1f}Initial\n\ngarbage\n";
+        let entries = parse_log(output);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].subject, "Initial");
+        assert!(entries[0].refs.is_empty());
+    }
+
+    #[test]
+    fn test_log_mode_cycle() {
+        assert_eq!(LogMode::History.next(), LogMode::Incoming);
+        assert_eq!(LogMode::Incoming.next(), LogMode::Outgoing);
+        assert_eq!(LogMode::Outgoing.next(), LogMode::History);
+    }
+}
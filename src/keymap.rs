@@ -0,0 +1,888 @@
+use std::collections::BTreeMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Named action a key sequence can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    Quit,
+    Help,
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+    Refresh,
+    Fetch,
+    Pull,
+    Push,
+    Mark,
+    MarkAll,
+    BatchCheckout,
+    Prune,
+    Exec,
+    Log,
+    Details,
+    Branches,
+    Open,
+    Browse,
+    Copy,
+    CycleSort,
+    ReverseSort,
+    Search,
+    ClearSearch,
+    ToggleMouse,
+    Close,
+    NextSection,
+    PrevSection,
+    CycleLogMode,
+    DiffFile,
+    DiffAll,
+    Checkout,
+    DeleteBranch,
+    ToggleSelection,
+    ToggleAll,
+    DeleteSelected,
+    CheckoutPresent,
+    CheckoutCreate,
+    Palette,
+    Columns,
+    AddWorktree,
+    PruneWorktrees,
+    UpdateMirror,
+    CloneMissing,
+    CancelExec,
+    Accept,
+    Cancel,
+    DeleteChar,
+    MoveDown,
+    MoveUp,
+    Widen,
+    Narrow,
+    ResetWidth,
+}
+
+impl Command {
+    pub const ALL: [Command; 56] = [
+        Command::Quit,
+        Command::Help,
+        Command::Down,
+        Command::Up,
+        Command::PageDown,
+        Command::PageUp,
+        Command::First,
+        Command::Last,
+        Command::Refresh,
+        Command::Fetch,
+        Command::Pull,
+        Command::Push,
+        Command::Mark,
+        Command::MarkAll,
+        Command::BatchCheckout,
+        Command::Prune,
+        Command::Exec,
+        Command::Log,
+        Command::Details,
+        Command::Branches,
+        Command::Open,
+        Command::Browse,
+        Command::Copy,
+        Command::CycleSort,
+        Command::ReverseSort,
+        Command::Search,
+        Command::ClearSearch,
+        Command::ToggleMouse,
+        Command::Close,
+        Command::NextSection,
+        Command::PrevSection,
+        Command::CycleLogMode,
+        Command::DiffFile,
+        Command::DiffAll,
+        Command::Checkout,
+        Command::DeleteBranch,
+        Command::ToggleSelection,
+        Command::ToggleAll,
+        Command::DeleteSelected,
+        Command::CheckoutPresent,
+        Command::CheckoutCreate,
+        Command::Palette,
+        Command::Columns,
+        Command::AddWorktree,
+        Command::PruneWorktrees,
+        Command::UpdateMirror,
+        Command::CloneMissing,
+        Command::CancelExec,
+        Command::Accept,
+        Command::Cancel,
+        Command::DeleteChar,
+        Command::MoveDown,
+        Command::MoveUp,
+        Command::Widen,
+        Command::Narrow,
+        Command::ResetWidth,
+    ];
+
+    /// Name used in the `[keys]` config section.
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::Quit => "quit",
+            Command::Help => "help",
+            Command::Down => "down",
+            Command::Up => "up",
+            Command::PageDown => "page-down",
+            Command::PageUp => "page-up",
+            Command::First => "first",
+            Command::Last => "last",
+            Command::Refresh => "refresh",
+            Command::Fetch => "fetch",
+            Command::Pull => "pull",
+            Command::Push => "push",
+            Command::Mark => "mark",
+            Command::MarkAll => "mark-all",
+            Command::BatchCheckout => "batch-checkout",
+            Command::Prune => "prune",
+            Command::Exec => "exec",
+            Command::Log => "log",
+            Command::Details => "details",
+            Command::Branches => "branches",
+            Command::Open => "open",
+            Command::Browse => "browse",
+            Command::Copy => "copy",
+            Command::CycleSort => "cycle-sort",
+            Command::ReverseSort => "reverse-sort",
+            Command::Search => "search",
+            Command::ClearSearch => "clear-search",
+            Command::ToggleMouse => "toggle-mouse",
+            Command::Close => "close",
+            Command::NextSection => "next-section",
+            Command::PrevSection => "prev-section",
+            Command::CycleLogMode => "cycle-log-mode",
+            Command::DiffFile => "diff-file",
+            Command::DiffAll => "diff-all",
+            Command::Checkout => "checkout",
+            Command::DeleteBranch => "delete-branch",
+            Command::ToggleSelection => "toggle-selection",
+            Command::ToggleAll => "toggle-all",
+            Command::DeleteSelected => "delete-selected",
+            Command::CheckoutPresent => "checkout-present",
+            Command::CheckoutCreate => "checkout-create",
+            Command::Palette => "palette",
+            Command::Columns => "columns",
+            Command::AddWorktree => "add-worktree",
+            Command::PruneWorktrees => "prune-worktrees",
+            Command::UpdateMirror => "update-mirror",
+            Command::CloneMissing => "clone-missing",
+            Command::CancelExec => "cancel-exec",
+            Command::Accept => "accept",
+            Command::Cancel => "cancel",
+            Command::DeleteChar => "delete-char",
+            Command::MoveDown => "move-down",
+            Command::MoveUp => "move-up",
+            Command::Widen => "widen",
+            Command::Narrow => "narrow",
+            Command::ResetWidth => "reset-width",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Command::Quit => "Quit git-dash",
+            Command::Help => "Toggle this help screen",
+            Command::Down => "Move selection down",
+            Command::Up => "Move selection up",
+            Command::PageDown => "Page down",
+            Command::PageUp => "Page up",
+            Command::First => "Jump to top",
+            Command::Last => "Jump to bottom",
+            Command::Refresh => "Refresh repository status",
+            Command::Fetch => "Fetch all remotes (with confirmation)",
+            Command::Pull => "Pull (with confirmation)",
+            Command::Push => "Push (with confirmation)",
+            Command::Mark => "Mark repository",
+            Command::MarkAll => "Mark all filtered repositories",
+            Command::BatchCheckout => "Checkout branch in marked (or filtered) repos",
+            Command::Prune => "Prune gone/merged branches across all repos",
+            Command::Exec => "Run a shell command in marked (or filtered) repos",
+            Command::Log => "Show commit log",
+            Command::Details => "Show changed files",
+            Command::Branches => "Show branches",
+            Command::Open => "Open repository in editor, shell or git UI",
+            Command::Browse => "Browse repository on its hosting service",
+            Command::Copy => "Copy path, branch, remote URL or error",
+            Command::CycleSort => "Sort by the next column",
+            Command::ReverseSort => "Reverse the sort order",
+            Command::Search => "Search/filter repositories by name",
+            Command::ClearSearch => "Clear search filter",
+            Command::ToggleMouse => "Toggle mouse support",
+            Command::Close => "Close pane",
+            Command::NextSection => "Next hunk / repository",
+            Command::PrevSection => "Previous hunk / repository",
+            Command::CycleLogMode => "Switch History → Incoming → Outgoing",
+            Command::DiffFile => "Diff selected file",
+            Command::DiffAll => "Diff whole repository",
+            Command::Checkout => "Checkout branch (clean tree only)",
+            Command::DeleteBranch => "Delete merged branch",
+            Command::ToggleSelection => "Toggle branch, repository or column",
+            Command::ToggleAll => "Toggle all branches",
+            Command::DeleteSelected => "Delete selected branches",
+            Command::CheckoutPresent => "Checkout where the branch exists",
+            Command::CheckoutCreate => "Checkout, creating missing branches",
+            Command::Palette => "Open the command palette",
+            Command::Columns => "Choose, reorder and resize table columns",
+            Command::AddWorktree => "Check out a branch in a new linked worktree",
+            Command::PruneWorktrees => {
+                "Prune worktrees whose directory is gone (with confirmation)"
+            }
+            Command::UpdateMirror => {
+                "Update a bare repository from its remotes (with confirmation)"
+            }
+            Command::CloneMissing => "Clone repositories declared in the manifest but missing",
+            Command::CancelExec => "Stop the running command in every repository",
+            Command::Accept => "Confirm, submit or run the highlighted entry",
+            Command::Cancel => "Cancel",
+            Command::DeleteChar => "Delete the last character",
+            Command::MoveDown => "Move the column down",
+            Command::MoveUp => "Move the column up",
+            Command::Widen => "Widen the column to a fixed width",
+            Command::Narrow => "Narrow the column to a fixed width",
+            Command::ResetWidth => "Let the column size itself again",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Command> {
+        Command::ALL
+            .into_iter()
+            .find(|command| command.name() == name)
+    }
+}
+
+/// Where a binding applies. Bindings of a specific pane take precedence over
+/// the general `Pane` bindings. Menus, prompts, confirmations, search input,
+/// the command palette and the column picker only see their own bindings;
+/// other keys there select menu entries or are typed as text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyContext {
+    Table,
+    Pane,
+    Log,
+    Detail,
+    Diff,
+    Branches,
+    Batch,
+    Prune,
+    Exec,
+    Menu,
+    Prompt,
+    Confirm,
+    Search,
+    Palette,
+    ColumnPicker,
+}
+
+impl KeyContext {
+    pub const ALL: [KeyContext; 15] = [
+        KeyContext::Table,
+        KeyContext::Pane,
+        KeyContext::Log,
+        KeyContext::Detail,
+        KeyContext::Diff,
+        KeyContext::Branches,
+        KeyContext::Batch,
+        KeyContext::Prune,
+        KeyContext::Exec,
+        KeyContext::Menu,
+        KeyContext::Prompt,
+        KeyContext::Confirm,
+        KeyContext::Search,
+        KeyContext::Palette,
+        KeyContext::ColumnPicker,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyContext::Table => "table",
+            KeyContext::Pane => "pane",
+            KeyContext::Log => "log",
+            KeyContext::Detail => "detail",
+            KeyContext::Diff => "diff",
+            KeyContext::Branches => "branches",
+            KeyContext::Batch => "batch",
+            KeyContext::Prune => "prune",
+            KeyContext::Exec => "exec",
+            KeyContext::Menu => "menu",
+            KeyContext::Prompt => "prompt",
+            KeyContext::Confirm => "confirm",
+            KeyContext::Search => "search",
+            KeyContext::Palette => "palette",
+            KeyContext::ColumnPicker => "columns",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<KeyContext> {
+        KeyContext::ALL
+            .into_iter()
+            .find(|context| context.name() == name)
+    }
+
+    /// Contexts searched for a key, most specific first.
+    fn chain(self) -> &'static [KeyContext] {
+        match self {
+            KeyContext::Table => &[KeyContext::Table],
+            KeyContext::Pane => &[KeyContext::Pane],
+            KeyContext::Log => &[KeyContext::Log, KeyContext::Pane],
+            KeyContext::Detail => &[KeyContext::Detail, KeyContext::Pane],
+            KeyContext::Diff => &[KeyContext::Diff, KeyContext::Pane],
+            KeyContext::Branches => &[KeyContext::Branches, KeyContext::Pane],
+            KeyContext::Batch => &[KeyContext::Batch, KeyContext::Pane],
+            KeyContext::Prune => &[KeyContext::Prune, KeyContext::Pane],
+            KeyContext::Exec => &[KeyContext::Exec, KeyContext::Pane],
+            KeyContext::Menu => &[KeyContext::Menu],
+            KeyContext::Prompt => &[KeyContext::Prompt],
+            KeyContext::Confirm => &[KeyContext::Confirm],
+            KeyContext::Search => &[KeyContext::Search],
+            KeyContext::Palette => &[KeyContext::Palette],
+            KeyContext::ColumnPicker => &[KeyContext::ColumnPicker],
+        }
+    }
+}
+
+/// A key with its modifiers. Shift is folded into the character for
+/// printable keys, so `G` and `Shift+g` are the same chord.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if let KeyCode::Char(_) = code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    /// Parse `x`, `Enter`, `Ctrl+c`, `Alt+Left`, `Shift+Tab`, `F5`, ...
+    fn parse(text: &str) -> Result<KeyChord, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        while let Some((prefix, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier in \"{text}\"")),
+            };
+            rest = key;
+        }
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => {
+                // Terminals report Shift+Tab as BackTab with Shift held
+                modifiers |= KeyModifiers::SHIFT;
+                KeyCode::BackTab
+            }
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            name => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                        KeyCode::Char(c.to_ascii_uppercase())
+                    }
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                        Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                        _ => return Err(format!("unknown key \"{text}\"")),
+                    },
+                }
+            }
+        };
+        Ok(KeyChord::new(code, modifiers))
+    }
+
+    pub fn label(self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "Shift+Tab".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::Insert => "Ins".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::F(n) => format!("F{n}"),
+            other => format!("{other:?}"),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt+");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) && self.code != KeyCode::BackTab {
+            label.push_str("Shift+");
+        }
+        label.push_str(&key);
+        label
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        KeyChord::new(key.code, key.modifiers)
+    }
+}
+
+/// Parse a space separated key sequence such as `g g` or `Ctrl+x s`.
+fn parse_sequence(text: &str) -> Result<Vec<KeyChord>, String> {
+    let chords = text
+        .split_whitespace()
+        .map(KeyChord::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    if chords.is_empty() {
+        return Err("empty key binding".to_string());
+    }
+    Ok(chords)
+}
+
+pub fn sequence_label(sequence: &[KeyChord]) -> String {
+    sequence
+        .iter()
+        .map(|chord| chord.label())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// One key or a list of keys bound to a command in the config.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum KeyBinding {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBinding {
+    fn sequences(&self) -> Vec<&str> {
+        match self {
+            KeyBinding::One(key) => vec![key.as_str()],
+            KeyBinding::Many(keys) => keys.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// `[keys.<context>]` tables mapping command names to key sequences.
+pub type KeyOverrides = BTreeMap<String, BTreeMap<String, KeyBinding>>;
+
+/// Outcome of looking up the keys typed so far.
+#[derive(Debug, PartialEq)]
+pub enum Lookup {
+    Command(Command),
+    /// The keys start a longer sequence; wait for more.
+    Prefix,
+    None,
+}
+
+struct Binding {
+    context: KeyContext,
+    keys: Vec<KeyChord>,
+    command: Command,
+}
+
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+const DEFAULT_BINDINGS: &[(KeyContext, Command, &[&str])] = &[
+    (KeyContext::Table, Command::Quit, &["q", "Ctrl+c"]),
+    (KeyContext::Table, Command::Down, &["j", "Down"]),
+    (KeyContext::Table, Command::Up, &["k", "Up"]),
+    (KeyContext::Table, Command::PageDown, &["PageDown"]),
+    (KeyContext::Table, Command::PageUp, &["PageUp"]),
+    (KeyContext::Table, Command::First, &["g", "Home"]),
+    (KeyContext::Table, Command::Last, &["G", "End"]),
+    (KeyContext::Table, Command::Fetch, &["f"]),
+    (KeyContext::Table, Command::Pull, &["p"]),
+    (KeyContext::Table, Command::Push, &["u"]),
+    (KeyContext::Table, Command::Refresh, &["r"]),
+    (KeyContext::Table, Command::Mark, &["Space"]),
+    (KeyContext::Table, Command::MarkAll, &["a"]),
+    (KeyContext::Table, Command::BatchCheckout, &["B"]),
+    (KeyContext::Table, Command::Prune, &["P"]),
+    (KeyContext::Table, Command::AddWorktree, &["W"]),
+    (KeyContext::Table, Command::PruneWorktrees, &["X"]),
+    (KeyContext::Table, Command::Exec, &["!"]),
+    (KeyContext::Table, Command::CloneMissing, &["c"]),
+    (KeyContext::Table, Command::Log, &["l"]),
+    (KeyContext::Table, Command::Details, &["Enter"]),
+    (KeyContext::Table, Command::Branches, &["b"]),
+    (KeyContext::Table, Command::Open, &["o"]),
+    (KeyContext::Table, Command::Browse, &["w"]),
+    (KeyContext::Table, Command::Copy, &["y"]),
+    (KeyContext::Table, Command::CycleSort, &["s"]),
+    (KeyContext::Table, Command::ReverseSort, &["S"]),
+    (KeyContext::Table, Command::Search, &["/"]),
+    (KeyContext::Table, Command::ClearSearch, &["Esc"]),
+    (KeyContext::Table, Command::ToggleMouse, &["M"]),
+    (KeyContext::Table, Command::Help, &["?"]),
+    (KeyContext::Table, Command::Palette, &[":", "Ctrl+p"]),
+    (KeyContext::Table, Command::Columns, &["C"]),
+    (KeyContext::Pane, Command::Close, &["q", "Esc"]),
+    (KeyContext::Pane, Command::Quit, &["Ctrl+c"]),
+    (KeyContext::Pane, Command::Down, &["j", "Down"]),
+    (KeyContext::Pane, Command::Up, &["k", "Up"]),
+    (KeyContext::Pane, Command::PageDown, &["PageDown"]),
+    (KeyContext::Pane, Command::PageUp, &["PageUp"]),
+    (KeyContext::Pane, Command::First, &["g", "Home"]),
+    (KeyContext::Pane, Command::Last, &["G", "End"]),
+    (KeyContext::Pane, Command::NextSection, &["n", "]"]),
+    (KeyContext::Pane, Command::PrevSection, &["N", "["]),
+    (KeyContext::Pane, Command::Open, &["o"]),
+    (KeyContext::Pane, Command::Browse, &["w"]),
+    (KeyContext::Pane, Command::Copy, &["y"]),
+    (KeyContext::Pane, Command::ToggleMouse, &["M"]),
+    (KeyContext::Pane, Command::Help, &["?"]),
+    (KeyContext::Pane, Command::Palette, &[":", "Ctrl+p"]),
+    (KeyContext::Log, Command::CycleLogMode, &["Tab"]),
+    (KeyContext::Detail, Command::DiffFile, &["Enter"]),
+    (KeyContext::Detail, Command::DiffAll, &["d"]),
+    (KeyContext::Detail, Command::Log, &["l"]),
+    (KeyContext::Detail, Command::Branches, &["b"]),
+    (KeyContext::Branches, Command::Checkout, &["Enter", "c"]),
+    (KeyContext::Branches, Command::DeleteBranch, &["D"]),
+    (KeyContext::Batch, Command::CheckoutPresent, &["y"]),
+    (KeyContext::Batch, Command::CheckoutCreate, &["c"]),
+    (KeyContext::Prune, Command::ToggleSelection, &["Space"]),
+    (KeyContext::Prune, Command::ToggleAll, &["a"]),
+    (KeyContext::Prune, Command::DeleteSelected, &["D", "Enter"]),
+    (KeyContext::Exec, Command::CancelExec, &["x"]),
+    (KeyContext::Menu, Command::Accept, &["Enter"]),
+    (KeyContext::Menu, Command::Cancel, &["Esc", "q"]),
+    (KeyContext::Menu, Command::Down, &["j", "Down"]),
+    (KeyContext::Menu, Command::Up, &["k", "Up"]),
+    (KeyContext::Prompt, Command::Accept, &["Enter"]),
+    (KeyContext::Prompt, Command::Cancel, &["Esc"]),
+    (KeyContext::Prompt, Command::DeleteChar, &["Backspace"]),
+    (KeyContext::Confirm, Command::Accept, &["y"]),
+    (KeyContext::Confirm, Command::Cancel, &["n", "Esc"]),
+    (KeyContext::Search, Command::Accept, &["Enter"]),
+    (KeyContext::Search, Command::Cancel, &["Esc"]),
+    (KeyContext::Search, Command::DeleteChar, &["Backspace"]),
+    (KeyContext::Palette, Command::Accept, &["Enter"]),
+    (KeyContext::Palette, Command::Cancel, &["Esc", "Ctrl+c"]),
+    (KeyContext::Palette, Command::Down, &["Down", "Ctrl+n"]),
+    (KeyContext::Palette, Command::Up, &["Up", "Ctrl+p"]),
+    (KeyContext::Palette, Command::DeleteChar, &["Backspace"]),
+    (KeyContext::ColumnPicker, Command::Accept, &["Enter"]),
+    (KeyContext::ColumnPicker, Command::Cancel, &["Esc", "q"]),
+    (KeyContext::ColumnPicker, Command::Down, &["j", "Down"]),
+    (KeyContext::ColumnPicker, Command::Up, &["k", "Up"]),
+    (
+        KeyContext::ColumnPicker,
+        Command::MoveDown,
+        &["J", "Shift+Down"],
+    ),
+    (
+        KeyContext::ColumnPicker,
+        Command::MoveUp,
+        &["K", "Shift+Up"],
+    ),
+    (
+        KeyContext::ColumnPicker,
+        Command::ToggleSelection,
+        &["Space"],
+    ),
+    (KeyContext::ColumnPicker, Command::Widen, &["+", "="]),
+    (KeyContext::ColumnPicker, Command::Narrow, &["-"]),
+    (KeyContext::ColumnPicker, Command::ResetWidth, &["0"]),
+];
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .flat_map(|(context, command, keys)| {
+                keys.iter().map(move |key| Binding {
+                    context: *context,
+                    keys: parse_sequence(key).expect("default key bindings parse"),
+                    command: *command,
+                })
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Default bindings with the config overrides applied. Binding a command
+    /// in a context replaces its default keys there; `[]` unbinds it.
+    pub fn with_overrides(overrides: &KeyOverrides) -> Result<Keymap, String> {
+        let mut keymap = Keymap::default();
+        for (context_name, commands) in overrides {
+            let context = KeyContext::from_name(context_name)
+                .ok_or_else(|| format!("unknown key context \"{context_name}\""))?;
+            for (command_name, binding) in commands {
+                let command = Command::from_name(command_name)
+                    .ok_or_else(|| format!("unknown command \"{command_name}\""))?;
+                keymap
+                    .bindings
+                    .retain(|b| !(b.context == context && b.command == command));
+                for sequence in binding.sequences() {
+                    keymap.bindings.push(Binding {
+                        context,
+                        keys: parse_sequence(sequence)
+                            .map_err(|err| format!("{context_name}.{command_name}: {err}"))?,
+                        command,
+                    });
+                }
+            }
+        }
+        let conflicts = keymap.conflicts();
+        if !conflicts.is_empty() {
+            return Err(format!(
+                "conflicting key bindings: {}",
+                conflicts.join("; ")
+            ));
+        }
+        Ok(keymap)
+    }
+
+    /// Bindings that can never fire: the same keys bound twice in one context,
+    /// or a sequence that starts with another sequence visible in the same context.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        for (i, a) in self.bindings.iter().enumerate() {
+            for b in &self.bindings[i + 1..] {
+                let same_context = a.context == b.context;
+                let visible = same_context
+                    || a.context.chain().contains(&b.context)
+                    || b.context.chain().contains(&a.context);
+                if !visible || a.command == b.command && a.keys == b.keys {
+                    continue;
+                }
+                let clash = if a.keys == b.keys {
+                    // A specific pane may deliberately shadow a general pane key
+                    same_context
+                } else {
+                    a.keys.starts_with(&b.keys) || b.keys.starts_with(&a.keys)
+                };
+                if clash {
+                    conflicts.push(format!(
+                        "{} \"{}\" ({}) and {} \"{}\" ({})",
+                        a.context.name(),
+                        sequence_label(&a.keys),
+                        a.command.name(),
+                        b.context.name(),
+                        sequence_label(&b.keys),
+                        b.command.name()
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+
+    pub fn lookup(&self, context: KeyContext, keys: &[KeyChord]) -> Lookup {
+        let mut prefix = false;
+        for scope in context.chain() {
+            for binding in self.bindings.iter().filter(|b| b.context == *scope) {
+                if binding.keys == keys {
+                    return Lookup::Command(binding.command);
+                }
+                prefix |= binding.keys.starts_with(keys);
+            }
+        }
+        if prefix {
+            Lookup::Prefix
+        } else {
+            Lookup::None
+        }
+    }
+
+    /// Labels of the key sequences running `command` in `context`.
+    pub fn keys_for(&self, context: KeyContext, command: Command) -> Vec<String> {
+        let Some(scope) = context.chain().iter().find(|scope| {
+            self.bindings
+                .iter()
+                .any(|b| b.context == **scope && b.command == command)
+        }) else {
+            return Vec::new();
+        };
+        self.bindings
+            .iter()
+            .filter(|b| b.context == *scope && b.command == command)
+            .map(|b| sequence_label(&b.keys))
+            .collect()
+    }
+
+    /// Commands bound directly in `context`, in binding order.
+    pub fn commands(&self, context: KeyContext) -> Vec<Command> {
+        let mut commands = Vec::new();
+        for binding in self.bindings.iter().filter(|b| b.context == context) {
+            if !commands.contains(&binding.command) {
+                commands.push(binding.command);
+            }
+        }
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(text: &str) -> KeyChord {
+        KeyChord::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_default_keymap_has_no_conflicts() {
+        assert_eq!(Keymap::default().conflicts(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_parse_chords() {
+        assert_eq!(
+            chord("G"),
+            KeyChord::new(KeyCode::Char('G'), KeyModifiers::SHIFT)
+        );
+        assert_eq!(chord("Shift+g"), chord("G"));
+        assert_eq!(chord("ctrl+c").label(), "Ctrl+c");
+        assert_eq!(chord("Space").label(), "Space");
+        assert_eq!(chord("F5").code, KeyCode::F(5));
+        assert_eq!(chord("Shift+Tab").code, KeyCode::BackTab);
+        assert_eq!(chord("BackTab"), chord("Shift+Tab"));
+        assert_eq!(
+            chord("backtab"),
+            KeyChord::from(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT))
+        );
+        assert_eq!(chord("+").code, KeyCode::Char('+'));
+        assert!(KeyChord::parse("Hyper+x").is_err());
+        assert!(KeyChord::parse("nope").is_err());
+    }
+
+    #[test]
+    fn test_lookup_prefers_specific_pane() {
+        let keymap = Keymap::default();
+        let y = [chord("y")];
+        assert_eq!(
+            keymap.lookup(KeyContext::Batch, &y),
+            Lookup::Command(Command::CheckoutPresent)
+        );
+        assert_eq!(
+            keymap.lookup(KeyContext::Diff, &y),
+            Lookup::Command(Command::Copy)
+        );
+        assert_eq!(
+            keymap.lookup(KeyContext::Table, &[chord("x")]),
+            Lookup::None
+        );
+    }
+
+    #[test]
+    fn test_overlay_contexts_only_see_their_bindings() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.lookup(KeyContext::Confirm, &[chord("y")]),
+            Lookup::Command(Command::Accept)
+        );
+        assert_eq!(
+            keymap.lookup(KeyContext::Menu, &[chord("q")]),
+            Lookup::Command(Command::Cancel)
+        );
+        // Typed as text rather than running a table or pane command
+        assert_eq!(
+            keymap.lookup(KeyContext::Prompt, &[chord("q")]),
+            Lookup::None
+        );
+        assert_eq!(
+            keymap.lookup(KeyContext::Search, &[chord("j")]),
+            Lookup::None
+        );
+        assert_eq!(
+            keymap.lookup(KeyContext::Palette, &[chord("Ctrl+n")]),
+            Lookup::Command(Command::Down)
+        );
+        assert_eq!(
+            keymap.lookup(KeyContext::Palette, &[chord("q")]),
+            Lookup::None
+        );
+        assert_eq!(
+            keymap.lookup(KeyContext::ColumnPicker, &[chord("+")]),
+            Lookup::Command(Command::Widen)
+        );
+        assert_eq!(
+            keymap.lookup(KeyContext::ColumnPicker, &[chord("Shift+Up")]),
+            Lookup::Command(Command::MoveUp)
+        );
+    }
+
+    #[test]
+    fn test_overrides_and_sequences() {
+        let overrides: KeyOverrides = toml::from_str(
+            r#"
+            [table]
+            first = ["g g", "Home"]
+            pull = []
+            "#,
+        )
+        .unwrap();
+        let keymap = Keymap::with_overrides(&overrides).unwrap();
+        assert_eq!(
+            keymap.lookup(KeyContext::Table, &[chord("g")]),
+            Lookup::Prefix
+        );
+        assert_eq!(
+            keymap.lookup(KeyContext::Table, &[chord("g"), chord("g")]),
+            Lookup::Command(Command::First)
+        );
+        assert_eq!(
+            keymap.lookup(KeyContext::Table, &[chord("p")]),
+            Lookup::None
+        );
+        assert_eq!(
+            keymap.keys_for(KeyContext::Table, Command::First),
+            vec!["g g", "Home"]
+        );
+    }
+
+    #[test]
+    fn test_overrides_report_conflicts() {
+        let overrides: KeyOverrides = toml::from_str("[table]\npush = \"p\"").unwrap();
+        let err = Keymap::with_overrides(&overrides).err().unwrap();
+        assert!(err.contains("pull"), "{err}");
+
+        let overrides: KeyOverrides = toml::from_str("[pane]\nhelp = \"q x\"").unwrap();
+        assert!(Keymap::with_overrides(&overrides).is_err());
+
+        let overrides: KeyOverrides = toml::from_str("[tabel]\nhelp = \"h\"").unwrap();
+        assert!(Keymap::with_overrides(&overrides).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+
+use crate::logger::log_debug;
+
+/// External program opened on a repository, e.g. an editor or git UI.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Launcher {
+    /// Key selecting the launcher in the "open with" menu; must not be
+    /// bound in the `menu` key context.
+    pub key: char,
+    pub name: String,
+    /// Shell command; `{path}`, `{branch}`, `{remote}` (the configured URL)
+    /// and `{remote_display}` (its short form) are replaced with quoted
+    /// values. Runs with the repository as working directory.
+    pub command: String,
+    /// Hand the terminal to the command and wait for it. Disable for GUI
+    /// programs that should open in the background.
+    #[serde(default = "default_terminal")]
+    pub terminal: bool,
+}
+
+fn default_terminal() -> bool {
+    true
+}
+
+impl Launcher {
+    fn new(key: char, name: &str, command: &str, terminal: bool) -> Self {
+        Self {
+            key,
+            name: name.to_string(),
+            command: command.to_string(),
+            terminal,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.command.trim().is_empty() {
+            return Err(format!("launcher \"{}\" has an empty command", self.name));
+        }
+        Ok(())
+    }
+
+    pub fn request(&self, path: &Path, branch: &str, remote: &Remote) -> LaunchRequest {
+        LaunchRequest {
+            name: self.name.clone(),
+            command: expand_template(&self.command, path, branch, remote),
+            path: path.to_path_buf(),
+            terminal: self.terminal,
+        }
+    }
+}
+
+/// Command opening a file or URL with the desktop's default application.
+fn system_opener() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    }
+}
+
+pub fn default_launchers() -> Vec<Launcher> {
+    let opener = format!("{} {{path}}", system_opener());
+    vec![
+        Launcher::new('e', "Editor", "${VISUAL:-${EDITOR:-vi}} {path}", true),
+        Launcher::new('s', "Shell", "${SHELL:-sh}", true),
+        Launcher::new('g', "lazygit", "lazygit -p {path}", true),
+        Launcher::new('t', "tig", "tig", true),
+        Launcher::new('f', "File manager", &opener, false),
+    ]
+}
+
+/// A launcher expanded for one repository, waiting to be run by the UI loop.
+#[derive(Clone, Debug)]
+pub struct LaunchRequest {
+    pub name: String,
+    pub command: String,
+    pub path: PathBuf,
+    pub terminal: bool,
+}
+
+impl LaunchRequest {
+    /// Open `url` in the default browser without leaving the dashboard.
+    pub fn open_url(url: &str, path: &Path) -> Self {
+        Self {
+            name: "browser".to_string(),
+            command: format!("{} {}", system_opener(), shell_quote(url)),
+            path: path.to_path_buf(),
+            terminal: false,
+        }
+    }
+
+    /// Run the command. Terminal launchers block until the program exits, so
+    /// the caller must have released the terminal first.
+    pub fn run(&self) -> Result<(), String> {
+        log_debug(&format!(
+            "Launching {} in {}: {}",
+            self.name,
+            self.path.display(),
+            self.command
+        ));
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(&self.command).current_dir(&self.path);
+        if !self.terminal {
+            let mut child = command
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|err| format!("Failed to start {}: {err}", self.name))?;
+            // Reap the child without blocking the UI
+            std::thread::spawn(move || child.wait());
+            return Ok(());
+        }
+        let status = command
+            .status()
+            .map_err(|err| format!("Failed to start {}: {err}", self.name))?;
+        match status.code() {
+            Some(0) => Ok(()),
+            Some(code) => Err(format!("{} exited with status {code}", self.name)),
+            None => Err(format!("{} was killed", self.name)),
+        }
+    }
+}
+
+/// Remote URL of a repository as configured and as shown in the table.
+pub struct Remote<'a> {
+    pub url: &'a str,
+    pub display: &'a str,
+}
+
+/// Substitute the placeholders in `template`, shell-quoting each value.
+pub fn expand_template(template: &str, path: &Path, branch: &str, remote: &Remote) -> String {
+    let path = path.to_string_lossy();
+    template
+        .replace("{path}", &shell_quote(&path))
+        .replace("{branch}", &shell_quote(branch))
+        .replace("{remote}", &shell_quote(remote.url))
+        .replace("{remote_display}", &shell_quote(remote.display))
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_template_quotes_values() {
+        let command = expand_template(
+            "tool {path} --branch={branch} {remote} {remote_display}",
+            Path::new("/work/it's here"),
+            "feature/x",
+            &Remote {
+                url: "git@github.com:o/r.git",
+                display: "github.com/o/r",
+            },
+        );
+        assert_eq!(
+            command,
+            "tool '/work/it'\\''s here' --branch='feature/x' 'git@github.com:o/r.git' 'github.com/o/r'"
+        );
+    }
+
+    #[test]
+    fn test_default_launcher_keys_are_unique() {
+        let launchers = default_launchers();
+        let mut keys: Vec<char> = launchers.iter().map(|launcher| launcher.key).collect();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), launchers.len());
+    }
+}
@@ -1,39 +1,123 @@
 mod app;
+mod backend;
+mod bare;
+mod branches;
+mod browse;
+mod cache;
+mod clipboard;
+mod columns;
+mod config;
+mod diff;
 mod discovery;
+mod exec;
 mod git;
+#[cfg(feature = "gix")]
+mod gix_backend;
+mod history;
+mod keymap;
+mod launch;
 mod logger;
+mod manifest;
+mod palette;
 mod status;
+mod submodule;
+#[cfg(test)]
+mod test_support;
+mod theme;
 mod ui;
 mod worker;
+mod worktree;
 
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+    MouseButton, MouseEvent, MouseEventKind,
+};
 use crossterm::execute;
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
 use ratatui::prelude::*;
 
-use app::{App, StatusType};
+use app::{App, BatchStage, Pane, PromptKind, PruneStage, StatusType};
+use backend::{init_backend, BackendKind};
+use cache::{cache_path, load_repo_list, StatusCache, REPO_LIST_FILE, STATUS_CACHE_FILE};
+use columns::{default_columns, status_extras};
+use config::{config_path, load_config};
+use discovery::{merge_repo_lists, repo_at, RepoRef, ScanTargets};
+use exec::ExecCommand;
 use git::friendly_error;
+use keymap::{Command, KeyChord, KeyContext, Lookup};
+use launch::LaunchRequest;
 use logger::{init_logger, log_debug};
-use status::git_status;
+use manifest::load_manifest;
+use theme::{init_theme, no_color};
 use ui::render_ui;
 use worker::{spawn_worker, Action, WorkerEvent};
 
 const TICK_RATE: Duration = Duration::from_millis(100);
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let config = parse_args()?;
-    if config.debug {
+    let args = parse_args()?;
+    if args.debug {
         init_logger("git-dash-debug.log")?;
     }
     log_debug("Starting git-dash");
-    let root = config.root;
+    let config = load_config(config_path().as_deref())?;
+    // Paths on the command line replace the configured roots, repositories and manifest
+    let manifest_path = match args.manifest {
+        Some(path) => Some(path),
+        None if args.roots.is_empty() => config.manifest_path(),
+        None => None,
+    };
+    let manifest = manifest_path.as_deref().map(load_manifest).transpose()?;
+    let mut targets = if args.roots.is_empty() {
+        match config.scan_targets() {
+            Some(targets) => targets,
+            None if manifest.is_some() => ScanTargets::default(),
+            None => ScanTargets::single(std::env::current_dir()?),
+        }
+    } else {
+        ScanTargets {
+            roots: args.roots,
+            repos: Vec::new(),
+        }
+    };
+    if let Some(manifest) = &manifest {
+        // The root is walked for undeclared repositories; declared ones are
+        // listed too in case they sit inside another repository
+        targets.roots.push(manifest.root.clone());
+        targets
+            .repos
+            .extend(manifest.present().map(Path::to_path_buf));
+    }
+    // Cached lists and statuses are keyed by path, so spell paths the same way every run
+    let (targets, missing) = targets.resolve();
+    if let Some(exec) = args.exec {
+        for path in &missing {
+            eprintln!("Skipping {}: not found", path.display());
+        }
+        let options = exec::ExecOptions {
+            fail_fast: exec.fail_fast || config.exec.fail_fast,
+            ..config.exec.clone()
+        };
+        let code = exec::run_cli(
+            &targets,
+            &config.discovery,
+            &exec.command,
+            exec.jobs,
+            &options,
+        );
+        std::process::exit(code);
+    }
+    init_theme(config.theme(no_color())?);
+    let git_backend = args.backend.unwrap_or(config.backend).create()?;
+    log_debug(&format!("Using {} backend", git_backend.name()));
+    init_backend(git_backend);
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
@@ -44,15 +128,43 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (cmd_tx, cmd_rx) = mpsc::channel();
     let (evt_tx, evt_rx) = mpsc::channel();
 
-    let worker_handle = spawn_worker(cmd_rx, evt_tx);
-
-    let mut app = App::new(root.clone(), cmd_tx);
-    app.request_scan();
+    let cache = StatusCache::load(cache_path(STATUS_CACHE_FILE));
+    let cached = cache.states_in(&targets);
+    let extras = status_extras(&config.columns().unwrap_or_else(|_| default_columns()));
+    let worker_handle = spawn_worker(cmd_rx, evt_tx, cache, config.discovery.clone(), extras);
+
+    let known = if args.rescan {
+        Vec::new()
+    } else {
+        let list_path = cache_path(REPO_LIST_FILE);
+        let known = targets
+            .roots
+            .iter()
+            .flat_map(|root| load_repo_list(list_path.as_deref(), root));
+        // An empty list means a full walk, so only add listed repositories to a cached one
+        let mut known: Vec<RepoRef> = known.collect();
+        if !known.is_empty() {
+            known.extend(targets.repos.iter().filter_map(|path| repo_at(path)));
+        }
+        merge_repo_lists(known)
+    };
+    let mut app = App::new(targets, cmd_tx, &config);
+    if !cached.is_empty() {
+        app.set_repos(cached);
+        app.set_status("Showing cached status, revalidating...".to_string());
+    }
+    app.missing_targets = missing;
+    app.manifest = manifest;
+    app.request_scan(known);
 
     let res = run_app(&mut terminal, &mut app, evt_rx);
 
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
     terminal.show_cursor()?;
 
     app.request_quit();
@@ -67,11 +179,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn parse_args() -> Result<Config, Box<dyn std::error::Error>> {
-    let mut root: Option<PathBuf> = None;
+fn parse_args() -> Result<Args, Box<dyn std::error::Error>> {
+    let mut roots = Vec::new();
     let mut debug = false;
+    let mut backend = None;
+    let mut rescan = false;
+    let mut manifest = None;
+    let mut exec: Option<ExecArgs> = None;
     // nosemgrep: rust.lang.security.args-os.args-os -- CLI parsing skips argv[0] and does not make security decisions from it.
-    for arg in std::env::args_os().skip(1) {
+    let mut args = std::env::args_os().skip(1).peekable();
+    if args.peek().is_some_and(|arg| arg == "exec") {
+        args.next();
+        exec = Some(ExecArgs {
+            command: ExecCommand::Args(Vec::new()),
+            jobs: worker::default_worker_count(),
+            fail_fast: false,
+        });
+    }
+    while let Some(arg) = args.next() {
         if arg == "--help" || arg == "-h" {
             print_help();
             std::process::exit(0);
@@ -80,32 +205,96 @@ fn parse_args() -> Result<Config, Box<dyn std::error::Error>> {
             debug = true;
             continue;
         }
+        if arg == "--rescan" {
+            rescan = true;
+            continue;
+        }
+        if arg == "--manifest" {
+            let value = args.next().ok_or("--manifest requires a file")?;
+            manifest = Some(PathBuf::from(value));
+            continue;
+        }
+        if arg == "--backend" {
+            let value = args.next().ok_or("--backend requires a value")?;
+            backend = Some(BackendKind::parse(&value.to_string_lossy())?);
+            continue;
+        }
+        if let Some(exec) = exec.as_mut() {
+            if arg == "--" {
+                let command: Vec<String> = args
+                    .by_ref()
+                    .map(|arg| arg.to_string_lossy().into_owned())
+                    .collect();
+                exec.command = ExecCommand::Args(command);
+                break;
+            }
+            if arg == "--fail-fast" {
+                exec.fail_fast = true;
+                continue;
+            }
+            if arg == "--jobs" || arg == "-j" {
+                let value = args.next().ok_or("--jobs requires a value")?;
+                exec.jobs = value
+                    .to_string_lossy()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|jobs| *jobs > 0)
+                    .ok_or("--jobs expects a positive number")?;
+                continue;
+            }
+        }
 
         if arg.to_string_lossy().starts_with('-') {
             return Err(format!("Unknown option: {}", arg.to_string_lossy()).into());
         }
 
-        if root.is_some() {
-            return Err("Too many arguments".into());
+        roots.push(PathBuf::from(arg));
+    }
+
+    if let Some(ExecArgs {
+        command: ExecCommand::Args(command),
+        ..
+    }) = &exec
+    {
+        if command.is_empty() {
+            return Err("exec requires a command after --".into());
         }
-        root = Some(PathBuf::from(arg));
     }
 
-    Ok(Config {
-        root: root.unwrap_or(std::env::current_dir()?),
+    Ok(Args {
+        roots,
         debug,
+        backend,
+        rescan,
+        manifest,
+        exec,
     })
 }
 
 fn print_help() {
     println!(
-        "git-dash\nA fast TUI dashboard for discovering and managing multiple Git repositories.\n\nUSAGE:\n    git-dash [path]\n\nARGS:\n    path    Optional directory to scan (defaults to current directory)\n\nOPTIONS:\n    -d, --debug    Enable debug logging to git-dash-debug.log\n    -h, --help     Print help information"
+        "git-dash\nA fast TUI dashboard for discovering and managing multiple Git repositories.\n\nUSAGE:\n    git-dash [--backend NAME] [--manifest FILE] [--rescan] [path...]\n    git-dash exec [--fail-fast] [-j N] [path...] -- <command> [args...]\n\nARGS:\n    path    Directories to scan (defaults to the configured roots and repos, or the current directory)\n\nOPTIONS:\n    -d, --debug       Enable debug logging to git-dash-debug.log\n    --backend NAME    Git backend: cli (default) or gix\n    --manifest FILE   Workspace manifest of repositories to show and clone\n    --rescan          Ignore the cached repository list and walk the path again\n    -h, --help        Print help information\n\nEXEC OPTIONS:\n    -j, --jobs N   Number of repositories to run in parallel\n    --fail-fast    Skip remaining repositories after the first failure"
     );
 }
 
-struct Config {
-    root: PathBuf,
+struct Args {
+    /// Directories to scan; the configured roots and repositories when empty.
+    roots: Vec<PathBuf>,
     debug: bool,
+    /// Overrides the `backend` config setting.
+    backend: Option<BackendKind>,
+    /// Ignore the cached repository list and walk the root again.
+    rescan: bool,
+    /// Overrides the `manifest` config setting.
+    manifest: Option<PathBuf>,
+    exec: Option<ExecArgs>,
+}
+
+/// Options for `git-dash exec`.
+struct ExecArgs {
+    command: ExecCommand,
+    jobs: usize,
+    fail_fast: bool,
 }
 
 fn run_app(
@@ -114,9 +303,19 @@ fn run_app(
     evt_rx: mpsc::Receiver<WorkerEvent>,
 ) -> io::Result<()> {
     let mut last_tick = Instant::now();
+    let mut mouse_captured = false;
 
     loop {
+        if app.mouse_enabled != mouse_captured {
+            if app.mouse_enabled {
+                execute!(terminal.backend_mut(), EnableMouseCapture)?;
+            } else {
+                execute!(terminal.backend_mut(), DisableMouseCapture)?;
+            }
+            mouse_captured = app.mouse_enabled;
+        }
         drain_worker_events(app, &evt_rx);
+        app.expire_pending_keys();
         terminal.draw(|frame| render_ui(frame, app))?;
 
         if app.should_quit {
@@ -125,17 +324,57 @@ fn run_app(
 
         let timeout = TICK_RATE.saturating_sub(last_tick.elapsed());
         if event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                handle_key_event(app, key);
+            match event::read()? {
+                Event::Key(key) => handle_key_event(app, key),
+                Event::Mouse(mouse) if app.mouse_enabled => handle_mouse_event(app, mouse),
+                _ => {}
             }
         }
 
+        if let Some(request) = app.pending_launch.take() {
+            run_launch(terminal, app, &request)?;
+        }
+
         if last_tick.elapsed() >= TICK_RATE {
             last_tick = Instant::now();
         }
     }
 }
 
+/// Run a launcher, handing the terminal over to it unless it opens in the background.
+fn run_launch(
+    terminal: &mut Terminal<ratatui::backend::CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    request: &LaunchRequest,
+) -> io::Result<()> {
+    let result = if request.terminal {
+        disable_raw_mode()?;
+        execute!(
+            terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture
+        )?;
+        terminal.show_cursor()?;
+        let result = request.run();
+        enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+        if app.mouse_enabled {
+            execute!(terminal.backend_mut(), EnableMouseCapture)?;
+        }
+        terminal.clear()?;
+        result
+    } else {
+        request.run()
+    };
+    match result {
+        Ok(()) if request.terminal => app.set_status(format!("Returned from {}", request.name)),
+        Ok(()) => app.set_status_with_type(format!("Opened {}", request.name), StatusType::Success),
+        Err(err) => app.set_status_with_type(err, StatusType::Error),
+    }
+    app.refresh_repo(&request.path);
+    Ok(())
+}
+
 fn handle_key_event(app: &mut App, key: KeyEvent) {
     // Help screen takes priority - close it with any key
     if app.help_visible {
@@ -143,9 +382,23 @@ fn handle_key_event(app: &mut App, key: KeyEvent) {
         return;
     }
 
-    // Search mode takes priority
-    if app.search_mode {
-        handle_search_key(app, key);
+    if app.palette.is_some() {
+        handle_palette_key(app, key);
+        return;
+    }
+
+    if app.column_picker.is_some() {
+        handle_column_picker_key(app, key);
+        return;
+    }
+
+    if app.menu.is_some() {
+        handle_menu_key(app, key);
+        return;
+    }
+
+    if app.prompt.is_some() {
+        handle_prompt_key(app, key);
         return;
     }
 
@@ -154,36 +407,270 @@ fn handle_key_event(app: &mut App, key: KeyEvent) {
         return;
     }
 
-    match key.code {
-        KeyCode::Char('q') => app.should_quit = true,
-        KeyCode::Char('r') => app.request_refresh(),
-        KeyCode::Char('p') => app.request_confirm(Action::Pull),
-        KeyCode::Char('u') => app.request_confirm(Action::Push),
-        KeyCode::Char('s') => app.cycle_sort_order(),
-        KeyCode::Char('?') => app.toggle_help(),
-        KeyCode::Char('/') => app.enter_search_mode(),
-        KeyCode::Esc => app.exit_search_mode(),
-        KeyCode::Down | KeyCode::Char('j') => app.next(),
-        KeyCode::Up | KeyCode::Char('k') => app.previous(),
-        KeyCode::PageDown => app.page_down(),
-        KeyCode::PageUp => app.page_up(),
-        KeyCode::Home | KeyCode::Char('g') => app.jump_to_first(),
-        KeyCode::End | KeyCode::Char('G') => app.jump_to_last(),
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.should_quit = true
+    if let Some(Pane::Prune(view)) = app.panes.last() {
+        if view.stage == PruneStage::Confirm {
+            match app.lookup_key(KeyContext::Confirm, KeyChord::from(key)) {
+                Lookup::Command(Command::Accept) => app.confirm_prune(true),
+                Lookup::Command(Command::Cancel) => app.confirm_prune(false),
+                _ => {}
+            }
+            return;
         }
+    }
+
+    // Search mode takes priority
+    if app.search_mode && app.panes.is_empty() {
+        handle_search_key(app, key);
+        return;
+    }
+
+    if let Lookup::Command(command) = app.lookup_key(app.key_context(), KeyChord::from(key)) {
+        run_command(app, command);
+    }
+}
+
+fn run_command(app: &mut App, command: Command) {
+    let in_pane = !app.panes.is_empty();
+    match command {
+        Command::Quit => app.should_quit = true,
+        Command::Help => app.toggle_help(),
+        Command::Close => app.close_pane(),
+        Command::Down if in_pane => app.scroll_pane(1),
+        Command::Up if in_pane => app.scroll_pane(-1),
+        Command::PageDown if in_pane => app.scroll_pane(10),
+        Command::PageUp if in_pane => app.scroll_pane(-10),
+        Command::First if in_pane => app.scroll_pane_to_start(),
+        Command::Last if in_pane => app.scroll_pane_to_end(),
+        Command::Down => app.next(),
+        Command::Up => app.previous(),
+        Command::PageDown => app.page_down(),
+        Command::PageUp => app.page_up(),
+        Command::First => app.jump_to_first(),
+        Command::Last => app.jump_to_last(),
+        Command::Refresh => app.request_refresh(true),
+        Command::Fetch => app.request_confirm(Action::Fetch),
+        Command::Pull => app.request_confirm(Action::Pull),
+        Command::Push => app.request_confirm(Action::Push),
+        Command::Mark => app.toggle_mark(),
+        Command::MarkAll => app.toggle_mark_all(),
+        Command::BatchCheckout => app.open_prompt(PromptKind::BatchCheckout),
+        Command::Prune => app.open_prune(),
+        Command::Exec => app.open_prompt(PromptKind::Exec),
+        Command::CancelExec => app.cancel_exec(),
+        Command::AddWorktree => app.open_prompt(PromptKind::AddWorktree),
+        Command::PruneWorktrees => app.request_confirm(Action::PruneWorktrees),
+        Command::UpdateMirror => app.request_confirm(Action::UpdateMirror),
+        Command::CloneMissing => app.request_clone_missing(),
+        Command::Log => app.open_log(),
+        Command::Details => app.open_detail(),
+        Command::Branches => app.open_branches(),
+        Command::Open if app.focused_repo().is_some() => app.open_launch_menu(),
+        Command::Browse if app.focused_repo().is_some() => app.open_browse_menu(),
+        Command::Copy if app.focused_repo().is_some() => app.open_copy_menu(),
+        Command::Open | Command::Browse | Command::Copy => {}
+        Command::CycleSort => app.cycle_sort_order(),
+        Command::ReverseSort => app.reverse_sort_order(),
+        Command::Search => app.enter_search_mode(),
+        Command::ClearSearch => app.exit_search_mode(),
+        Command::ToggleMouse => app.toggle_mouse(),
+        Command::NextSection => app.jump_section(true),
+        Command::PrevSection => app.jump_section(false),
+        Command::CycleLogMode => app.cycle_log_mode(),
+        Command::DiffFile => app.open_diff(false),
+        Command::DiffAll => app.open_diff(true),
+        Command::Checkout => app.request_checkout(),
+        Command::DeleteBranch => app.request_delete_branch(),
+        Command::ToggleSelection => app.toggle_prune_selection(),
+        Command::ToggleAll => app.toggle_prune_all(),
+        Command::DeleteSelected => app.request_prune_delete(),
+        Command::CheckoutPresent if is_batch_review(app) => app.run_batch_checkout(false),
+        Command::CheckoutCreate if is_batch_review(app) => app.run_batch_checkout(true),
+        Command::CheckoutPresent | Command::CheckoutCreate => {}
+        Command::Palette => app.open_palette(),
+        // Only bound in overlays, which handle their keys themselves
+        Command::Accept
+        | Command::Cancel
+        | Command::DeleteChar
+        | Command::MoveDown
+        | Command::MoveUp
+        | Command::Widen
+        | Command::Narrow
+        | Command::ResetWidth => {}
+        Command::Columns => app.open_column_picker(),
+    }
+}
+
+fn handle_column_picker_key(app: &mut App, key: KeyEvent) {
+    let Lookup::Command(command) = app.lookup_key(KeyContext::ColumnPicker, KeyChord::from(key))
+    else {
+        return;
+    };
+    let Some(picker) = app.column_picker.as_mut() else {
+        return;
+    };
+    match command {
+        Command::Cancel => app.column_picker = None,
+        Command::Accept => app.apply_column_picker(),
+        Command::MoveDown => picker.move_entry(1),
+        Command::MoveUp => picker.move_entry(-1),
+        Command::Down => picker.move_selection(1),
+        Command::Up => picker.move_selection(-1),
+        Command::ToggleSelection => picker.toggle(),
+        Command::Widen => picker.resize(1),
+        Command::Narrow => picker.resize(-1),
+        Command::ResetWidth => picker.reset_width(),
         _ => {}
     }
 }
 
-fn handle_confirm_key(app: &mut App, key: KeyEvent) {
+fn handle_palette_key(app: &mut App, key: KeyEvent) {
+    match app.lookup_key(KeyContext::Palette, KeyChord::from(key)) {
+        Lookup::Command(Command::Accept) => {
+            if let Some(command) = app.take_palette_command() {
+                run_command(app, command);
+            }
+        }
+        Lookup::Command(Command::Cancel) => app.palette = None,
+        Lookup::Command(Command::Down) => app.move_palette_selection(1),
+        Lookup::Command(Command::Up) => app.move_palette_selection(-1),
+        Lookup::Command(Command::DeleteChar) => app.palette_pop_char(),
+        Lookup::None => {
+            if let Some(c) = typed_char(key) {
+                app.palette_push_char(c);
+            }
+        }
+        Lookup::Command(_) | Lookup::Prefix => {}
+    }
+}
+
+fn handle_mouse_event(app: &mut App, mouse: MouseEvent) {
+    let delta: isize = match mouse.kind {
+        MouseEventKind::ScrollDown => 3,
+        MouseEventKind::ScrollUp => -3,
+        MouseEventKind::Down(MouseButton::Left) => {
+            handle_click(app, mouse.column, mouse.row);
+            return;
+        }
+        _ => return,
+    };
+    if app.help_visible || app.prompt.is_some() || app.confirmation.is_some() {
+        return;
+    }
+    if app.palette.is_some() {
+        app.move_palette_selection(delta.signum());
+    } else if let Some(picker) = app.column_picker.as_mut() {
+        picker.move_selection(delta.signum());
+    } else if app.menu.is_some() {
+        app.move_menu_selection(delta.signum());
+    } else if !app.panes.is_empty() {
+        app.scroll_pane(delta);
+    } else {
+        app.move_selection(delta);
+    }
+}
+
+fn handle_click(app: &mut App, column: u16, row: u16) {
+    let position = Position::new(column, row);
+    if app.help_visible {
+        app.toggle_help();
+        return;
+    }
+    if app.palette.is_some() {
+        app.palette = None;
+        return;
+    }
+    if app.column_picker.is_some() {
+        app.column_picker = None;
+        return;
+    }
+    if let Some(area) = app.hit_areas.menu {
+        let inner = area.inner(Margin::new(1, 1));
+        if inner.contains(position) {
+            let index = usize::from(row - inner.y);
+            if index < app.menu_items().len() {
+                app.activate_menu(index, false);
+            }
+        } else {
+            app.menu = None;
+        }
+        return;
+    }
+    // Prompts and confirmations are answered with the keyboard
+    if app.prompt.is_some() || app.confirmation.is_some() {
+        return;
+    }
+    let hint = app
+        .hit_areas
+        .hints
+        .iter()
+        .find(|(area, _)| area.contains(position))
+        .map(|(_, command)| *command);
+    if let Some(command) = hint {
+        run_command(app, command);
+        return;
+    }
+    if app.panes.is_empty() {
+        app.click_table(column, row);
+    }
+}
+
+fn is_batch_review(app: &App) -> bool {
+    matches!(app.panes.last(), Some(Pane::Batch(view)) if view.stage == BatchStage::Review)
+}
+
+fn handle_menu_key(app: &mut App, key: KeyEvent) {
+    let selected = app.menu.as_ref().map_or(0, |menu| menu.selected);
+    match app.lookup_key(KeyContext::Menu, KeyChord::from(key)) {
+        Lookup::Command(Command::Cancel) => app.menu = None,
+        Lookup::Command(Command::Down) => app.move_menu_selection(1),
+        Lookup::Command(Command::Up) => app.move_menu_selection(-1),
+        Lookup::Command(Command::Accept) => app.activate_menu(selected, false),
+        Lookup::None => {
+            if let KeyCode::Char(c) = key.code {
+                app.activate_menu_key(c);
+            }
+        }
+        Lookup::Command(_) | Lookup::Prefix => {}
+    }
+}
+
+/// Character typed into a text field: unmodified apart from Shift.
+fn typed_char(key: KeyEvent) -> Option<char> {
     match key.code {
-        KeyCode::Char('y') => {
+        KeyCode::Char(c) if (key.modifiers - KeyModifiers::SHIFT).is_empty() => Some(c),
+        _ => None,
+    }
+}
+
+fn handle_prompt_key(app: &mut App, key: KeyEvent) {
+    match app.lookup_key(KeyContext::Prompt, KeyChord::from(key)) {
+        Lookup::Command(Command::Accept) => app.submit_prompt(),
+        Lookup::Command(Command::Cancel) => {
+            app.prompt = None;
+            app.set_status("Action canceled".to_string());
+        }
+        Lookup::Command(Command::DeleteChar) => {
+            if let Some(prompt) = app.prompt.as_mut() {
+                prompt.input.pop();
+            }
+        }
+        Lookup::None => {
+            if let (Some(prompt), Some(c)) = (app.prompt.as_mut(), typed_char(key)) {
+                prompt.input.push(c);
+            }
+        }
+        Lookup::Command(_) | Lookup::Prefix => {}
+    }
+}
+
+fn handle_confirm_key(app: &mut App, key: KeyEvent) {
+    match app.lookup_key(KeyContext::Confirm, KeyChord::from(key)) {
+        Lookup::Command(Command::Accept) => {
             if let Some(action) = app.confirmation.take() {
                 app.perform_action(action);
             }
         }
-        KeyCode::Char('n') | KeyCode::Esc => {
+        Lookup::Command(Command::Cancel) => {
             app.set_status("Action canceled".to_string());
             app.confirmation = None;
         }
@@ -192,13 +679,17 @@ fn handle_confirm_key(app: &mut App, key: KeyEvent) {
 }
 
 fn handle_search_key(app: &mut App, key: KeyEvent) {
-    match key.code {
-        KeyCode::Char(c) => app.search_push_char(c),
-        KeyCode::Backspace => app.search_pop_char(),
-        KeyCode::Enter | KeyCode::Esc => {
+    match app.lookup_key(KeyContext::Search, KeyChord::from(key)) {
+        Lookup::Command(Command::Accept | Command::Cancel) => {
             app.search_mode = false;
         }
-        _ => {}
+        Lookup::Command(Command::DeleteChar) => app.search_pop_char(),
+        Lookup::None => {
+            if let Some(c) = typed_char(key) {
+                app.search_push_char(c);
+            }
+        }
+        Lookup::Command(_) | Lookup::Prefix => {}
     }
 }
 
@@ -206,29 +697,42 @@ fn drain_worker_events(app: &mut App, evt_rx: &mpsc::Receiver<WorkerEvent>) {
     while let Ok(event) = evt_rx.try_recv() {
         match event {
             WorkerEvent::ScanComplete(repos) => {
-                app.repos = repos;
-                app.sort_repos();
-                app.loading = false;
-                app.scan_progress = 1.0;
-                app.set_status_with_type("Scan complete".to_string(), StatusType::Success);
+                app.set_repos(repos);
+                app.finish_scan("Scan complete".to_string());
+            }
+            WorkerEvent::KnownReposLoaded(repos) => {
+                app.set_repos(repos);
+                app.set_status("Looking for new repositories...".to_string());
+            }
+            WorkerEvent::DiscoveryComplete(repos) => app.apply_discovery(repos),
+            WorkerEvent::WorktreesLoaded { main, result } => app.apply_worktrees(&main, result),
+            WorkerEvent::StatusLoaded(repos) => {
+                let added = repos.len();
+                app.merge_repos(repos);
+                app.finish_scan(format!("Scan complete: {added} new"));
             }
             WorkerEvent::RefreshComplete(repos) => {
-                app.repos = repos;
-                app.sort_repos();
+                app.set_repos(repos);
                 app.set_status_with_type("Status refreshed".to_string(), StatusType::Success);
             }
             WorkerEvent::ScanProgress { ratio } => {
                 app.scan_progress = ratio;
             }
+            WorkerEvent::LogLoaded { path, mode, result } => {
+                app.apply_log(&path, mode, result);
+            }
+            WorkerEvent::DiffLoaded { path, file, result } => {
+                app.apply_diff(&path, file, result);
+            }
             WorkerEvent::ActionResult {
                 path,
                 action,
                 result,
             } => {
-                let action_label = match action {
-                    Action::Pull => "Pull",
-                    Action::Push => "Push",
-                };
+                let action_label = action.describe();
+                if action.changes_worktrees() && result.is_ok() {
+                    app.request_worktrees(&path);
+                }
                 match result {
                     Ok(message) => app.set_status_with_type(
                         format!("{action_label} OK: {message}"),
@@ -242,12 +746,36 @@ fn drain_worker_events(app: &mut App, evt_rx: &mpsc::Receiver<WorkerEvent>) {
                         )
                     }
                 }
-                if let Some(repo) = app.repos.iter_mut().find(|repo| repo.path == path) {
-                    if let Ok(status) = git_status(&path, &repo.git_dir) {
-                        *repo = status;
-                    }
+                app.refresh_repo(&path);
+                if matches!(app.panes.last(), Some(Pane::Branches(view)) if view.path == path) {
+                    app.request_branches();
                 }
             }
+            WorkerEvent::CheckoutPreflight { branch, plans } => {
+                app.apply_checkout_preflight(&branch, plans);
+            }
+            WorkerEvent::ExecOutput { path, outcome } => {
+                app.apply_exec_output(&path, outcome);
+            }
+            WorkerEvent::ExecComplete => app.apply_exec_complete(),
+            WorkerEvent::PruneCandidates { results } => {
+                app.apply_prune_candidates(results);
+            }
+            WorkerEvent::BatchProgress { done, total } => {
+                app.apply_batch_progress(done, total);
+            }
+            WorkerEvent::BatchResult { results } => {
+                app.apply_batch_result(results);
+            }
+            WorkerEvent::BranchesLoaded { path, result } => {
+                app.apply_branches(&path, result);
+            }
+            WorkerEvent::CloneProgress { done, total } => {
+                app.apply_clone_progress(done, total);
+            }
+            WorkerEvent::CloneComplete { results, states } => {
+                app.apply_clone_result(results, states);
+            }
         }
     }
 }
@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::git::run_git;
+use crate::status::{RepoState, StatusExtras, StatusSnapshot, NO_BRANCH};
+
+/// Cloning transfers the whole history, so it gets far longer than a fetch.
+const GIT_CLONE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Repositories a team works on, declared in a file kept in a meta-repo:
+///
+/// ```toml
+/// root = ".."                # optional, relative to the manifest
+///
+/// [[repo]]
+/// path = "services/api"      # relative to the root
+/// url = "git@github.com:acme/api.git"
+/// branch = "main"            # optional, checked out when cloning
+/// groups = ["backend"]
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Manifest {
+    /// Directory the repository paths are relative to.
+    pub root: PathBuf,
+    pub repos: Vec<ManifestRepo>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    root: Option<PathBuf>,
+    #[serde(default, rename = "repo")]
+    repos: Vec<ManifestRepo>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestRepo {
+    /// Relative to the manifest root in the file, absolute once loaded.
+    pub path: PathBuf,
+    pub url: String,
+    /// Default branch; the remote's HEAD is cloned when unset.
+    pub branch: Option<String>,
+    #[serde(default)]
+    pub groups: Vec<String>,
+}
+
+/// How a table row relates to the manifest.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ManifestMark {
+    Declared(ManifestRepo),
+    /// Declared but not cloned; the row is a placeholder.
+    Missing(ManifestRepo),
+    /// Found under the manifest root without being declared.
+    Undeclared,
+}
+
+impl Manifest {
+    pub fn entry(&self, path: &Path) -> Option<&ManifestRepo> {
+        self.repos.iter().find(|repo| repo.path == path)
+    }
+
+    /// Declared repositories that have been cloned.
+    pub fn present(&self) -> impl Iterator<Item = &Path> {
+        self.repos
+            .iter()
+            .map(|repo| repo.path.as_path())
+            .filter(|path| path.exists())
+    }
+}
+
+impl ManifestRepo {
+    /// Table row standing in for the repository until it is cloned.
+    pub fn placeholder(&self) -> RepoState {
+        let snapshot = StatusSnapshot {
+            branch: self.branch.clone().unwrap_or_else(|| NO_BRANCH.to_string()),
+            remote_url: Some(self.url.clone()),
+            ..StatusSnapshot::default()
+        };
+        let mut state =
+            snapshot.into_repo_state(&self.path, &self.path.join(".git"), StatusExtras::default());
+        state.change_summary = "not cloned".to_string();
+        state.manifest = Some(ManifestMark::Missing(self.clone()));
+        state
+    }
+}
+
+pub fn load_manifest(path: &Path) -> Result<Manifest, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let base = path
+        .canonicalize()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    parse_manifest(&contents, &base)
+        .map_err(|err| format!("Invalid manifest {}: {err}", path.display()))
+}
+
+fn parse_manifest(contents: &str, base: &Path) -> Result<Manifest, String> {
+    let file: ManifestFile = toml::from_str(contents).map_err(|err| err.message().to_string())?;
+    let root = base.join(file.root.unwrap_or_default());
+    let root = root
+        .canonicalize()
+        .map_err(|err| format!("root {}: {err}", root.display()))?;
+    let mut seen = HashSet::new();
+    let mut repos = Vec::with_capacity(file.repos.len());
+    for mut repo in file.repos {
+        // Paths are compared with discovered ones, so keep them inside the root
+        if !repo
+            .path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(format!(
+                "repo path {} must be relative to the root",
+                repo.path.display()
+            ));
+        }
+        if repo.url.trim().is_empty() {
+            return Err(format!("repo {} has no url", repo.path.display()));
+        }
+        let path = root.join(&repo.path);
+        repo.path = path.canonicalize().unwrap_or(path);
+        if !seen.insert(repo.path.clone()) {
+            return Err(format!("repo {} is declared twice", repo.path.display()));
+        }
+        repos.push(repo);
+    }
+    Ok(Manifest { root, repos })
+}
+
+/// Clone a declared repository into its path, creating missing parent
+/// directories. A failed or timed out clone leaves no partial checkout
+/// behind unless the target directory existed before.
+pub fn git_clone(repo: &ManifestRepo) -> Result<String, String> {
+    let parent = repo
+        .path
+        .parent()
+        .ok_or_else(|| format!("No parent directory for {}", repo.path.display()))?;
+    fs::create_dir_all(parent)
+        .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    let existed = repo.path.exists();
+    let target = repo.path.to_string_lossy();
+    let mut args = vec!["clone"];
+    if let Some(branch) = &repo.branch {
+        args.extend(["--branch", branch.as_str()]);
+    }
+    args.extend(["--", repo.url.as_str(), target.as_ref()]);
+    if let Err(err) = run_git(parent, &args, GIT_CLONE_TIMEOUT) {
+        if !existed {
+            let _ = fs::remove_dir_all(&repo.path);
+        }
+        return Err(err);
+    }
+    Ok(match &repo.branch {
+        Some(branch) => format!("cloned {branch}"),
+        None => "cloned".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{git, TempDir};
+
+    #[test]
+    fn test_parse_manifest() {
+        let dir = TempDir::new("manifest-parse");
+        fs::create_dir_all(dir.join("meta")).unwrap();
+        let manifest = parse_manifest(
+            r#"
+            root = ".."
+            [[repo]]
+            path = "services/api"
+            url = "git@github.com:acme/api.git"
+            branch = "main"
+            groups = ["backend"]
+            [[repo]]
+            path = "meta"
+            url = "https://github.com/acme/meta"
+            "#,
+            &dir.join("meta"),
+        )
+        .unwrap();
+        assert_eq!(manifest.root, *dir);
+        assert_eq!(manifest.repos[0].path, dir.join("services/api"));
+        assert_eq!(manifest.repos[0].groups, ["backend"]);
+        assert_eq!(manifest.repos[1].branch, None);
+        assert!(manifest.entry(&dir.join("meta")).is_some());
+        assert_eq!(manifest.present().collect::<Vec<_>>(), [dir.join("meta")]);
+
+        let placeholder = manifest.repos[0].placeholder();
+        assert_eq!(placeholder.name, "api");
+        assert_eq!(placeholder.branch, "main");
+        assert_eq!(placeholder.remote_url, "github.com/acme/api");
+        assert!(placeholder.is_placeholder());
+
+        let repo =
+            |path: &str, url: &str| format!("[[repo]]\npath = \"{path}\"\nurl = \"{url}\"\n");
+        assert!(parse_manifest(&repo("../x", "u"), &dir).is_err());
+        assert!(parse_manifest(&repo("/x", "u"), &dir).is_err());
+        assert!(parse_manifest(&repo("x", " "), &dir).is_err());
+        assert!(parse_manifest(&format!("{}{}", repo("x", "u"), repo("x", "v")), &dir).is_err());
+        assert!(parse_manifest("root = \"missing\"", &dir).is_err());
+        assert!(parse_manifest("[[repos]]\npath = \"x\"", &dir).is_err());
+    }
+
+    #[test]
+    fn test_clone_declared_repo() {
+        let dir = TempDir::new("manifest-clone");
+        let upstream = dir.join("upstream");
+        fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "-q", "-b", "main"]);
+        git(
+            &upstream,
+            &["commit", "-q", "--allow-empty", "-m", "initial"],
+        );
+        git(&upstream, &["branch", "develop"]);
+
+        let repo = ManifestRepo {
+            path: dir.join("work/nested/app"),
+            url: upstream.to_string_lossy().into_owned(),
+            branch: Some("develop".to_string()),
+            groups: Vec::new(),
+        };
+        assert_eq!(git_clone(&repo).unwrap(), "cloned develop");
+        let head = fs::read_to_string(repo.path.join(".git/HEAD")).unwrap();
+        assert_eq!(head.trim(), "ref: refs/heads/develop");
+        // The target is no longer empty, and is kept when the clone fails
+        assert!(git_clone(&repo).is_err());
+        assert!(repo.path.join(".git").is_dir());
+
+        let missing = ManifestRepo {
+            path: dir.join("work/missing"),
+            url: dir.join("no-such-upstream").to_string_lossy().into_owned(),
+            branch: None,
+            groups: Vec::new(),
+        };
+        assert!(git_clone(&missing).is_err());
+        assert!(!missing.path.exists());
+    }
+}
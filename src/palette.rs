@@ -0,0 +1,98 @@
+use crate::keymap::Command;
+
+/// Command palette state: the typed query and the highlighted match.
+pub struct Palette {
+    pub query: String,
+    pub selected: usize,
+}
+
+/// A command offered by the palette, with what it applies to and its keys.
+pub struct PaletteEntry {
+    pub command: Command,
+    /// Target of the command, e.g. the repository name or "3 marked repos".
+    pub scope: String,
+    pub keys: String,
+}
+
+impl PaletteEntry {
+    fn search_text(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.command.description(),
+            self.command.name(),
+            self.scope
+        )
+    }
+}
+
+/// Entries matching `query`, best match first. Ties keep the given order.
+pub fn filter_entries(entries: Vec<PaletteEntry>, query: &str) -> Vec<PaletteEntry> {
+    let mut scored: Vec<(i32, PaletteEntry)> = entries
+        .into_iter()
+        .filter_map(|entry| fuzzy_score(query, &entry.search_text()).map(|score| (score, entry)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    scored.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Score `text` against `query` when every query character appears in
+/// order, ignoring case and spaces. Consecutive characters and matches at
+/// word starts score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = position + text[position..].iter().position(|t| *t == c)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_requires_order() {
+        assert!(fuzzy_score("pl", "Pull").is_some());
+        assert!(fuzzy_score("lp", "Pull").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_word_starts_and_runs() {
+        let word_start = fuzzy_score("sb", "Show branches").unwrap();
+        let scattered = fuzzy_score("sb", "Toggle submodules").unwrap();
+        assert!(word_start > scattered);
+        let run = fuzzy_score("push", "Push").unwrap();
+        let spread = fuzzy_score("push", "Prune unused shared hooks").unwrap();
+        assert!(run > spread);
+    }
+
+    #[test]
+    fn test_filter_entries_orders_by_score() {
+        let entry = |command| PaletteEntry {
+            command,
+            scope: String::new(),
+            keys: String::new(),
+        };
+        let entries = vec![
+            entry(Command::Prune),
+            entry(Command::Push),
+            entry(Command::Pull),
+        ];
+        let filtered = filter_entries(entries, "push");
+        assert_eq!(filtered[0].command, Command::Push);
+        assert!(filtered.iter().all(|entry| entry.command != Command::Pull));
+    }
+}
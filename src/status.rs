@@ -2,8 +2,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 
-use crate::discovery::RepoRef;
+use serde::{Deserialize, Serialize};
+
+use crate::bare::BareRepo;
+use crate::discovery::{common_dir, RepoRef, ScanTargets};
 use crate::git::{run_git, GIT_STATUS_TIMEOUT};
+use crate::manifest::ManifestMark;
+use crate::submodule::Submodule;
 
 pub const NO_REMOTE: &str = "-";
 pub const NO_AHEAD_BEHIND: &str = "-";
@@ -11,8 +16,9 @@ pub const NO_LAST_FETCH: &str = "-";
 pub const NO_CHANGES: &str = "-";
 pub const NO_BRANCH: &str = "-";
 pub const DETACHED_BRANCH: &str = "DETACHED";
+pub const NO_UPSTREAM: &str = "-";
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RepoState {
     pub path: PathBuf,
     pub git_dir: PathBuf,
@@ -21,80 +27,247 @@ pub struct RepoState {
     pub dirty: bool,
     pub ahead_behind: String,
     pub change_summary: String,
+    /// Short form of `origin_url` for display, e.g. `github.com/user/repo`.
     pub remote_url: String,
-    pub last_fetch: String,
+    /// URL of `origin` exactly as configured.
+    pub origin_url: Option<String>,
+    /// When `FETCH_HEAD` was last written; `None` if never fetched.
+    pub last_fetch: Option<SystemTime>,
+    /// Changed paths as `(short status code, path)` pairs, in porcelain order.
+    pub changes: Vec<(String, String)>,
     pub error_message: Option<String>,
+    pub stash_count: usize,
+    /// Committer time of HEAD; `None` for unborn branches.
+    pub last_commit: Option<SystemTime>,
+    /// Upstream branch, e.g. `origin/main`.
+    pub upstream: String,
+    /// Size of the git directory in bytes.
+    pub size: Option<u64>,
+    /// Set for submodules found by submodule-aware discovery.
+    #[serde(default)]
+    pub submodule: Option<Submodule>,
+    /// Main worktree when this is a linked worktree.
+    #[serde(default)]
+    pub worktree_of: Option<PathBuf>,
+    /// Refs and remotes of a bare repository, which has no working tree.
+    #[serde(default)]
+    pub bare: Option<BareRepo>,
+    /// Relation to the workspace manifest, set by the table when one is loaded.
+    #[serde(skip)]
+    pub manifest: Option<ManifestMark>,
+    /// Loaded from the status cache and not yet confirmed by a scan.
+    #[serde(skip)]
+    pub stale: bool,
+}
+
+impl RepoState {
+    pub fn repo_ref(&self) -> RepoRef {
+        RepoRef {
+            path: self.path.clone(),
+            git_dir: self.git_dir.clone(),
+            superproject: self
+                .submodule
+                .as_ref()
+                .map(|submodule| submodule.superproject.clone()),
+            main_worktree: self.worktree_of.clone(),
+        }
+    }
+
+    pub fn is_bare(&self) -> bool {
+        self.bare.is_some()
+    }
+
+    /// Groups the manifest puts the repository in.
+    pub fn manifest_groups(&self) -> &[String] {
+        match &self.manifest {
+            Some(ManifestMark::Declared(entry) | ManifestMark::Missing(entry)) => &entry.groups,
+            _ => &[],
+        }
+    }
+
+    /// Declared in the manifest but not cloned yet.
+    pub fn is_placeholder(&self) -> bool {
+        matches!(self.manifest, Some(ManifestMark::Missing(_)))
+    }
+
+    /// Scan root the repository was found under. Linked worktrees outside
+    /// every root belong to their main worktree's root; `None` for
+    /// repositories listed by path.
+    pub fn source_root<'a>(&self, targets: &'a ScanTargets) -> Option<&'a Path> {
+        targets
+            .root_of(&self.path)
+            .or_else(|| targets.root_of(self.parent()?))
+    }
+
+    /// Row this one is listed under: the superproject of a submodule or the
+    /// main worktree of a linked worktree.
+    pub fn parent(&self) -> Option<&Path> {
+        match &self.submodule {
+            Some(submodule) => Some(&submodule.superproject),
+            None => self.worktree_of.as_deref(),
+        }
+    }
+}
+
+/// Status fields that cost extra work, read only while a table column
+/// shows them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StatusExtras {
+    /// Size of the git directory, a walk over every object file.
+    pub size: bool,
+    /// Stash count, read from the stash reflog.
+    pub stashes: bool,
+    /// Committer time of HEAD, an extra `git log` for the CLI backend.
+    pub last_commit: bool,
+}
+
+impl StatusExtras {
+    #[cfg(test)]
+    pub const ALL: StatusExtras = StatusExtras {
+        size: true,
+        stashes: true,
+        last_commit: true,
+    };
+
+    /// Whether status read with these extras has every field `wanted` asks for.
+    pub fn covers(self, wanted: StatusExtras) -> bool {
+        (self.size || !wanted.size)
+            && (self.stashes || !wanted.stashes)
+            && (self.last_commit || !wanted.last_commit)
+    }
+}
+
+/// Branch and working tree state as reported by a git backend. The rest of
+/// `RepoState` is read straight from the git directory.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StatusSnapshot {
+    pub branch: String,
+    /// Short upstream name, e.g. `origin/main`.
+    pub upstream: Option<String>,
+    /// Only known when the upstream branch exists.
+    pub ahead_behind: Option<(u32, u32)>,
+    pub changes: Vec<(String, String)>,
+    pub dirty: bool,
+    /// URL of `origin` exactly as configured.
+    pub remote_url: Option<String>,
+    pub last_commit: Option<SystemTime>,
+}
+
+impl StatusSnapshot {
+    /// Complete the state with what is read from the git directory; the
+    /// fields of `extras` that are not set stay empty.
+    pub fn into_repo_state(self, path: &Path, git_dir: &Path, extras: StatusExtras) -> RepoState {
+        let ahead_behind = match self.ahead_behind {
+            Some((ahead, behind)) => format!("+{ahead}/-{behind}"),
+            None => NO_AHEAD_BEHIND.to_string(),
+        };
+        let remote_url = match &self.remote_url {
+            Some(raw) => simplify_remote_url(raw).unwrap_or_else(|| raw.clone()),
+            None => NO_REMOTE.to_string(),
+        };
+        RepoState {
+            path: path.to_path_buf(),
+            git_dir: git_dir.to_path_buf(),
+            name: repo_name(path),
+            branch: self.branch,
+            dirty: self.dirty,
+            ahead_behind,
+            change_summary: summarize_changes(&self.changes),
+            remote_url,
+            origin_url: self.remote_url,
+            last_fetch: git_last_fetch(git_dir).ok(),
+            changes: self.changes,
+            error_message: None,
+            stash_count: if extras.stashes {
+                stash_count(git_dir)
+            } else {
+                0
+            },
+            last_commit: self.last_commit,
+            upstream: self.upstream.unwrap_or_else(|| NO_UPSTREAM.to_string()),
+            size: extras.size.then(|| dir_size(git_dir).ok()).flatten(),
+            submodule: None,
+            worktree_of: None,
+            bare: None,
+            manifest: None,
+            stale: false,
+        }
+    }
+}
+
+pub fn git_status(path: &Path, git_dir: &Path, extras: StatusExtras) -> Result<RepoState, String> {
+    // NUL-separated records keep paths verbatim instead of C-quoted
+    let output = run_git(
+        path,
+        &["status", "--porcelain=2", "-b", "-z"],
+        GIT_STATUS_TIMEOUT,
+    )?;
+    let mut snapshot = parse_porcelain(&String::from_utf8_lossy(&output));
+    snapshot.remote_url = git_remote_url(path).ok();
+    if extras.last_commit {
+        snapshot.last_commit = git_last_commit(path).ok();
+    }
+    Ok(snapshot.into_repo_state(path, git_dir, extras))
 }
 
-pub fn git_status(path: &Path, git_dir: &Path) -> Result<RepoState, String> {
-    let output = run_git(path, &["status", "--porcelain=2", "-b"], GIT_STATUS_TIMEOUT)?;
-    let stdout = String::from_utf8_lossy(&output);
-    let mut branch = "unknown".to_string();
+fn parse_porcelain(stdout: &str) -> StatusSnapshot {
+    let mut snapshot = StatusSnapshot {
+        branch: "unknown".to_string(),
+        ..StatusSnapshot::default()
+    };
     let mut ahead = None;
     let mut behind = None;
-    let mut dirty = false;
-    let mut changes = Vec::new();
 
-    for line in stdout.lines() {
+    let mut records = stdout.split('\0').filter(|record| !record.is_empty());
+    while let Some(line) = records.next() {
         if let Some(rest) = line.strip_prefix("# branch.head ") {
-            branch = match rest {
+            snapshot.branch = match rest {
                 "(detached)" | "HEAD" => DETACHED_BRANCH.to_string(),
                 _ => rest.to_string(),
             };
+        } else if let Some(rest) = line.strip_prefix("# branch.upstream ") {
+            snapshot.upstream = Some(rest.to_string());
         } else if let Some(rest) = line.strip_prefix("# branch.ab ") {
             let mut parts = rest.split_whitespace();
             if let Some(ahead_part) = parts.next() {
                 ahead = ahead_part
                     .strip_prefix('+')
-                    .and_then(|v| v.parse::<i32>().ok());
+                    .and_then(|v| v.parse::<u32>().ok());
             }
             if let Some(behind_part) = parts.next() {
                 behind = behind_part
                     .strip_prefix('-')
-                    .and_then(|v| v.parse::<i32>().ok());
+                    .and_then(|v| v.parse::<u32>().ok());
             }
         } else if let Some(rest) = line.strip_prefix("? ") {
-            dirty = true;
-            changes.push((String::from("??"), rest.to_string()));
+            snapshot.dirty = true;
+            snapshot
+                .changes
+                .push((String::from("??"), rest.to_string()));
         } else if let Some(rest) = line.strip_prefix("1 ") {
-            dirty = true;
+            snapshot.dirty = true;
             if let Some((code, path)) = parse_status_line(rest) {
-                changes.push((code, path));
+                snapshot.changes.push((code, path));
             }
         } else if let Some(rest) = line.strip_prefix("2 ") {
-            dirty = true;
+            snapshot.dirty = true;
             if let Some((code, path)) = parse_status_line(rest) {
-                changes.push((code, path));
+                snapshot.changes.push((code, rename_target(&path)));
             }
+            // The original path follows as a record of its own
+            records.next();
         } else if let Some(rest) = line.strip_prefix("u ") {
-            dirty = true;
+            snapshot.dirty = true;
             if let Some((code, path)) = parse_status_line(rest) {
-                changes.push((code, path));
+                snapshot.changes.push((code, path));
             }
         } else if !line.starts_with('#') {
-            dirty = true;
+            snapshot.dirty = true;
         }
     }
 
-    let ahead_behind = match (ahead, behind) {
-        (Some(a), Some(b)) => format!("+{a}/-{b}"),
-        _ => NO_AHEAD_BEHIND.to_string(),
-    };
-
-    let name = repo_name(path);
-
-    Ok(RepoState {
-        path: path.to_path_buf(),
-        git_dir: git_dir.to_path_buf(),
-        name,
-        branch,
-        dirty,
-        ahead_behind,
-        change_summary: summarize_changes(&changes),
-        remote_url: git_remote_simple(path).unwrap_or_else(|_| NO_REMOTE.to_string()),
-        last_fetch: git_last_fetch(git_dir).unwrap_or_else(|_| NO_LAST_FETCH.to_string()),
-        error_message: None,
-    })
+    snapshot.ahead_behind = ahead.zip(behind);
+    snapshot
 }
 
 fn parse_status_line(rest: &str) -> Option<(String, String)> {
@@ -110,6 +283,13 @@ fn parse_status_line(rest: &str) -> Option<(String, String)> {
     Some((short_status(status), path))
 }
 
+// Rename/copy entries carry an extra score field before the path.
+fn rename_target(rest: &str) -> String {
+    rest.split_once(' ')
+        .map_or(rest, |(_, path)| path)
+        .to_string()
+}
+
 fn short_status(status: &str) -> String {
     if status == "??" {
         return status.to_string();
@@ -136,14 +316,78 @@ fn summarize_changes(changes: &[(String, String)]) -> String {
     items.join(" ")
 }
 
-fn git_last_fetch(git_dir: &Path) -> Result<String, String> {
+fn git_last_fetch(git_dir: &Path) -> Result<SystemTime, String> {
     let fetch_head = git_dir.join("FETCH_HEAD");
     let metadata = fs::metadata(fetch_head).map_err(|err| err.to_string())?;
-    let modified = metadata.modified().map_err(|err| err.to_string())?;
-    let age = SystemTime::now()
-        .duration_since(modified)
-        .unwrap_or(Duration::ZERO);
-    Ok(format_age(age))
+    metadata.modified().map_err(|err| err.to_string())
+}
+
+/// Stash entries, counted from the stash reflog of the shared git directory.
+fn stash_count(git_dir: &Path) -> usize {
+    fs::read_to_string(common_dir(git_dir).join("logs/refs/stash"))
+        .map(|log| log.lines().filter(|line| !line.is_empty()).count())
+        .unwrap_or(0)
+}
+
+pub fn git_last_commit(path: &Path) -> Result<SystemTime, String> {
+    let output = run_git(
+        path,
+        &["log", "-1", "--format=%ct", "HEAD"],
+        GIT_STATUS_TIMEOUT,
+    )?;
+    let secs = String::from_utf8_lossy(&output)
+        .trim()
+        .parse::<u64>()
+        .map_err(|err| err.to_string())?;
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Total size of the files below `path`, without following symlinks.
+fn dir_size(path: &Path) -> Result<u64, String> {
+    let mut total = 0;
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let entries = fs::read_dir(&dir).map_err(|err| err.to_string())?;
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                stack.push(entry.path());
+            } else if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Age of `time` in the short form used by the table, e.g. `3d`.
+pub fn format_time_age(time: SystemTime) -> String {
+    format_age(
+        SystemTime::now()
+            .duration_since(time)
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Byte count in binary units, e.g. `12.3M`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 10.0 {
+        format!("{value:.1}{}", UNITS[unit])
+    } else {
+        format!("{value:.0}{}", UNITS[unit])
+    }
 }
 
 fn format_age(age: Duration) -> String {
@@ -175,7 +419,7 @@ pub fn parse_ahead_behind(value: &str) -> Option<(u32, u32)> {
     Some((ahead, behind))
 }
 
-pub fn error_repo_state(repo: &RepoRef, err: &str) -> RepoState {
+pub fn error_repo_state(repo: &RepoRef, err: &str, extras: StatusExtras) -> RepoState {
     let change_summary = if err.contains("timed out") {
         "timeout".to_string()
     } else {
@@ -190,8 +434,23 @@ pub fn error_repo_state(repo: &RepoRef, err: &str) -> RepoState {
         ahead_behind: NO_AHEAD_BEHIND.to_string(),
         change_summary,
         remote_url: NO_REMOTE.to_string(),
-        last_fetch: git_last_fetch(&repo.git_dir).unwrap_or_else(|_| NO_LAST_FETCH.to_string()),
+        origin_url: None,
+        last_fetch: git_last_fetch(&repo.git_dir).ok(),
+        changes: Vec::new(),
         error_message: Some(err.to_string()),
+        stash_count: if extras.stashes {
+            stash_count(&repo.git_dir)
+        } else {
+            0
+        },
+        last_commit: None,
+        upstream: NO_UPSTREAM.to_string(),
+        size: extras.size.then(|| dir_size(&repo.git_dir).ok()).flatten(),
+        submodule: None,
+        worktree_of: None,
+        bare: None,
+        manifest: None,
+        stale: false,
     }
 }
 
@@ -202,7 +461,8 @@ fn repo_name(path: &Path) -> String {
         .to_string()
 }
 
-fn git_remote_simple(path: &Path) -> Result<String, String> {
+/// URL of `origin` exactly as configured.
+pub fn git_remote_url(path: &Path) -> Result<String, String> {
     let output = run_git(
         path,
         &["config", "--get", "remote.origin.url"],
@@ -212,7 +472,7 @@ fn git_remote_simple(path: &Path) -> Result<String, String> {
     if raw.is_empty() {
         return Err("missing remote".to_string());
     }
-    Ok(simplify_remote_url(&raw).unwrap_or(raw))
+    Ok(raw)
 }
 
 fn simplify_remote_url(raw: &str) -> Option<String> {
@@ -237,6 +497,35 @@ fn simplify_remote_url(raw: &str) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_porcelain() {
+        let output = "# branch.oid abc123\0\
+                      # branch.head main\0\
+                      # branch.upstream origin/main\0\
+                      # branch.ab +2 -1\0\
+                      1 .M N... 100644 100644 100644 abc123 abc123 src/lib.rs\0\
+                      2 R. N... 100644 100644 100644 abc123 abc123 R100 new \"name\".txt\0old.txt\0\
+                      ? notes ä.txt\0";
+        let snapshot = parse_porcelain(output);
+        assert_eq!(snapshot.branch, "main");
+        assert_eq!(snapshot.upstream.as_deref(), Some("origin/main"));
+        assert_eq!(snapshot.ahead_behind, Some((2, 1)));
+        assert!(snapshot.dirty);
+        assert_eq!(
+            snapshot.changes,
+            vec![
+                ("M".to_string(), "src/lib.rs".to_string()),
+                ("R".to_string(), "new \"name\".txt".to_string()),
+                ("??".to_string(), "notes ä.txt".to_string()),
+            ]
+        );
+
+        let detached = parse_porcelain("# branch.oid abc123\0# branch.head (detached)\0");
+        assert_eq!(detached.branch, DETACHED_BRANCH);
+        assert_eq!(detached.ahead_behind, None);
+        assert!(!detached.dirty);
+    }
+
     #[test]
     fn test_parse_status_line_simple() {
         let line = "M. N... 100644 100644 100644 abc123 def456 file.txt";
@@ -254,6 +543,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rename_target() {
+        let line = "R. N... 100644 100644 100644 abc123 def456 R100 new name.txt";
+        let (code, rest) = parse_status_line(line).unwrap();
+        assert_eq!(code, "R");
+        assert_eq!(rename_target(&rest), "new name.txt");
+    }
+
     #[test]
     fn test_short_status_modified() {
         assert_eq!(short_status("M."), "M");
@@ -351,6 +648,14 @@ mod tests {
         assert_eq!(format_age(Duration::from_secs(90000)), "1d");
     }
 
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512B");
+        assert_eq!(format_size(1536), "1.5K");
+        assert_eq!(format_size(20 * 1024 * 1024), "20M");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0G");
+    }
+
     #[test]
     fn test_repo_name() {
         use std::path::PathBuf;
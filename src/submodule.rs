@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::git::{run_git, GIT_STATUS_TIMEOUT};
+
+/// Where a submodule sits in its superproject and whether the checked out
+/// commit is the one the superproject records.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Submodule {
+    pub superproject: PathBuf,
+    /// Commit recorded in the superproject's index.
+    pub recorded: Option<String>,
+    /// Commit checked out in the submodule.
+    pub checked_out: Option<String>,
+}
+
+impl Submodule {
+    pub fn read(superproject: &Path, path: &Path) -> Self {
+        Self {
+            superproject: superproject.to_path_buf(),
+            recorded: recorded_commit(superproject, path),
+            checked_out: run_git(path, &["rev-parse", "HEAD"], GIT_STATUS_TIMEOUT)
+                .ok()
+                .map(|output| String::from_utf8_lossy(&output).trim().to_string())
+                .filter(|commit| !commit.is_empty()),
+        }
+    }
+
+    /// The checked out commit differs from the recorded one, as shown by a
+    /// `+` in `git submodule status`.
+    pub fn is_out_of_sync(&self) -> bool {
+        match (&self.recorded, &self.checked_out) {
+            (Some(recorded), Some(checked_out)) => recorded != checked_out,
+            _ => false,
+        }
+    }
+
+    /// "recorded abc1234, checked out def5678" with abbreviated commits.
+    pub fn describe(&self) -> String {
+        let short = |commit: &Option<String>| match commit {
+            Some(commit) => commit.chars().take(7).collect(),
+            None => "-".to_string(),
+        };
+        format!(
+            "recorded {}, checked out {}",
+            short(&self.recorded),
+            short(&self.checked_out)
+        )
+    }
+}
+
+/// The gitlink entry for `path` in the superproject's index.
+fn recorded_commit(superproject: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(superproject).ok()?.to_str()?;
+    let output = run_git(
+        superproject,
+        &["ls-files", "--stage", "--", relative],
+        GIT_STATUS_TIMEOUT,
+    )
+    .ok()?;
+    parse_gitlink(&String::from_utf8_lossy(&output))
+}
+
+/// Commit of a `160000` entry in `git ls-files --stage` output.
+fn parse_gitlink(output: &str) -> Option<String> {
+    let mut fields = output.lines().next()?.split_whitespace();
+    if fields.next()? != "160000" {
+        return None;
+    }
+    fields.next().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_support::{git, TempDir};
+
+    #[test]
+    fn test_parse_gitlink() {
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(
+            parse_gitlink(&format!("160000 {sha} 0\tlib\n")),
+            Some(sha.to_string())
+        );
+        assert_eq!(parse_gitlink(&format!("100644 {sha} 0\tREADME\n")), None);
+        assert_eq!(parse_gitlink(""), None);
+    }
+
+    #[test]
+    fn test_read_detects_new_commit_in_submodule() {
+        let dir = TempDir::new("submodule");
+        let library = dir.join("library");
+        let app = dir.join("app");
+        fs::create_dir_all(&library).unwrap();
+        fs::create_dir_all(&app).unwrap();
+        git(&library, &["init"]);
+        git(&library, &["commit", "--allow-empty", "-m", "initial"]);
+        git(&app, &["init"]);
+        git(
+            &app,
+            &["submodule", "add", library.to_str().unwrap(), "lib"],
+        );
+        git(&app, &["commit", "-m", "add lib"]);
+
+        let path = app.join("lib");
+        let submodule = Submodule::read(&app, &path);
+        assert!(submodule.recorded.is_some());
+        assert!(!submodule.is_out_of_sync());
+
+        git(&path, &["commit", "--allow-empty", "-m", "ahead"]);
+        let submodule = Submodule::read(&app, &path);
+        assert!(submodule.is_out_of_sync());
+        assert!(submodule.describe().starts_with("recorded "));
+    }
+}
@@ -0,0 +1,52 @@
+//! Temporary directories and git repositories for unit tests.
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Directory under the system temp dir, removed when dropped so a failing
+/// assertion does not leave it behind.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// A fresh directory for `name`, unique to the test process, with its
+    /// canonical path so it compares equal to resolved repository paths.
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("git-dash-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir.canonicalize().unwrap())
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Run git in `dir` with a fixed identity, failing the test on error.
+pub fn git(dir: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@test.com"])
+        // Local submodule URLs are refused by default since git 2.38.1
+        .args(["-c", "protocol.file.allow=always"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
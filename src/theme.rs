@@ -0,0 +1,334 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+/// Styles for each kind of information the UI shows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// Clean state, successful results, lines added.
+    pub success: Style,
+    /// Failures, diverged branches, lines removed.
+    pub error: Style,
+    /// Dirty state, pending prompts and things needing attention.
+    pub warning: Style,
+    /// Secondary highlights such as hunk headers and untracked files.
+    pub accent: Style,
+    /// Progress and informational highlights.
+    pub info: Style,
+    /// Marked repositories and file headers.
+    pub marked: Style,
+    /// De-emphasized text such as placeholders and dates.
+    pub muted: Style,
+    /// Background of popups.
+    pub popup: Style,
+    /// Border of popups.
+    pub border: Style,
+    /// Selected row.
+    pub selection: Style,
+}
+
+pub const BUILTIN_THEMES: [&str; 5] = [
+    "dark",
+    "light",
+    "high-contrast",
+    "color-blind",
+    "monochrome",
+];
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            success: fg(Color::Green),
+            error: fg(Color::Red),
+            warning: fg(Color::Yellow),
+            accent: fg(Color::Cyan),
+            info: fg(Color::Blue),
+            marked: fg(Color::Magenta),
+            muted: fg(Color::DarkGray),
+            popup: Style::default().bg(Color::Black),
+            border: fg(Color::Cyan),
+            selection: Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            success: fg(Color::Rgb(0, 120, 0)),
+            error: fg(Color::Rgb(180, 0, 0)),
+            warning: fg(Color::Rgb(150, 90, 0)),
+            accent: fg(Color::Rgb(0, 100, 140)),
+            info: fg(Color::Rgb(0, 60, 180)),
+            marked: fg(Color::Rgb(140, 0, 140)),
+            muted: fg(Color::Rgb(110, 110, 110)),
+            popup: Style::default().bg(Color::Rgb(240, 240, 240)),
+            border: fg(Color::Rgb(0, 100, 140)),
+            selection: Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            success: fg(Color::LightGreen).add_modifier(Modifier::BOLD),
+            error: fg(Color::LightRed).add_modifier(Modifier::BOLD),
+            warning: fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+            accent: fg(Color::LightCyan),
+            info: fg(Color::LightBlue),
+            marked: fg(Color::LightMagenta).add_modifier(Modifier::BOLD),
+            muted: fg(Color::Gray),
+            popup: Style::default().bg(Color::Black).fg(Color::White),
+            border: fg(Color::White),
+            selection: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Okabe-Ito palette, which stays distinguishable with the common forms
+    /// of color blindness: no red/green pairs.
+    pub fn color_blind() -> Self {
+        Self {
+            success: fg(Color::Rgb(0, 114, 178)),
+            error: fg(Color::Rgb(213, 94, 0)).add_modifier(Modifier::BOLD),
+            warning: fg(Color::Rgb(230, 159, 0)),
+            accent: fg(Color::Rgb(86, 180, 233)),
+            info: fg(Color::Rgb(0, 158, 115)),
+            marked: fg(Color::Rgb(204, 121, 167)),
+            muted: fg(Color::DarkGray),
+            popup: Style::default().bg(Color::Black),
+            border: fg(Color::Rgb(86, 180, 233)),
+            selection: Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    /// No colors at all; states are told apart by text attributes.
+    pub fn monochrome() -> Self {
+        Self {
+            success: Style::default(),
+            error: Style::default().add_modifier(Modifier::BOLD),
+            warning: Style::default().add_modifier(Modifier::UNDERLINED),
+            accent: Style::default(),
+            info: Style::default(),
+            marked: Style::default().add_modifier(Modifier::BOLD),
+            muted: Style::default().add_modifier(Modifier::DIM),
+            popup: Style::default(),
+            border: Style::default(),
+            selection: Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "color-blind" => Some(Theme::color_blind()),
+            "monochrome" => Some(Theme::monochrome()),
+            _ => None,
+        }
+    }
+}
+
+fn fg(color: Color) -> Style {
+    Style::default().fg(color)
+}
+
+/// A style in the config: a foreground color name, or a table with `fg`,
+/// `bg` and `modifiers`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StyleSpec {
+    Color(String),
+    Full {
+        fg: Option<String>,
+        bg: Option<String>,
+        #[serde(default)]
+        modifiers: Vec<String>,
+    },
+}
+
+impl StyleSpec {
+    fn to_style(&self) -> Result<Style, String> {
+        match self {
+            StyleSpec::Color(color) => Ok(Style::default().fg(parse_color(color)?)),
+            StyleSpec::Full { fg, bg, modifiers } => {
+                let mut style = Style::default();
+                if let Some(color) = fg {
+                    style = style.fg(parse_color(color)?);
+                }
+                if let Some(color) = bg {
+                    style = style.bg(parse_color(color)?);
+                }
+                for modifier in modifiers {
+                    style = style.add_modifier(parse_modifier(modifier)?);
+                }
+                Ok(style)
+            }
+        }
+    }
+}
+
+/// User theme: a built-in base with some styles replaced.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub base: Option<String>,
+    pub success: Option<StyleSpec>,
+    pub error: Option<StyleSpec>,
+    pub warning: Option<StyleSpec>,
+    pub accent: Option<StyleSpec>,
+    pub info: Option<StyleSpec>,
+    pub marked: Option<StyleSpec>,
+    pub muted: Option<StyleSpec>,
+    pub popup: Option<StyleSpec>,
+    pub border: Option<StyleSpec>,
+    pub selection: Option<StyleSpec>,
+}
+
+/// Resolve the configured theme name against the built-in and user themes.
+/// `NO_COLOR` forces the monochrome theme.
+pub fn resolve_theme(
+    name: &str,
+    themes: &BTreeMap<String, ThemeConfig>,
+    no_color: bool,
+) -> Result<Theme, String> {
+    let theme = lookup_theme(name, themes, 0)?;
+    if no_color {
+        return Ok(Theme::monochrome());
+    }
+    Ok(theme)
+}
+
+fn lookup_theme(
+    name: &str,
+    themes: &BTreeMap<String, ThemeConfig>,
+    depth: usize,
+) -> Result<Theme, String> {
+    if let Some(config) = themes.get(name) {
+        if depth > themes.len() {
+            return Err(format!("theme \"{name}\" has a cyclic base"));
+        }
+        let base = config.base.as_deref().unwrap_or("dark");
+        let mut theme = if base == name {
+            Theme::builtin(base).ok_or_else(|| format!("unknown theme \"{base}\""))?
+        } else {
+            lookup_theme(base, themes, depth + 1)?
+        };
+        let slots = [
+            (&mut theme.success, &config.success),
+            (&mut theme.error, &config.error),
+            (&mut theme.warning, &config.warning),
+            (&mut theme.accent, &config.accent),
+            (&mut theme.info, &config.info),
+            (&mut theme.marked, &config.marked),
+            (&mut theme.muted, &config.muted),
+            (&mut theme.popup, &config.popup),
+            (&mut theme.border, &config.border),
+            (&mut theme.selection, &config.selection),
+        ];
+        for (slot, spec) in slots {
+            if let Some(spec) = spec {
+                *slot = spec
+                    .to_style()
+                    .map_err(|err| format!("theme \"{name}\": {err}"))?;
+            }
+        }
+        return Ok(theme);
+    }
+    Theme::builtin(name).ok_or_else(|| {
+        format!(
+            "unknown theme \"{name}\" (built-in: {})",
+            BUILTIN_THEMES.join(", ")
+        )
+    })
+}
+
+fn parse_color(text: &str) -> Result<Color, String> {
+    Color::from_str(text).map_err(|_| format!("invalid color \"{text}\""))
+}
+
+fn parse_modifier(text: &str) -> Result<Modifier, String> {
+    match text.to_ascii_lowercase().as_str() {
+        "bold" => Ok(Modifier::BOLD),
+        "dim" => Ok(Modifier::DIM),
+        "italic" => Ok(Modifier::ITALIC),
+        "underlined" | "underline" => Ok(Modifier::UNDERLINED),
+        "reversed" | "reverse" => Ok(Modifier::REVERSED),
+        "crossed-out" => Ok(Modifier::CROSSED_OUT),
+        _ => Err(format!("invalid modifier \"{text}\"")),
+    }
+}
+
+/// `NO_COLOR` is set to a non-empty value (see https://no-color.org).
+pub fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+pub fn init_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// The active theme, dark until `init_theme` is called.
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::dark)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn themes(toml: &str) -> BTreeMap<String, ThemeConfig> {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_themes_resolve() {
+        for name in BUILTIN_THEMES {
+            assert!(
+                resolve_theme(name, &BTreeMap::new(), false).is_ok(),
+                "{name}"
+            );
+        }
+        assert!(resolve_theme("solarized", &BTreeMap::new(), false).is_err());
+    }
+
+    #[test]
+    fn test_no_color_forces_monochrome() {
+        let theme = resolve_theme("dark", &BTreeMap::new(), true).unwrap();
+        assert_eq!(theme, Theme::monochrome());
+    }
+
+    #[test]
+    fn test_user_theme_overrides_base() {
+        let themes = themes(
+            r##"
+            [mine]
+            base = "light"
+            error = "#ff0000"
+            marked = { fg = "magenta", modifiers = ["bold", "italic"] }
+            "##,
+        );
+        let theme = resolve_theme("mine", &themes, false).unwrap();
+        assert_eq!(theme.error, Style::default().fg(Color::Rgb(255, 0, 0)));
+        assert_eq!(
+            theme.marked,
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD | Modifier::ITALIC)
+        );
+        assert_eq!(theme.success, Theme::light().success);
+    }
+
+    #[test]
+    fn test_user_theme_errors() {
+        let bad_color = themes("[mine]\nerror = \"not-a-color\"");
+        assert!(resolve_theme("mine", &bad_color, false).is_err());
+        let cyclic = themes("[a]\nbase = \"b\"\n[b]\nbase = \"a\"");
+        assert!(resolve_theme("a", &cyclic, false).is_err());
+    }
+}
@@ -1,40 +1,123 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
 use ratatui::layout::Alignment;
 use ratatui::prelude::*;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{
-    Block, Borders, Cell, Clear, Gauge, Paragraph, Row, Table, TableState, Wrap,
+    Block, Borders, Cell, Clear, Gauge, List, ListItem, ListState, Paragraph, Row, Table,
+    TableState, Wrap,
+};
+
+use crate::app::{
+    App, BatchStage, BatchView, BranchView, DetailView, DiffView, ExecView, HitAreas, LogView,
+    Menu, MenuKind, Pane, PruneStage, PruneView,
 };
+use crate::branches::CheckoutPlan;
+use crate::columns::{layout_columns, Column, ColumnPicker, SortOrder, COLUMN_SPACING};
+use crate::diff::{DiffLine, DiffLineKind};
+use crate::discovery::ScanTargets;
+use crate::git::friendly_error;
+use crate::history::{LogEntry, LogMode};
+use crate::keymap::{sequence_label, Command, KeyContext, Keymap};
+use crate::manifest::ManifestMark;
+use crate::status::{
+    format_size, format_time_age, parse_ahead_behind, RepoState, NO_BRANCH, NO_CHANGES,
+    NO_LAST_FETCH, NO_REMOTE,
+};
+use crate::theme::theme;
+
+/// Help overlay sections: title, key context and the commands listed.
+const HELP_SECTIONS: &[(&str, KeyContext, &[Command])] = &[
+    (
+        "NAVIGATION",
+        KeyContext::Table,
+        &[
+            Command::Down,
+            Command::Up,
+            Command::PageDown,
+            Command::PageUp,
+            Command::First,
+            Command::Last,
+        ],
+    ),
+    (
+        "ACTIONS",
+        KeyContext::Table,
+        &[
+            Command::Fetch,
+            Command::Pull,
+            Command::Push,
+            Command::Refresh,
+            Command::Mark,
+            Command::MarkAll,
+            Command::BatchCheckout,
+            Command::Prune,
+            Command::Exec,
+            Command::AddWorktree,
+            Command::PruneWorktrees,
+            Command::CloneMissing,
+            Command::Log,
+            Command::Details,
+            Command::Branches,
+            Command::Open,
+            Command::Browse,
+            Command::Copy,
+        ],
+    ),
+    (
+        "VIEW",
+        KeyContext::Table,
+        &[
+            Command::CycleSort,
+            Command::ReverseSort,
+            Command::Search,
+            Command::ClearSearch,
+            Command::ToggleMouse,
+            Command::Palette,
+            Command::Columns,
+            Command::Help,
+        ],
+    ),
+    (
+        "PANES",
+        KeyContext::Pane,
+        &[
+            Command::Close,
+            Command::Down,
+            Command::Up,
+            Command::First,
+            Command::Last,
+            Command::NextSection,
+            Command::PrevSection,
+        ],
+    ),
+];
 
-use crate::app::App;
-use crate::status::{parse_ahead_behind, RepoState, NO_CHANGES, NO_LAST_FETCH};
-use crate::worker::Action;
-
-const HELP_TEXT: &[&str] = &[
-    "NAVIGATION",
-    "  j / ↓          Move selection down",
-    "  k / ↑          Move selection up",
-    "  PgDn           Page down",
-    "  PgUp           Page up",
-    "  g / Home       Jump to first repository",
-    "  G / End        Jump to last repository",
-    "",
-    "ACTIONS",
-    "  p              Pull (with confirmation)",
-    "  u              Push (with confirmation)",
-    "  r              Refresh repository status",
-    "",
-    "VIEW",
-    "  s              Cycle sort order (Name → Status → Ahead/Behind → Last Fetch)",
-    "  /              Search/filter repositories by name",
-    "  Esc            Clear search filter",
-    "  ?              Toggle this help screen",
-    "",
-    "OTHER",
-    "  q / Ctrl+C     Quit git-dash",
-    "  y              Confirm action",
-    "  n / Esc        Cancel action",
+/// Pane contexts whose own bindings get a help section.
+const HELP_PANES: &[(&str, KeyContext)] = &[
+    ("LOG", KeyContext::Log),
+    ("DETAIL", KeyContext::Detail),
+    ("DIFF", KeyContext::Diff),
+    ("BRANCHES", KeyContext::Branches),
+    ("BATCH CHECKOUT", KeyContext::Batch),
+    ("PRUNE", KeyContext::Prune),
+    ("COMMAND OUTPUT", KeyContext::Exec),
+    ("MENUS", KeyContext::Menu),
+    ("PROMPTS", KeyContext::Prompt),
+    ("CONFIRMATION", KeyContext::Confirm),
+    ("SEARCH", KeyContext::Search),
+    ("COMMAND PALETTE", KeyContext::Palette),
+    ("COLUMN PICKER", KeyContext::ColumnPicker),
 ];
 
+/// Footer hint: one command, or two commands sharing a label like `j/k scroll`.
+enum Hint {
+    Key(Command, &'static str),
+    Pair(Command, Command, &'static str),
+}
+
 pub fn render_ui(frame: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -46,6 +129,7 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
         .split(frame.area());
 
     render_header(frame, chunks[0], app);
+    app.hit_areas = HitAreas::default();
 
     // Get filtered repos and their count before borrowing table_state mutably
     let filtered_indices = app.filtered_indices();
@@ -54,22 +138,64 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
     let search_query = app.search_query.clone();
     let status_line = app.status_line.clone();
 
-    // Show empty state if no repos found
-    if total_count == 0 && !app.loading {
+    if let Some(pane) = app.panes.last() {
+        render_pane(frame, chunks[1], pane, app);
+    } else if total_count == 0 && !app.loading {
+        // Show empty state if no repos found
         render_empty_state(frame, chunks[1]);
     } else if filtered_count == 0 && !search_query.is_empty() {
         render_no_results_state(frame, chunks[1], &search_query);
     } else {
-        let table = build_table(&app.repos, &filtered_indices);
+        let inner = Block::default().borders(Borders::ALL).inner(chunks[1]);
+        let layout = layout_columns(&app.columns, inner.width);
+        let table = build_table(
+            &app.repos,
+            &filtered_indices,
+            &app.marked,
+            &layout,
+            &app.targets,
+            app.sort_order,
+        );
         frame.render_stateful_widget(table, chunks[1], &mut app.table_state);
+        app.hit_areas.table = inner;
+        let mut x = inner.x;
+        for (column, width) in layout {
+            app.hit_areas.columns.push((column, x, width));
+            x += width + COLUMN_SPACING;
+        }
         render_scroll_hints(frame, chunks[1], filtered_count, &app.table_state);
     }
 
     // Build footer text with appropriate styling
     let (footer_text, footer_style) = if app.search_mode {
+        (format!("Search: {}_", app.search_query), theme().warning)
+    } else if let Some(prompt) = &app.prompt {
+        (
+            format!(
+                "{} in {} repos: {}_",
+                prompt.kind.label(),
+                app.batch_targets().len(),
+                prompt.input
+            ),
+            theme().warning,
+        )
+    } else if let Some(action) = &app.confirmation {
+        (
+            format!("Confirm {}? ({})", action.describe(), confirm_keys(app)),
+            theme().warning,
+        )
+    } else if !app.pending_keys.is_empty() {
         (
-            format!("Search: {}_", app.search_query),
-            Style::default().fg(Color::Yellow),
+            format!("Keys: {} …", sequence_label(&app.pending_keys)),
+            theme().warning,
+        )
+    } else if let Some((branches, repos)) = pending_prune(app) {
+        (
+            format!(
+                "Delete {branches} branches in {repos} repos? ({})",
+                confirm_keys(app)
+            ),
+            theme().warning,
         )
     } else if !search_query.is_empty() {
         (
@@ -79,14 +205,10 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
             ),
             Style::default(),
         )
-    } else if let Some(action) = &app.confirmation {
-        let label = match action {
-            Action::Pull => "Pull",
-            Action::Push => "Push",
-        };
+    } else if app.loading && app.repos.iter().any(|repo| repo.stale) {
         (
-            format!("Confirm {label}? (y/n)"),
-            Style::default().fg(Color::Yellow),
+            "Scanning repositories... (dimmed rows show cached status)".to_string(),
+            Style::default(),
         )
     } else if app.loading {
         ("Scanning repositories...".to_string(), Style::default())
@@ -99,27 +221,118 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
             app.status_line.clone()
         };
 
-        let color = match app.status_type {
-            crate::app::StatusType::Success => Color::Green,
-            crate::app::StatusType::Error => Color::Red,
-            crate::app::StatusType::Info => Color::Reset,
+        let style = match app.status_type {
+            crate::app::StatusType::Success => theme().success,
+            crate::app::StatusType::Error => theme().error,
+            crate::app::StatusType::Info => Style::default(),
         };
 
-        (with_timestamp, Style::default().fg(color))
+        (with_timestamp, style)
     };
 
-    let footer = Block::default()
-        .title("q quit | r refresh | p pull | u push | s sort | / search | ? help")
-        .borders(Borders::ALL);
+    let hints = match &app.menu {
+        _ if app.palette.is_some() => palette_hints(app),
+        _ if app.column_picker.is_some() => column_picker_hints(app),
+        Some(Menu {
+            kind: MenuKind::Browse(_),
+            ..
+        }) => menu_hints(app, "open | Shift+key copy"),
+        Some(Menu {
+            kind: MenuKind::Copy(_),
+            ..
+        }) => menu_hints(app, "copy"),
+        Some(_) => menu_hints(app, "launch"),
+        None => {
+            let (hints, areas) = keymap_hints(app, chunks[2]);
+            app.hit_areas.hints = areas;
+            hints
+        }
+    };
+    let footer = Block::default().title(hints).borders(Borders::ALL);
     let footer_paragraph = Paragraph::new(footer_text)
         .block(footer)
         .style(footer_style)
         .wrap(Wrap { trim: true });
     frame.render_widget(footer_paragraph, chunks[2]);
 
+    if app.menu.is_some() {
+        app.hit_areas.menu = render_menu(frame, app);
+    }
+
+    if app.palette.is_some() {
+        render_palette(frame, app);
+    }
+
+    if let Some(picker) = &app.column_picker {
+        render_column_picker(frame, picker);
+    }
+
     // Render help overlay on top if visible
     if app.help_visible {
-        render_help_overlay(frame);
+        render_help_overlay(frame, &app.keymap);
+    }
+}
+
+/// Selected branch and repository counts while a prune awaits confirmation.
+/// First key of `command` in `context`, or "?" when it is unbound.
+fn first_key(app: &App, context: KeyContext, command: Command) -> String {
+    app.keymap
+        .keys_for(context, command)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| "?".to_string())
+}
+
+/// Keys answering a confirmation, e.g. "y/n".
+fn confirm_keys(app: &App) -> String {
+    format!(
+        "{}/{}",
+        first_key(app, KeyContext::Confirm, Command::Accept),
+        first_key(app, KeyContext::Confirm, Command::Cancel)
+    )
+}
+
+fn menu_hints(app: &App, action: &str) -> String {
+    let key = |command| first_key(app, KeyContext::Menu, command);
+    format!(
+        "key/{} {action} | {}/{} move | {} cancel",
+        key(Command::Accept),
+        key(Command::Down),
+        key(Command::Up),
+        key(Command::Cancel)
+    )
+}
+
+fn palette_hints(app: &App) -> String {
+    let key = |command| first_key(app, KeyContext::Palette, command);
+    format!(
+        "type to filter | {}/{} move | {} run | {} close",
+        key(Command::Down),
+        key(Command::Up),
+        key(Command::Accept),
+        key(Command::Cancel)
+    )
+}
+
+fn column_picker_hints(app: &App) -> String {
+    let key = |command| first_key(app, KeyContext::ColumnPicker, command);
+    format!(
+        "{} show/hide | {}/{} reorder | {}/{} width | {} auto | {} apply | {} cancel",
+        key(Command::ToggleSelection),
+        key(Command::MoveDown),
+        key(Command::MoveUp),
+        key(Command::Widen),
+        key(Command::Narrow),
+        key(Command::ResetWidth),
+        key(Command::Accept),
+        key(Command::Cancel)
+    )
+}
+
+fn pending_prune(app: &App) -> Option<(usize, usize)> {
+    match app.panes.last() {
+        Some(Pane::Prune(view)) if view.stage == PruneStage::Confirm => Some(view.selected_count()),
+        _ => None,
     }
 }
 
@@ -151,109 +364,732 @@ fn render_header(frame: &mut Frame, area: Rect, app: &App) {
             });
 
     let title = if !app.loading && total_repos > 0 {
-        format!(
+        let mut title = format!(
             "git-dash — {} │ {} repos │ {} dirty │ {} ahead │ {} behind",
-            app.root.display(),
+            header_location(app),
             total_repos,
             dirty_count,
             ahead_count,
             behind_count
-        )
+        );
+        let missing = app.repos.iter().filter(|r| r.is_placeholder()).count();
+        if missing > 0 {
+            title.push_str(&format!(" │ {missing} missing"));
+        }
+        if !app.marked.is_empty() {
+            title.push_str(&format!(" │ {} marked", app.marked.len()));
+        }
+        title
     } else {
-        format!("git-dash — {}", app.root.display())
+        format!("git-dash — {}", header_location(app))
     };
 
     let title_paragraph = Paragraph::new(title).wrap(Wrap { trim: true });
     frame.render_widget(title_paragraph, header_chunks[0]);
 
-    if app.loading {
+    if let Some((done, total)) = app.clone_progress {
+        let gauge = Gauge::default()
+            .ratio(done as f64 / total.max(1) as f64)
+            .label(format!("Cloning {done}/{total}"))
+            .gauge_style(theme().info);
+        frame.render_widget(gauge, header_chunks[1]);
+    } else if app.loading {
         let ratio = app.scan_progress.clamp(0.0, 1.0);
         let percent = (ratio * 100.0).round() as u16;
         let gauge = Gauge::default()
             .ratio(ratio)
             .label(format!("Scanning {percent}%"))
-            .gauge_style(Style::default().fg(Color::Blue).bg(Color::Black));
+            .gauge_style(theme().info);
         frame.render_widget(gauge, header_chunks[1]);
     }
 }
 
-fn build_table<'a>(repos: &'a [RepoState], indices: &'a [usize]) -> Table<'a> {
-    let header = Row::new(vec![
-        Cell::from("Repository"),
-        Cell::from("Branch"),
-        Cell::from("Dirty"),
-        Cell::from("Ahead/Behind"),
-        Cell::from("Changes"),
-        Cell::from("Remote"),
-        Cell::from("Last Fetch"),
-    ])
+/// Where the repositories come from: the scan root, or with several roots
+/// and listed repositories the source of the selected one and how many
+/// there are.
+fn header_location(app: &App) -> String {
+    let targets = &app.targets;
+    if let ([root], []) = (targets.roots.as_slice(), targets.repos.as_slice()) {
+        return root.display().to_string();
+    }
+    let mut counts = Vec::new();
+    match targets.roots.len() {
+        0 => {}
+        1 => counts.push("1 root".to_string()),
+        count => counts.push(format!("{count} roots")),
+    }
+    match targets.repos.len() {
+        0 => {}
+        1 => counts.push("1 listed repo".to_string()),
+        count => counts.push(format!("{count} listed repos")),
+    }
+    let counts = counts.join(", ");
+    match app.selected_repo() {
+        Some(repo) => match repo.source_root(targets) {
+            Some(root) => format!("{} ({counts})", root.display()),
+            None => format!("listed ({counts})"),
+        },
+        None => counts,
+    }
+}
+
+fn build_table<'a>(
+    repos: &'a [RepoState],
+    indices: &'a [usize],
+    marked: &HashSet<PathBuf>,
+    layout: &[(Column, u16)],
+    targets: &ScanTargets,
+    sort_order: SortOrder,
+) -> Table<'a> {
+    let header = Row::new(layout.iter().map(|(column, _)| {
+        if *column == sort_order.column {
+            Cell::from(format!("{} {}", column.title(), sort_order.indicator()))
+        } else {
+            Cell::from(column.title())
+        }
+    }))
     .style(Style::default().add_modifier(Modifier::BOLD));
 
     let rows = indices
         .iter()
         .filter_map(|idx| repos.get(*idx))
         .map(|repo| {
-            let dirty = if repo.dirty { "dirty *" } else { "clean ." };
-            let dirty_style = if repo.dirty {
-                Style::default().fg(Color::Yellow)
+            let row = Row::new(
+                layout
+                    .iter()
+                    .map(|(column, _)| column_cell(repo, *column, marked, targets)),
+            );
+            // Cached rows stay dimmed until the scan confirms them
+            if repo.stale {
+                row.style(theme().muted.add_modifier(Modifier::DIM))
             } else {
-                Style::default().fg(Color::Cyan)
-            };
+                row
+            }
+        });
+
+    let widths = layout.iter().map(|(_, width)| Constraint::Length(*width));
+    Table::new(rows, widths)
+        .column_spacing(COLUMN_SPACING)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("Repositories"))
+        .row_highlight_style(theme().selection)
+}
 
+fn column_cell<'a>(
+    repo: &'a RepoState,
+    column: Column,
+    marked: &HashSet<PathBuf>,
+    targets: &ScanTargets,
+) -> Cell<'a> {
+    match column {
+        Column::Name if marked.contains(&repo.path) => {
+            Cell::from(format!("● {}", repo.name)).style(theme().marked)
+        }
+        Column::Name => match &repo.submodule {
+            // Out of sync with the commit the superproject records
+            Some(submodule) if submodule.is_out_of_sync() => {
+                Cell::from(format!("└ {} ≠", repo.name)).style(theme().warning)
+            }
+            Some(_) => Cell::from(format!("└ {}", repo.name)),
+            None if repo.worktree_of.is_some() => Cell::from(format!("└ {}", repo.name)),
+            None => match (&repo.bare, &repo.manifest) {
+                (Some(bare), _) if bare.mirror => Cell::from(format!("{} [mirror]", repo.name)),
+                (Some(_), _) => Cell::from(format!("{} [bare]", repo.name)),
+                (None, Some(ManifestMark::Missing(_))) => {
+                    Cell::from(format!("{} [missing]", repo.name)).style(theme().muted)
+                }
+                (None, Some(ManifestMark::Undeclared)) => {
+                    Cell::from(format!("{} [undeclared]", repo.name)).style(theme().warning)
+                }
+                (None, _) => Cell::from(repo.name.as_str()),
+            },
+        },
+        Column::Branch => Cell::from(repo.branch.as_str()),
+        Column::Dirty if repo.is_bare() => Cell::from("bare").style(theme().muted),
+        Column::Dirty if repo.is_placeholder() => Cell::from("missing").style(theme().muted),
+        Column::Dirty if repo.dirty => Cell::from("dirty *").style(theme().warning),
+        Column::Dirty => Cell::from("clean .").style(theme().accent),
+        Column::AheadBehind => {
             // Color-code ahead/behind based on status
-            let ahead_behind_style = match parse_ahead_behind(&repo.ahead_behind) {
-                Some((0, 0)) => Style::default().fg(Color::DarkGray),
+            let style = match parse_ahead_behind(&repo.ahead_behind) {
+                Some((0, 0)) => theme().muted,
                 Some((ahead, behind)) if ahead > 0 && behind > 0 => {
                     // Diverged - both ahead and behind
-                    Style::default().fg(Color::Red)
+                    theme().error
                 }
                 Some((ahead, _)) if ahead > 0 => {
                     // Only ahead
-                    Style::default().fg(Color::Green)
+                    theme().success
                 }
                 Some((_, behind)) if behind > 0 => {
                     // Only behind
-                    Style::default().fg(Color::Yellow)
+                    theme().warning
                 }
-                _ => Style::default().fg(Color::DarkGray),
+                _ => theme().muted,
+            };
+            Cell::from(repo.ahead_behind.as_str()).style(style)
+        }
+        // Show error message in the changes column if present
+        Column::Changes => match (&repo.error_message, &repo.bare) {
+            (Some(err), _) => Cell::from(format!("⚠ {}", err)).style(theme().error),
+            // Bare repositories have refs instead of changes
+            (None, Some(bare)) => Cell::from(bare.describe_refs()).style(theme().muted),
+            (None, None) if repo.is_placeholder() => {
+                Cell::from(repo.change_summary.as_str()).style(theme().muted)
+            }
+            (None, None) => Cell::from(colorize_change_summary(&repo.change_summary)),
+        },
+        Column::Remote => Cell::from(repo.remote_url.as_str()),
+        // Color-code last fetch by staleness
+        Column::LastFetch => Cell::from(last_fetch_label(repo.last_fetch))
+            .style(get_staleness_style(repo.last_fetch)),
+        Column::Stashes if repo.stash_count == 0 => Cell::from("-").style(theme().muted),
+        Column::Stashes => Cell::from(repo.stash_count.to_string()).style(theme().warning),
+        Column::LastCommit => match repo.last_commit {
+            Some(time) => Cell::from(format_time_age(time)),
+            None => Cell::from("-").style(theme().muted),
+        },
+        Column::Upstream => Cell::from(repo.upstream.as_str()),
+        Column::Size => match repo.size {
+            Some(bytes) => Cell::from(format_size(bytes)),
+            None => Cell::from("-").style(theme().muted),
+        },
+        Column::Path => {
+            let root = repo.source_root(targets).unwrap_or(Path::new(""));
+            let relative = match repo.path.strip_prefix(root) {
+                Ok(path) if path.as_os_str().is_empty() => ".".to_string(),
+                Ok(path) => path.display().to_string(),
+                Err(_) => repo.path.display().to_string(),
             };
+            Cell::from(relative)
+        }
+    }
+}
+
+fn render_pane(frame: &mut Frame, area: Rect, pane: &Pane, app: &App) {
+    match pane {
+        Pane::Log(view) => render_log_view(frame, area, view),
+        Pane::Detail(view) => render_detail_view(frame, area, view, app),
+        Pane::Diff(view) => render_diff_view(frame, area, view),
+        Pane::Branches(view) => render_branch_view(frame, area, view),
+        Pane::Batch(view) => render_batch_view(frame, area, view),
+        Pane::Prune(view) => render_prune_view(frame, area, view),
+        Pane::Exec(view) => render_exec_view(frame, area, view),
+    }
+}
+
+fn render_exec_view(frame: &mut Frame, area: Rect, view: &ExecView) {
+    let finished = view.rows.iter().filter(|row| row.outcome.is_some()).count();
+    let failed = view
+        .rows
+        .iter()
+        .filter(|row| {
+            row.outcome
+                .as_ref()
+                .is_some_and(|outcome| !outcome.is_success())
+        })
+        .count();
+    let progress = if view.done {
+        format!("{} ok, {failed} failed", finished - failed)
+    } else {
+        format!("running {finished}/{}", view.rows.len())
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" $ {} │ {progress} ", view.command));
 
-            // Show error message in the changes column if present
-            let change_cell = if let Some(err) = &repo.error_message {
-                Cell::from(format!("⚠ {}", err)).style(Style::default().fg(Color::Red))
+    let mut lines = Vec::new();
+    for row in &view.rows {
+        let (summary, style) = match &row.outcome {
+            None => ("running...".to_string(), theme().muted),
+            Some(outcome) if outcome.is_success() => (outcome.summary(), theme().success),
+            Some(outcome) => (outcome.summary(), theme().error),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("==> {} ", row.name),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(summary, style),
+        ]));
+        for (is_stderr, text) in row.output_lines() {
+            let style = if is_stderr {
+                theme().warning
             } else {
-                Cell::from(colorize_change_summary(&repo.change_summary))
+                Style::default()
             };
+            lines.push(Line::from(Span::styled(text.to_string(), style)));
+        }
+        lines.push(Line::from(""));
+    }
 
-            // Color-code last fetch by staleness
-            let fetch_style = get_staleness_style(&repo.last_fetch);
-
-            Row::new(vec![
-                Cell::from(repo.name.clone()),
-                Cell::from(repo.branch.clone()),
-                Cell::from(dirty).style(dirty_style),
-                Cell::from(repo.ahead_behind.clone()).style(ahead_behind_style),
-                change_cell,
-                Cell::from(repo.remote_url.clone()),
-                Cell::from(repo.last_fetch.clone()).style(fetch_style),
-            ])
-        });
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((view.scroll.min(u16::MAX as usize) as u16, 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn render_prune_view(frame: &mut Frame, area: Rect, view: &PruneView) {
+    let (selected_branches, selected_repos) = view.selected_count();
+    let stage = match view.stage {
+        PruneStage::Scanning => "scanning repositories...".to_string(),
+        PruneStage::Review | PruneStage::Confirm => {
+            format!("{selected_branches} selected in {selected_repos} repos")
+        }
+        PruneStage::Running { done, total } => format!("deleting {done}/{total}"),
+        PruneStage::Done => "done".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Prune branches │ {stage} "));
+
+    if view.stage != PruneStage::Scanning && view.groups.is_empty() {
+        let paragraph = Paragraph::new("No gone or merged branches found")
+            .block(block)
+            .style(theme().muted);
+        frame.render_widget(paragraph, area);
+        return;
+    }
+
+    let dim = theme().muted;
+    let items: Vec<ListItem> = view
+        .rows()
+        .into_iter()
+        .map(|(group_idx, item_idx)| {
+            let group = &view.groups[group_idx];
+            let Some(item_idx) = item_idx else {
+                let mut spans = vec![Span::styled(
+                    group.name.clone(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )];
+                if !group.default_branch.is_empty() {
+                    spans.push(Span::styled(
+                        format!(" (default: {})", group.default_branch),
+                        dim,
+                    ));
+                }
+                if let Some(err) = &group.error {
+                    spans.push(Span::styled(format!("  ⚠ {err}"), theme().error));
+                }
+                match &group.result {
+                    Some(Ok(message)) => {
+                        spans.push(Span::styled(format!("  OK {message}"), theme().success))
+                    }
+                    Some(Err(err)) => spans.push(Span::styled(
+                        format!("  FAIL {}", friendly_error(err)),
+                        theme().error,
+                    )),
+                    None => {}
+                }
+                return ListItem::new(Line::from(spans));
+            };
+
+            let item = &group.items[item_idx];
+            let checkbox = if item.selected { "[x]" } else { "[ ]" };
+            let mut spans = vec![
+                Span::raw(format!("  {checkbox} ")),
+                Span::raw(item.candidate.branch.clone()),
+            ];
+            if item.candidate.gone {
+                spans.push(Span::styled("  gone", theme().error));
+            }
+            if item.candidate.merged {
+                spans.push(Span::styled("  merged", theme().accent));
+            }
+            spans.push(Span::styled(
+                format!("  {}", item.candidate.last_commit),
+                dim,
+            ));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(theme().selection);
+    let mut state = ListState::default().with_selected(Some(view.cursor));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_batch_view(frame: &mut Frame, area: Rect, view: &BatchView) {
+    let stage = match view.stage {
+        BatchStage::Preflight => "checking repositories...".to_string(),
+        BatchStage::Review => "review".to_string(),
+        BatchStage::Running { done, total } => format!("running {done}/{total}"),
+        BatchStage::Done => "done".to_string(),
+    };
+    let title = format!(
+        " Checkout {} — {} repos │ {} ",
+        view.branch,
+        view.rows.len(),
+        stage
+    );
 
-    Table::new(
+    let header = Row::new(vec![
+        Cell::from("Repository"),
+        Cell::from("Pre-flight"),
+        Cell::from("Result"),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let rows = view.rows.iter().map(|row| {
+        let plan_cell = match &row.plan {
+            None => Cell::from("…").style(theme().muted),
+            Some(plan) => {
+                let style = match plan {
+                    CheckoutPlan::AlreadyOn => theme().muted,
+                    CheckoutPlan::Switch | CheckoutPlan::Track => theme().success,
+                    CheckoutPlan::Missing | CheckoutPlan::Dirty => theme().warning,
+                    CheckoutPlan::Fail(_) => theme().error,
+                };
+                Cell::from(plan.label()).style(style)
+            }
+        };
+        let result_cell = match &row.result {
+            Some(Ok(message)) => Cell::from(format!("OK {message}")).style(theme().success),
+            Some(Err(err)) => {
+                Cell::from(format!("FAIL {}", friendly_error(err))).style(theme().error)
+            }
+            None if view.stage == BatchStage::Done => Cell::from("SKIP").style(theme().muted),
+            None => Cell::from(""),
+        };
+        Row::new(vec![Cell::from(row.name.clone()), plan_cell, result_cell])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(30),
+            Constraint::Percentage(45),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(title))
+    .row_highlight_style(theme().selection);
+    let mut state = TableState::default().with_selected(Some(view.scroll));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn render_branch_view(frame: &mut Frame, area: Rect, view: &BranchView) {
+    let title = format!(" Branches — {} ", view.repo_name);
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    if view.branches.is_empty() || view.error.is_some() {
+        let (message, style) = if let Some(err) = &view.error {
+            (format!("⚠ {err}"), theme().error)
+        } else if view.loading {
+            ("Loading branches...".to_string(), theme().muted)
+        } else {
+            ("No local branches".to_string(), theme().muted)
+        };
+        frame.render_widget(Paragraph::new(message).block(block).style(style), area);
+        return;
+    }
+
+    let header = Row::new(vec![
+        Cell::from("Branch"),
+        Cell::from("Upstream"),
+        Cell::from("Ahead/Behind"),
+        Cell::from("Last Commit"),
+        Cell::from("Flags"),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let rows = view.branches.iter().map(|branch| {
+        let name = if branch.is_head {
+            Cell::from(format!("* {}", branch.name)).style(theme().success)
+        } else {
+            Cell::from(format!("  {}", branch.name))
+        };
+        let upstream = if branch.upstream.is_empty() {
+            Cell::from("-").style(theme().muted)
+        } else {
+            Cell::from(branch.upstream.clone())
+        };
+        let ahead_behind = if branch.upstream.is_empty() || branch.gone {
+            Cell::from("-").style(theme().muted)
+        } else {
+            let style = match (branch.ahead, branch.behind) {
+                (0, 0) => theme().muted,
+                (ahead, behind) if ahead > 0 && behind > 0 => theme().error,
+                (ahead, _) if ahead > 0 => theme().success,
+                _ => theme().warning,
+            };
+            Cell::from(format!("+{}/-{}", branch.ahead, branch.behind)).style(style)
+        };
+        let mut flags = Vec::new();
+        if branch.gone {
+            flags.push(Span::styled("gone", theme().error));
+        }
+        if branch.merged && !branch.is_head {
+            if !flags.is_empty() {
+                flags.push(Span::raw(" "));
+            }
+            flags.push(Span::styled("merged", theme().accent));
+        }
+        Row::new(vec![
+            name,
+            upstream,
+            ahead_behind,
+            Cell::from(branch.last_commit.clone()).style(theme().muted),
+            Cell::from(Line::from(flags)),
+        ])
+    });
+
+    let table = Table::new(
         rows,
         [
-            Constraint::Percentage(18),
-            Constraint::Percentage(10),
-            Constraint::Percentage(8),
+            Constraint::Percentage(32),
+            Constraint::Percentage(26),
             Constraint::Percentage(12),
-            Constraint::Percentage(22),
-            Constraint::Percentage(20),
-            Constraint::Percentage(10),
+            Constraint::Percentage(16),
+            Constraint::Percentage(14),
         ],
     )
     .header(header)
-    .block(Block::default().borders(Borders::ALL).title("Repositories"))
-    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .block(block)
+    .row_highlight_style(theme().selection);
+    let mut state = TableState::default().with_selected(Some(view.selected));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn render_detail_view(frame: &mut Frame, area: Rect, view: &DetailView, app: &App) {
+    let Some(repo) = app.repos.iter().find(|repo| repo.path == view.path) else {
+        let paragraph = Paragraph::new("Repository no longer available")
+            .block(Block::default().borders(Borders::ALL).title(" Details "))
+            .style(theme().muted);
+        frame.render_widget(paragraph, area);
+        return;
+    };
+
+    let label = theme().muted;
+    let mut info = vec![
+        Line::from(vec![
+            Span::styled("Path:         ", label),
+            Span::raw(repo.path.display().to_string()),
+        ]),
+        Line::from(vec![
+            Span::styled("Branch:       ", label),
+            Span::raw(repo.branch.clone()),
+        ]),
+        Line::from(vec![
+            Span::styled("Ahead/Behind: ", label),
+            Span::raw(repo.ahead_behind.clone()),
+        ]),
+        Line::from(vec![
+            Span::styled("Remote:       ", label),
+            Span::raw(repo.remote_url.clone()),
+            Span::styled("  (last fetch ", label),
+            Span::raw(last_fetch_label(repo.last_fetch)),
+            Span::styled(")", label),
+        ]),
+    ];
+    if let Some(submodule) = &repo.submodule {
+        let style = if submodule.is_out_of_sync() {
+            theme().warning
+        } else {
+            Style::default()
+        };
+        let superproject = submodule.superproject.file_name().map_or_else(
+            || submodule.superproject.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        info.push(Line::from(vec![
+            Span::styled("Submodule of: ", label),
+            Span::raw(superproject),
+            Span::styled("  (", label),
+            Span::styled(submodule.describe(), style),
+            Span::styled(")", label),
+        ]));
+    }
+    if let Some(main) = &repo.worktree_of {
+        info.push(Line::from(vec![
+            Span::styled("Worktree of:  ", label),
+            Span::raw(main.display().to_string()),
+        ]));
+    }
+    if let Some(bare) = &repo.bare {
+        let size = repo.size.map_or_else(|| "-".to_string(), format_size);
+        info.push(Line::from(vec![
+            Span::styled("Refs:         ", label),
+            Span::raw(bare.describe_refs()),
+            Span::styled("  (", label),
+            Span::raw(size),
+            Span::styled(")", label),
+        ]));
+        let remotes = if bare.remotes.is_empty() {
+            NO_REMOTE.to_string()
+        } else {
+            bare.remotes.join(", ")
+        };
+        info.push(Line::from(vec![
+            Span::styled("Remotes:      ", label),
+            Span::raw(remotes),
+            Span::styled(if bare.mirror { "  (mirror)" } else { "" }, label),
+        ]));
+    }
+    match &repo.manifest {
+        Some(ManifestMark::Declared(entry) | ManifestMark::Missing(entry)) => {
+            let groups = if entry.groups.is_empty() {
+                "-".to_string()
+            } else {
+                entry.groups.join(", ")
+            };
+            let branch = entry.branch.as_deref().unwrap_or(NO_BRANCH);
+            info.push(Line::from(vec![
+                Span::styled("Manifest:     ", label),
+                Span::raw(groups),
+                Span::styled("  (default branch ", label),
+                Span::raw(branch.to_string()),
+                Span::styled(")", label),
+            ]));
+        }
+        Some(ManifestMark::Undeclared) => info.push(Line::from(vec![
+            Span::styled("Manifest:     ", label),
+            Span::styled("not declared", theme().warning),
+        ])),
+        None => {}
+    }
+    if let Some(err) = &repo.error_message {
+        info.push(Line::from(Span::styled(format!("⚠ {err}"), theme().error)));
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(info.len() as u16 + 2),
+            Constraint::Min(3),
+        ])
+        .split(area);
+    let info_paragraph = Paragraph::new(info).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", repo.name)),
+    );
+    frame.render_widget(info_paragraph, chunks[0]);
+
+    let title = format!(" Changes ({}) ", repo.changes.len());
+    if repo.changes.is_empty() {
+        let message = if repo.is_bare() {
+            "Bare repository, no working tree"
+        } else if repo.is_placeholder() {
+            "Not cloned yet"
+        } else {
+            "Working tree clean"
+        };
+        let paragraph = Paragraph::new(message)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .style(theme().muted);
+        frame.render_widget(paragraph, chunks[1]);
+        return;
+    }
+
+    let items: Vec<ListItem> = repo
+        .changes
+        .iter()
+        .map(|(code, path)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{code:>2} "), change_style(code)),
+                Span::raw(path.clone()),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(theme().selection);
+    let mut state = ListState::default().with_selected(Some(view.selected));
+    frame.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn render_diff_view(frame: &mut Frame, area: Rect, view: &DiffView) {
+    let target = view.file.as_deref().unwrap_or("working tree");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Diff — {} │ {} ", view.repo_name, target));
+
+    let (lines, style) = if view.loading {
+        (vec![Line::from("Loading diff...")], theme().muted)
+    } else if let Some(err) = &view.error {
+        (vec![Line::from(format!("⚠ {err}"))], theme().error)
+    } else if view.lines.is_empty() {
+        (vec![Line::from("No changes")], theme().muted)
+    } else {
+        (view.lines.iter().map(diff_line).collect(), Style::default())
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(style)
+        .scroll((view.scroll.min(u16::MAX as usize) as u16, 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn diff_line(line: &DiffLine) -> Line<'static> {
+    let style = match line.kind {
+        DiffLineKind::Section => theme().marked.add_modifier(Modifier::BOLD),
+        DiffLineKind::FileHeader => Style::default().add_modifier(Modifier::BOLD),
+        DiffLineKind::Hunk => theme().accent,
+        DiffLineKind::Added => theme().success,
+        DiffLineKind::Removed => theme().error,
+        DiffLineKind::Context => Style::default(),
+    };
+    Line::from(Span::styled(line.text.clone(), style))
+}
+
+fn render_log_view(frame: &mut Frame, area: Rect, view: &LogView) {
+    let mut title = vec![Span::raw(format!(" Log — {} │ ", view.repo_name))];
+    for mode in [LogMode::History, LogMode::Incoming, LogMode::Outgoing] {
+        let style = if mode == view.mode {
+            Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            theme().muted
+        };
+        title.push(Span::styled(format!(" {} ", mode.label()), style));
+    }
+    if !view.loading && view.error.is_none() {
+        title.push(Span::raw(format!(" │ {} commits ", view.entries.len())));
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Line::from(title));
+
+    let (lines, style) = if view.loading {
+        (vec![Line::from("Loading commits...")], theme().muted)
+    } else if let Some(err) = &view.error {
+        (vec![Line::from(format!("⚠ {err}"))], theme().error)
+    } else if view.entries.is_empty() {
+        let message = match view.mode {
+            LogMode::History => "No commits",
+            LogMode::Incoming => "Nothing to pull - up to date with upstream",
+            LogMode::Outgoing => "Nothing to push - up to date with upstream",
+        };
+        (vec![Line::from(message)], theme().muted)
+    } else {
+        (
+            view.entries.iter().map(log_entry_line).collect(),
+            Style::default(),
+        )
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(style)
+        .scroll((view.scroll.min(u16::MAX as usize) as u16, 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn log_entry_line(entry: &LogEntry) -> Line<'static> {
+    let mut spans = vec![
+        Span::styled(entry.hash.clone(), theme().warning),
+        Span::raw(" "),
+    ];
+    if !entry.refs.is_empty() {
+        spans.push(Span::styled(format!("({}) ", entry.refs), theme().accent));
+    }
+    spans.push(Span::raw(entry.subject.clone()));
+    spans.push(Span::styled(format!("  {}", entry.author), theme().info));
+    spans.push(Span::styled(format!(", {}", entry.date), theme().muted));
+    Line::from(spans)
 }
 
 fn render_empty_state(frame: &mut Frame, area: Rect) {
@@ -271,11 +1107,11 @@ fn render_empty_state(frame: &mut Frame, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
         .title("Repositories")
-        .style(Style::default().fg(Color::DarkGray));
+        .style(theme().muted);
 
     let paragraph = Paragraph::new(empty_text.join("\n"))
         .block(block)
-        .style(Style::default().fg(Color::DarkGray))
+        .style(theme().muted)
         .alignment(Alignment::Center);
 
     frame.render_widget(paragraph, area);
@@ -305,7 +1141,7 @@ fn render_scroll_hints(frame: &mut Frame, area: Rect, total_rows: usize, state:
         return;
     }
 
-    let hint_style = Style::default().fg(Color::DarkGray);
+    let hint_style = theme().muted;
     let x = inner.x + inner.width.saturating_sub(1);
     let top_y = inner.y.saturating_add(1);
     let bottom_y = inner.y + inner.height.saturating_sub(1);
@@ -352,11 +1188,11 @@ fn render_no_results_state(frame: &mut Frame, area: Rect, query: &str) {
     let block = Block::default()
         .borders(Borders::ALL)
         .title("Repositories")
-        .style(Style::default().fg(Color::DarkGray));
+        .style(theme().muted);
 
     let paragraph = Paragraph::new(no_results_text.join("\n"))
         .block(block)
-        .style(Style::default().fg(Color::Yellow))
+        .style(theme().warning)
         .alignment(Alignment::Center);
 
     frame.render_widget(paragraph, area);
@@ -364,7 +1200,7 @@ fn render_no_results_state(frame: &mut Frame, area: Rect, query: &str) {
 
 fn colorize_change_summary(change_summary: &str) -> Line<'static> {
     if change_summary == NO_CHANGES || change_summary.is_empty() {
-        return Line::from(Span::styled("-", Style::default().fg(Color::DarkGray)));
+        return Line::from(Span::styled("-", theme().muted));
     }
 
     let mut spans = Vec::new();
@@ -374,17 +1210,7 @@ fn colorize_change_summary(change_summary: &str) -> Line<'static> {
         // Each part is like "M:3" or "D:1" or "??:2"
         if let Some(colon_pos) = part.find(':') {
             let change_type = &part[..colon_pos];
-            let color = match change_type {
-                "M" => Color::Yellow,  // Modified
-                "D" => Color::Red,     // Deleted
-                "A" => Color::Green,   // Added
-                "??" => Color::Cyan,   // Untracked
-                "R" => Color::Magenta, // Renamed
-                "C" => Color::Blue,    // Copied
-                _ => Color::White,     // Unknown
-            };
-
-            spans.push(Span::styled(part.to_string(), Style::default().fg(color)));
+            spans.push(Span::styled(part.to_string(), change_style(change_type)));
         } else {
             // Fallback for malformed parts
             spans.push(Span::raw(part.to_string()));
@@ -399,41 +1225,386 @@ fn colorize_change_summary(change_summary: &str) -> Line<'static> {
     Line::from(spans)
 }
 
-fn get_staleness_style(last_fetch: &str) -> Style {
-    // Parse the age from strings like "2d", "5h", "30m", etc.
-    if last_fetch == NO_LAST_FETCH {
-        return Style::default().fg(Color::DarkGray);
+fn change_style(change_type: &str) -> Style {
+    match change_type {
+        "M" => theme().warning, // Modified
+        "D" => theme().error,   // Deleted
+        "A" => theme().success, // Added
+        "??" => theme().accent, // Untracked
+        "R" => theme().marked,  // Renamed
+        "C" => theme().info,    // Copied
+        _ => Style::default(),  // Unknown
     }
+}
 
-    // Extract the numeric value and unit
-    let trimmed = last_fetch.trim();
-    if let Some(d_pos) = trimmed.find('d') {
-        // Days
-        if let Ok(days) = trimmed[..d_pos].parse::<u64>() {
-            return if days == 0 {
-                Style::default().fg(Color::Green)
-            } else if days < 7 {
-                Style::default()
-            } else if days < 30 {
-                Style::default().fg(Color::Yellow)
+fn get_staleness_style(last_fetch: Option<SystemTime>) -> Style {
+    let Some(time) = last_fetch else {
+        return theme().muted;
+    };
+    let days = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs()
+        / 86_400;
+    if days == 0 {
+        theme().success
+    } else if days < 7 {
+        Style::default()
+    } else if days < 30 {
+        theme().warning
+    } else {
+        theme().error
+    }
+}
+
+fn last_fetch_label(last_fetch: Option<SystemTime>) -> String {
+    last_fetch.map_or_else(|| NO_LAST_FETCH.to_string(), format_time_age)
+}
+
+fn render_palette(frame: &mut Frame, app: &App) {
+    let Some(palette) = &app.palette else {
+        return;
+    };
+    let entries = app.palette_entries();
+    let area = frame.area();
+    let popup_width = 80.min(area.width.saturating_sub(4));
+    let popup_height = (entries.len() as u16 + 3)
+        .clamp(4, 20)
+        .min(area.height.saturating_sub(4));
+    let popup_area = Rect {
+        x: area.width.saturating_sub(popup_width) / 2,
+        y: area.height.saturating_sub(popup_height) / 4,
+        width: popup_width,
+        height: popup_height,
+    };
+    let block = Block::default()
+        .title(" Command Palette ")
+        .borders(Borders::ALL)
+        .border_style(theme().border)
+        .style(theme().popup);
+    let inner = block.inner(popup_area);
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(block, popup_area);
+
+    let [input_area, list_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+    let input = Paragraph::new(format!("> {}_", palette.query)).style(theme().warning);
+    frame.render_widget(input, input_area);
+
+    if entries.is_empty() {
+        let empty = Paragraph::new("  No matching commands").style(theme().muted);
+        frame.render_widget(empty, list_area);
+        return;
+    }
+    let keys_width = entries
+        .iter()
+        .map(|entry| entry.keys.chars().count())
+        .max()
+        .unwrap_or(0);
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|entry| {
+            let description = entry.command.description();
+            let mut used = 2 + description.chars().count();
+            if !entry.scope.is_empty() {
+                used += 3 + entry.scope.chars().count();
+            }
+            let padding = usize::from(inner.width).saturating_sub(used + keys_width + 1);
+            let mut spans = vec![Span::raw(format!("  {description}"))];
+            if !entry.scope.is_empty() {
+                spans.push(Span::styled(format!(" · {}", entry.scope), theme().marked));
+            }
+            spans.push(Span::raw(" ".repeat(padding)));
+            spans.push(Span::styled(
+                format!("{:>keys_width$}", entry.keys),
+                theme().muted,
+            ));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items).highlight_style(theme().selection);
+    let mut state = ListState::default().with_selected(Some(palette.selected));
+    frame.render_stateful_widget(list, list_area, &mut state);
+}
+
+fn render_column_picker(frame: &mut Frame, picker: &ColumnPicker) {
+    let area = frame.area();
+    let popup_width = 44.min(area.width.saturating_sub(4));
+    let popup_height = (picker.entries.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let popup_area = Rect {
+        x: area.width.saturating_sub(popup_width) / 2,
+        y: area.height.saturating_sub(popup_height) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+    let items: Vec<ListItem> = picker
+        .entries
+        .iter()
+        .map(|(spec, shown)| {
+            let (check, style) = if *shown {
+                ("[x]", Style::default())
             } else {
-                Style::default().fg(Color::Red)
+                ("[ ]", theme().muted)
             };
+            let width = match spec.width {
+                Some(width) => format!("width {width}"),
+                None => "auto".to_string(),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {check} {:<20}", spec.column.title()), style),
+                Span::styled(width, theme().muted),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(" Columns ")
+                .borders(Borders::ALL)
+                .border_style(theme().border),
+        )
+        .style(theme().popup)
+        .highlight_style(theme().selection);
+
+    frame.render_widget(Clear, popup_area);
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    frame.render_stateful_widget(list, popup_area, &mut state);
+}
+
+/// Draw the open menu and return its area.
+fn render_menu(frame: &mut Frame, app: &App) -> Option<Rect> {
+    let menu = app.menu.as_ref()?;
+    let area = frame.area();
+    let name = app.focused_repo().map_or("", |repo| repo.name.as_str());
+    let (title, width) = match menu.kind {
+        MenuKind::Launch => (format!(" Open {name} with "), 50),
+        MenuKind::Browse(_) => (format!(" Browse {name} "), 100),
+        MenuKind::Copy(_) => (format!(" Copy from {name} "), 100),
+    };
+    let items = app.menu_items();
+    let popup_width = width.min(area.width.saturating_sub(4));
+    let popup_height = (items.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let popup_area = Rect {
+        x: area.width.saturating_sub(popup_width) / 2,
+        y: area.height.saturating_sub(popup_height) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    let items: Vec<ListItem> = items
+        .into_iter()
+        .map(|(key, label, detail)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {key}  "), theme().warning),
+                Span::raw(format!("{label:<30}")),
+                Span::styled(detail.replace('\n', " "), theme().muted),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(theme().border),
+        )
+        .style(theme().popup)
+        .highlight_style(theme().selection);
+
+    frame.render_widget(Clear, popup_area);
+    let mut state = ListState::default().with_selected(Some(menu.selected));
+    frame.render_stateful_widget(list, popup_area, &mut state);
+    Some(popup_area)
+}
+
+fn footer_hints(app: &App) -> Vec<Hint> {
+    let scroll = Hint::Pair(Command::Down, Command::Up, "scroll");
+    let top_bottom = Hint::Pair(Command::First, Command::Last, "top/bottom");
+    match app.panes.last() {
+        Some(Pane::Log(_)) => vec![
+            Hint::Key(Command::Close, "close"),
+            Hint::Key(Command::CycleLogMode, "mode"),
+            scroll,
+            top_bottom,
+            Hint::Key(Command::Help, "help"),
+        ],
+        Some(Pane::Detail(_)) => vec![
+            Hint::Key(Command::Close, "close"),
+            Hint::Key(Command::DiffFile, "diff file"),
+            Hint::Key(Command::DiffAll, "diff all"),
+            Hint::Key(Command::Log, "log"),
+            Hint::Key(Command::Branches, "branches"),
+            Hint::Key(Command::Help, "help"),
+        ],
+        Some(Pane::Diff(_)) => vec![
+            Hint::Key(Command::Close, "close"),
+            Hint::Pair(Command::NextSection, Command::PrevSection, "next/prev hunk"),
+            scroll,
+            top_bottom,
+            Hint::Key(Command::Help, "help"),
+        ],
+        Some(Pane::Branches(_)) => vec![
+            Hint::Key(Command::Close, "close"),
+            Hint::Key(Command::Checkout, "checkout"),
+            Hint::Key(Command::DeleteBranch, "delete merged"),
+            Hint::Pair(Command::Down, Command::Up, "select"),
+            Hint::Key(Command::Help, "help"),
+        ],
+        Some(Pane::Batch(view)) if view.stage == BatchStage::Review => vec![
+            Hint::Key(Command::CheckoutPresent, "checkout where present"),
+            Hint::Key(Command::CheckoutCreate, "checkout, creating missing"),
+            Hint::Key(Command::Close, "cancel"),
+        ],
+        Some(Pane::Batch(_)) => vec![
+            Hint::Key(Command::Close, "close"),
+            scroll,
+            Hint::Key(Command::Help, "help"),
+        ],
+        Some(Pane::Exec(view)) if !view.done => vec![
+            Hint::Key(Command::CancelExec, "cancel"),
+            Hint::Key(Command::Close, "close"),
+            Hint::Pair(Command::NextSection, Command::PrevSection, "next/prev repo"),
+            scroll,
+            top_bottom,
+            Hint::Key(Command::Help, "help"),
+        ],
+        Some(Pane::Exec(_)) => vec![
+            Hint::Key(Command::Close, "close"),
+            Hint::Pair(Command::NextSection, Command::PrevSection, "next/prev repo"),
+            scroll,
+            top_bottom,
+            Hint::Key(Command::Help, "help"),
+        ],
+        Some(Pane::Prune(_)) => vec![
+            Hint::Key(Command::Close, "close"),
+            Hint::Key(Command::ToggleSelection, "toggle"),
+            Hint::Key(Command::ToggleAll, "toggle all"),
+            Hint::Key(Command::DeleteSelected, "delete selected"),
+            Hint::Pair(Command::Down, Command::Up, "move"),
+            Hint::Key(Command::Help, "help"),
+        ],
+        None => vec![
+            Hint::Key(Command::Quit, "quit"),
+            Hint::Key(Command::Details, "details"),
+            Hint::Key(Command::Refresh, "refresh"),
+            Hint::Key(Command::Pull, "pull"),
+            Hint::Key(Command::Push, "push"),
+            Hint::Key(Command::Log, "log"),
+            Hint::Key(Command::Branches, "branches"),
+            Hint::Key(Command::Open, "open"),
+            Hint::Key(Command::Browse, "web"),
+            Hint::Key(Command::Copy, "copy"),
+            Hint::Key(Command::Mark, "mark"),
+            Hint::Key(Command::BatchCheckout, "batch checkout"),
+            Hint::Key(Command::CycleSort, "sort"),
+            Hint::Key(Command::Search, "search"),
+            Hint::Key(Command::Help, "help"),
+        ],
+    }
+}
+
+/// Footer title built from the current key bindings, with the screen cells
+/// of each single-command hint so clicks can run it.
+fn keymap_hints(app: &App, footer: Rect) -> (String, Vec<(Rect, Command)>) {
+    let context = app.key_context();
+    let first_key = |command| app.keymap.keys_for(context, command).into_iter().next();
+    let mut parts = Vec::new();
+    let mut areas = Vec::new();
+    let mut x = footer.x + 1;
+    for hint in footer_hints(app) {
+        let (text, command) = match hint {
+            Hint::Key(command, label) => match first_key(command) {
+                Some(key) => (format!("{key} {label}"), Some(command)),
+                None => continue,
+            },
+            Hint::Pair(first, second, label) => match (first_key(first), first_key(second)) {
+                (Some(a), Some(b)) => (format!("{a}/{b} {label}"), None),
+                _ => continue,
+            },
+        };
+        let width = text.chars().count() as u16;
+        if let Some(command) = command {
+            let area = Rect::new(x, footer.y, width, 1).intersection(footer);
+            areas.push((area, command));
         }
-    } else if trimmed.ends_with('h') || trimmed.ends_with('m') || trimmed.ends_with('s') {
-        // Hours, minutes, or seconds - all less than a day
-        return Style::default().fg(Color::Green);
+        x = x.saturating_add(width + 3);
+        parts.push(text);
     }
+    (parts.join(" | "), areas)
+}
 
-    Style::default()
+fn help_lines(keymap: &Keymap) -> Vec<String> {
+    let line = |context, command: Command| {
+        let keys = keymap.keys_for(context, command);
+        (!keys.is_empty()).then(|| format!("  {:<14} {}", keys.join(" / "), command.description()))
+    };
+    let mut lines = Vec::new();
+    let mut push_section = |title: &str, entries: Vec<String>| {
+        if entries.is_empty() {
+            return;
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(title.to_string());
+        lines.extend(entries);
+    };
+
+    let mut listed = vec![Command::Quit];
+    for (title, context, commands) in HELP_SECTIONS {
+        let entries = commands
+            .iter()
+            .filter_map(|command| line(*context, *command))
+            .collect();
+        if *context == KeyContext::Table {
+            listed.extend(commands.iter());
+        }
+        push_section(title, entries);
+    }
+    for (title, context) in HELP_PANES {
+        let entries = keymap
+            .commands(*context)
+            .into_iter()
+            .filter_map(|command| line(*context, command))
+            .collect();
+        push_section(title, entries);
+    }
+    let extra = keymap
+        .commands(KeyContext::Table)
+        .into_iter()
+        .filter(|command| !listed.contains(command))
+        .filter_map(|command| line(KeyContext::Table, command))
+        .collect();
+    push_section("MORE", extra);
+
+    let other = line(KeyContext::Table, Command::Quit).into_iter().collect();
+    push_section("OTHER", other);
+    lines
 }
 
-fn render_help_overlay(frame: &mut Frame) {
+fn render_help_overlay(frame: &mut Frame, keymap: &Keymap) {
     let area = frame.area();
+    let lines = help_lines(keymap);
+
+    // Split into two columns at a section break when one would not fit
+    let max_height = area.height.saturating_sub(4);
+    let columns = if lines.len() as u16 + 2 > max_height && area.width >= 124 {
+        let split = lines
+            .iter()
+            .enumerate()
+            .skip(lines.len() / 2)
+            .find(|(_, line)| line.is_empty())
+            .map_or(lines.len() / 2, |(idx, _)| idx);
+        let (left, right) = lines.split_at(split);
+        vec![left.to_vec(), right.iter().skip(1).cloned().collect()]
+    } else {
+        vec![lines]
+    };
+    let rows = columns.iter().map(Vec::len).max().unwrap_or(0);
 
     // Create centered popup area
-    let popup_width = 60.min(area.width.saturating_sub(4));
-    let popup_height = 20.min(area.height.saturating_sub(4));
+    let popup_width = (60 * columns.len() as u16).min(area.width.saturating_sub(4));
+    let popup_height = (rows as u16 + 2).min(max_height);
     let popup_x = (area.width.saturating_sub(popup_width)) / 2;
     let popup_y = (area.height.saturating_sub(popup_height)) / 2;
 
@@ -444,17 +1615,23 @@ fn render_help_overlay(frame: &mut Frame) {
         height: popup_height,
     };
 
-    let help_paragraph = Paragraph::new(HELP_TEXT.join("\n"))
-        .block(
-            Block::default()
-                .title(" Help (press any key to close) ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
-        )
-        .style(Style::default().bg(Color::Black))
-        .wrap(Wrap { trim: false });
+    let block = Block::default()
+        .title(" Help (press any key to close) ")
+        .borders(Borders::ALL)
+        .border_style(theme().border)
+        .style(theme().popup);
+    let inner = block.inner(popup_area);
 
     // Clear the area behind the popup
     frame.render_widget(Clear, popup_area);
-    frame.render_widget(help_paragraph, popup_area);
+    frame.render_widget(block, popup_area);
+    let column_areas = Layout::horizontal(vec![
+        Constraint::Ratio(1, columns.len() as u32);
+        columns.len()
+    ])
+    .split(inner);
+    for (column, column_area) in columns.iter().zip(column_areas.iter()) {
+        let help_paragraph = Paragraph::new(column.join("\n")).wrap(Wrap { trim: false });
+        frame.render_widget(help_paragraph, *column_area);
+    }
 }
@@ -1,30 +1,180 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
 use std::thread;
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
 
-use crate::discovery::{discover_repos_with_progress, RepoRef};
-use crate::git::{git_pull, git_push};
+use crate::backend::backend;
+use crate::bare::{git_bare_status, git_update_mirror};
+use crate::branches::{plan_checkout, prune_candidates, BranchInfo, CheckoutPlan, PruneCandidate};
+use crate::cache::{
+    cache_path, git_dir_stamps, save_repo_list, CacheEntry, StatusCache, REPO_LIST_FILE,
+};
+use crate::diff::{git_diff, DiffLine};
+use crate::discovery::{discover_targets, repo_at, DiscoveryOptions, RepoRef, ScanTargets};
+use crate::exec::{run_in_repos, ExecCommand, ExecOptions, ExecOutcome};
+use crate::git::{git_checkout, git_delete_branch, git_prune_branches};
+use crate::history::{git_log, LogEntry, LogMode};
 use crate::logger::log_debug;
-use crate::status::{error_repo_state, git_status, RepoState};
+use crate::manifest::{git_clone, ManifestRepo};
+use crate::status::{error_repo_state, RepoState, StatusExtras};
+use crate::submodule::Submodule;
+use crate::worktree::{git_worktree_add, git_worktree_prune, linked_worktree_refs};
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum Action {
+    Fetch,
     Pull,
     Push,
+    Checkout {
+        branch: String,
+        create: bool,
+    },
+    DeleteBranch(String),
+    PruneBranches(Vec<String>),
+    /// Check out the branch in a new linked worktree.
+    AddWorktree(String),
+    PruneWorktrees,
+    /// Fetch every ref of a bare repository from its remotes.
+    UpdateMirror,
+}
+
+impl Action {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Fetch => "Fetch",
+            Action::Pull => "Pull",
+            Action::Push => "Push",
+            Action::Checkout { create: false, .. } => "Checkout",
+            Action::Checkout { create: true, .. } => "Checkout/create",
+            Action::DeleteBranch(_) => "Delete branch",
+            Action::PruneBranches(_) => "Prune",
+            Action::AddWorktree(_) => "Add worktree",
+            Action::PruneWorktrees => "Prune worktrees",
+            Action::UpdateMirror => "Update mirror",
+        }
+    }
+
+    /// Label including the action's target, used in confirmation prompts.
+    pub fn describe(&self) -> String {
+        match self {
+            Action::Fetch
+            | Action::Pull
+            | Action::Push
+            | Action::PruneWorktrees
+            | Action::UpdateMirror => self.label().to_string(),
+            Action::Checkout { branch, .. }
+            | Action::DeleteBranch(branch)
+            | Action::AddWorktree(branch) => {
+                format!("{} {branch}", self.label())
+            }
+            Action::PruneBranches(branches) => {
+                format!("{} {} branches", self.label(), branches.len())
+            }
+        }
+    }
+
+    /// Whether the action talks to the remote and needs one configured.
+    pub fn needs_remote(&self) -> bool {
+        matches!(
+            self,
+            Action::Fetch | Action::Pull | Action::Push | Action::UpdateMirror
+        )
+    }
+
+    /// Whether the action changes the working tree, which bare repositories lack.
+    pub fn needs_worktree(&self) -> bool {
+        matches!(self, Action::Pull | Action::Checkout { .. })
+    }
+
+    /// Whether the action adds or removes worktrees, so the list needs reloading.
+    pub fn changes_worktrees(&self) -> bool {
+        matches!(self, Action::AddWorktree(_) | Action::PruneWorktrees)
+    }
 }
 
 pub enum WorkerCmd {
-    Scan { root: PathBuf },
-    Refresh { repos: Vec<RepoRef> },
-    Action { path: PathBuf, action: Action },
+    /// Discover and read the repositories of `targets`. With `known`
+    /// repositories from an earlier run, their status is read first and
+    /// discovery continues in the background.
+    Scan {
+        targets: ScanTargets,
+        known: Vec<RepoRef>,
+    },
+    /// Read the status of repositories that are not in the table yet.
+    Status {
+        repos: Vec<RepoRef>,
+    },
+    /// List the linked worktrees of `main` and read their status.
+    Worktrees {
+        main: PathBuf,
+    },
+    Refresh {
+        repos: Vec<RepoRef>,
+        /// Read every repository again instead of trusting the status cache.
+        skip_cache: bool,
+    },
+    Action {
+        path: PathBuf,
+        action: Action,
+    },
+    /// Run actions across many repositories, reporting per-repo results.
+    BatchAction {
+        jobs: Vec<(PathBuf, Action)>,
+    },
+    CheckoutPreflight {
+        paths: Vec<PathBuf>,
+        branch: String,
+    },
+    /// Run an arbitrary command in each repository on a thread of its own;
+    /// setting `cancel` stops it.
+    Exec {
+        paths: Vec<PathBuf>,
+        command: ExecCommand,
+        options: ExecOptions,
+        cancel: Arc<AtomicBool>,
+    },
+    /// Collect prune candidates across repositories.
+    PruneScan {
+        paths: Vec<PathBuf>,
+    },
+    Log {
+        path: PathBuf,
+        mode: LogMode,
+    },
+    Diff {
+        path: PathBuf,
+        file: Option<String>,
+        untracked: bool,
+    },
+    Branches {
+        path: PathBuf,
+    },
+    /// Clone repositories declared in the manifest and read their status.
+    Clone {
+        repos: Vec<ManifestRepo>,
+    },
+    /// Read these costly status fields from now on, as the visible
+    /// columns changed.
+    SetStatusExtras(StatusExtras),
     Quit,
 }
 
+/// Default branch and prune candidates of one repository.
+pub type PruneScanResult = Result<(Option<String>, Vec<PruneCandidate>), String>;
+
 pub enum WorkerEvent {
     ScanComplete(Vec<RepoState>),
+    /// Status of the known repositories; background discovery continues.
+    KnownReposLoaded(Vec<RepoState>),
+    /// Background discovery finished with the current set of repositories.
+    DiscoveryComplete(Vec<RepoRef>),
+    StatusLoaded(Vec<RepoState>),
+    WorktreesLoaded {
+        main: PathBuf,
+        result: Result<Vec<RepoState>, String>,
+    },
     RefreshComplete(Vec<RepoState>),
     ScanProgress {
         ratio: f64,
@@ -34,6 +184,48 @@ pub enum WorkerEvent {
         action: Action,
         result: Result<String, String>,
     },
+    BatchProgress {
+        done: usize,
+        total: usize,
+    },
+    BatchResult {
+        results: Vec<(PathBuf, Action, Result<String, String>)>,
+    },
+    CheckoutPreflight {
+        branch: String,
+        plans: Vec<(PathBuf, CheckoutPlan)>,
+    },
+    PruneCandidates {
+        results: Vec<(PathBuf, PruneScanResult)>,
+    },
+    ExecOutput {
+        path: PathBuf,
+        outcome: ExecOutcome,
+    },
+    ExecComplete,
+    LogLoaded {
+        path: PathBuf,
+        mode: LogMode,
+        result: Result<Vec<LogEntry>, String>,
+    },
+    DiffLoaded {
+        path: PathBuf,
+        file: Option<String>,
+        result: Result<Vec<DiffLine>, String>,
+    },
+    BranchesLoaded {
+        path: PathBuf,
+        result: Result<Vec<BranchInfo>, String>,
+    },
+    CloneProgress {
+        done: usize,
+        total: usize,
+    },
+    /// Per-repository clone results and the status of the cloned ones.
+    CloneComplete {
+        results: Vec<(PathBuf, Result<String, String>)>,
+        states: Vec<RepoState>,
+    },
 }
 
 // Progress is split into discovery (40%) and status (60%) phases.
@@ -43,39 +235,29 @@ const STATUS_PROGRESS_WEIGHT: f64 = 0.6;
 pub fn spawn_worker(
     cmd_rx: Receiver<WorkerCmd>,
     evt_tx: Sender<WorkerEvent>,
+    mut cache: StatusCache,
+    discovery: DiscoveryOptions,
+    mut extras: StatusExtras,
 ) -> thread::JoinHandle<()> {
     thread::spawn(move || {
         'worker_loop: while let Ok(cmd) = cmd_rx.recv() {
             match cmd {
-                WorkerCmd::Scan { root } => {
-                    log_debug(&format!("Scan start root={}", root.display()));
+                WorkerCmd::Scan { targets, known } if known.is_empty() => {
+                    log_debug(&format!(
+                        "Scan start roots={} repos={}",
+                        targets.roots.len(),
+                        targets.repos.len()
+                    ));
                     let scan_start = Instant::now();
-                    let mut total_estimate = 0usize;
-                    let stop = Arc::new(AtomicBool::new(false));
-                    let stop_flag = Arc::clone(&stop);
-                    let repos = discover_repos_with_progress(&root, |visited, remaining| {
-                        if stop_flag.load(Ordering::Relaxed) {
-                            return false;
-                        }
-                        total_estimate = total_estimate.max(visited + remaining);
-                        if total_estimate == 0 {
-                            return true;
-                        }
-                        let ratio = visited as f64 / total_estimate as f64;
-                        let scaled =
-                            (ratio * DISCOVERY_PROGRESS_WEIGHT).min(DISCOVERY_PROGRESS_WEIGHT);
-                        if evt_tx
-                            .send(WorkerEvent::ScanProgress { ratio: scaled })
-                            .is_err()
-                        {
-                            stop_flag.store(true, Ordering::Relaxed);
-                            return false;
-                        }
-                        true
-                    });
-                    if stop.load(Ordering::Relaxed) {
+                    let Some(repos) = discover(
+                        &targets,
+                        &discovery,
+                        &evt_tx,
+                        0.0,
+                        DISCOVERY_PROGRESS_WEIGHT,
+                    ) else {
                         break 'worker_loop;
-                    }
+                    };
                     log_debug(&format!(
                         "Discovery complete repos={} elapsed_ms={}",
                         repos.len(),
@@ -83,7 +265,13 @@ pub fn spawn_worker(
                     ));
 
                     // Parallelize status fetching
-                    let (states, channel_closed) = fetch_status_parallel(repos, &evt_tx);
+                    let (states, channel_closed) = fetch_status_parallel(
+                        repos,
+                        &evt_tx,
+                        &mut cache,
+                        extras,
+                        (DISCOVERY_PROGRESS_WEIGHT, STATUS_PROGRESS_WEIGHT),
+                    );
                     if channel_closed {
                         break 'worker_loop;
                     }
@@ -96,9 +284,92 @@ pub fn spawn_worker(
                         scan_start.elapsed().as_millis()
                     ));
                 }
-                WorkerCmd::Refresh { repos } => {
+                WorkerCmd::Scan { targets, known } => {
+                    log_debug(&format!(
+                        "Scan start roots={} repos={} known={}",
+                        targets.roots.len(),
+                        targets.repos.len(),
+                        known.len()
+                    ));
+                    // Known repositories first, then a walk for added and
+                    // removed ones that leaves the worker free for other commands
+                    let (states, channel_closed) = fetch_status_parallel(
+                        known,
+                        &evt_tx,
+                        &mut cache,
+                        extras,
+                        (0.0, STATUS_PROGRESS_WEIGHT),
+                    );
+                    if channel_closed || evt_tx.send(WorkerEvent::KnownReposLoaded(states)).is_err()
+                    {
+                        break 'worker_loop;
+                    }
+                    let evt_tx = evt_tx.clone();
+                    let discovery = discovery.clone();
+                    thread::spawn(move || {
+                        let scan_start = Instant::now();
+                        let Some(repos) = discover(
+                            &targets,
+                            &discovery,
+                            &evt_tx,
+                            STATUS_PROGRESS_WEIGHT,
+                            DISCOVERY_PROGRESS_WEIGHT,
+                        ) else {
+                            return;
+                        };
+                        log_debug(&format!(
+                            "Background discovery complete repos={} elapsed_ms={}",
+                            repos.len(),
+                            scan_start.elapsed().as_millis()
+                        ));
+                        let _ = evt_tx.send(WorkerEvent::DiscoveryComplete(repos));
+                    });
+                }
+                WorkerCmd::Status { repos } => {
+                    let (states, channel_closed) =
+                        fetch_status_parallel(repos, &evt_tx, &mut cache, extras, (1.0, 0.0));
+                    if channel_closed || evt_tx.send(WorkerEvent::StatusLoaded(states)).is_err() {
+                        break 'worker_loop;
+                    }
+                }
+                WorkerCmd::Worktrees { main } => {
+                    let result = match linked_worktree_refs(&main) {
+                        Ok(repos) => {
+                            let (states, channel_closed) = fetch_status_parallel(
+                                repos,
+                                &evt_tx,
+                                &mut cache,
+                                extras,
+                                (1.0, 0.0),
+                            );
+                            if channel_closed {
+                                break 'worker_loop;
+                            }
+                            Ok(states)
+                        }
+                        Err(err) => Err(err),
+                    };
+                    if evt_tx
+                        .send(WorkerEvent::WorktreesLoaded { main, result })
+                        .is_err()
+                    {
+                        break 'worker_loop;
+                    }
+                }
+                WorkerCmd::Refresh { repos, skip_cache } => {
+                    if skip_cache {
+                        for repo in &repos {
+                            cache.remove(&repo.git_dir);
+                        }
+                    }
                     // Parallelize refresh as well
-                    let (refreshed, channel_closed) = fetch_status_parallel(repos, &evt_tx);
+                    let (refreshed, channel_closed) = fetch_status_parallel(
+                        repos,
+                        &evt_tx,
+                        &mut cache,
+                        extras,
+                        (DISCOVERY_PROGRESS_WEIGHT, STATUS_PROGRESS_WEIGHT),
+                    );
                     if channel_closed {
                         break 'worker_loop;
                     }
@@ -110,10 +381,7 @@ pub fn spawn_worker(
                     }
                 }
                 WorkerCmd::Action { path, action } => {
-                    let result = match action {
-                        Action::Pull => git_pull(&path),
-                        Action::Push => git_push(&path),
-                    };
+                    let result = run_action(&path, &action);
                     if evt_tx
                         .send(WorkerEvent::ActionResult {
                             path,
@@ -125,28 +393,258 @@ pub fn spawn_worker(
                         break 'worker_loop;
                     }
                 }
+                WorkerCmd::BatchAction { jobs } => {
+                    let total = jobs.len();
+                    let (results, channel_closed) = run_parallel(
+                        jobs,
+                        default_worker_count(),
+                        |(path, action)| {
+                            let result = run_action(&path, &action);
+                            (path, action, result)
+                        },
+                        |done| {
+                            evt_tx
+                                .send(WorkerEvent::BatchProgress { done, total })
+                                .is_ok()
+                        },
+                    );
+                    if channel_closed || evt_tx.send(WorkerEvent::BatchResult { results }).is_err()
+                    {
+                        break 'worker_loop;
+                    }
+                }
+                WorkerCmd::CheckoutPreflight { paths, branch } => {
+                    let (plans, channel_closed) = run_parallel(
+                        paths,
+                        default_worker_count(),
+                        |path| {
+                            let plan = plan_checkout(&path, &branch);
+                            (path, plan)
+                        },
+                        |_| true,
+                    );
+                    if channel_closed
+                        || evt_tx
+                            .send(WorkerEvent::CheckoutPreflight { branch, plans })
+                            .is_err()
+                    {
+                        break 'worker_loop;
+                    }
+                }
+                WorkerCmd::Exec {
+                    paths,
+                    command,
+                    options,
+                    cancel,
+                } => {
+                    // Commands may run for minutes, so keep the worker free
+                    let evt_tx = evt_tx.clone();
+                    thread::spawn(move || {
+                        let (_, channel_closed) = run_in_repos(
+                            paths,
+                            &command,
+                            default_worker_count(),
+                            &options,
+                            &cancel,
+                            |path, outcome| {
+                                evt_tx
+                                    .send(WorkerEvent::ExecOutput {
+                                        path: path.to_path_buf(),
+                                        outcome: outcome.clone(),
+                                    })
+                                    .is_ok()
+                            },
+                        );
+                        if !channel_closed {
+                            let _ = evt_tx.send(WorkerEvent::ExecComplete);
+                        }
+                    });
+                }
+                WorkerCmd::PruneScan { paths } => {
+                    let (results, channel_closed) = run_parallel(
+                        paths,
+                        default_worker_count(),
+                        |path| {
+                            let result = prune_candidates(&path);
+                            (path, result)
+                        },
+                        |_| true,
+                    );
+                    if channel_closed
+                        || evt_tx
+                            .send(WorkerEvent::PruneCandidates { results })
+                            .is_err()
+                    {
+                        break 'worker_loop;
+                    }
+                }
+                WorkerCmd::Log { path, mode } => {
+                    let result = git_log(&path, mode);
+                    if evt_tx
+                        .send(WorkerEvent::LogLoaded { path, mode, result })
+                        .is_err()
+                    {
+                        break 'worker_loop;
+                    }
+                }
+                WorkerCmd::Diff {
+                    path,
+                    file,
+                    untracked,
+                } => {
+                    let result = git_diff(&path, file.as_deref(), untracked);
+                    if evt_tx
+                        .send(WorkerEvent::DiffLoaded { path, file, result })
+                        .is_err()
+                    {
+                        break 'worker_loop;
+                    }
+                }
+                WorkerCmd::Branches { path } => {
+                    let result = backend().branches(&path);
+                    if evt_tx
+                        .send(WorkerEvent::BranchesLoaded { path, result })
+                        .is_err()
+                    {
+                        break 'worker_loop;
+                    }
+                }
+                WorkerCmd::Clone { repos } => {
+                    // Clones can take minutes; the worker keeps serving meanwhile
+                    let evt_tx = evt_tx.clone();
+                    thread::spawn(move || clone_repos(repos, extras, &evt_tx));
+                }
+                WorkerCmd::SetStatusExtras(wanted) => extras = wanted,
                 WorkerCmd::Quit => break 'worker_loop,
             }
         }
     })
 }
 
+/// Walk the roots of `targets` one after another, reporting progress scaled
+/// into `start..start + weight`, and remember each root's repositories.
+/// Returns `None` when the UI has gone away.
+fn discover(
+    targets: &ScanTargets,
+    options: &DiscoveryOptions,
+    evt_tx: &Sender<WorkerEvent>,
+    start: f64,
+    weight: f64,
+) -> Option<Vec<RepoRef>> {
+    let root_count = targets.roots.len().max(1) as f64;
+    let mut current_root = 0;
+    let mut total_estimate = 0usize;
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_flag = Arc::clone(&stop);
+    let (per_root, repos) = discover_targets(targets, options, |index, visited, remaining| {
+        if stop_flag.load(Ordering::Relaxed) {
+            return false;
+        }
+        if index != current_root {
+            current_root = index;
+            total_estimate = 0;
+        }
+        total_estimate = total_estimate.max(visited + remaining);
+        if total_estimate == 0 {
+            return true;
+        }
+        let ratio = (index as f64 + visited as f64 / total_estimate as f64) / root_count;
+        let scaled = start + (ratio * weight).min(weight);
+        if evt_tx
+            .send(WorkerEvent::ScanProgress { ratio: scaled })
+            .is_err()
+        {
+            stop_flag.store(true, Ordering::Relaxed);
+            return false;
+        }
+        true
+    });
+    if stop.load(Ordering::Relaxed) {
+        return None;
+    }
+    for (root, found) in targets.roots.iter().zip(&per_root) {
+        save_discovered(root, found);
+    }
+    Some(repos)
+}
+
+fn save_discovered(root: &Path, repos: &[RepoRef]) {
+    if let Err(err) = save_repo_list(cache_path(REPO_LIST_FILE).as_deref(), root, repos) {
+        log_debug(&format!("Repository list not saved: {err}"));
+    }
+}
+
+/// Clone `repos` in parallel and read the status of each one cloned. Fresh
+/// clones have nothing cached, so the status cache is not consulted.
+fn clone_repos(repos: Vec<ManifestRepo>, extras: StatusExtras, evt_tx: &Sender<WorkerEvent>) {
+    let total = repos.len();
+    let (cloned, channel_closed) = run_parallel(
+        repos,
+        default_worker_count(),
+        |repo| {
+            let result = git_clone(&repo);
+            let state = result
+                .is_ok()
+                .then(|| repo_at(&repo.path))
+                .flatten()
+                .map(|cloned| {
+                    repo_status(&cloned, extras)
+                        .unwrap_or_else(|err| error_repo_state(&cloned, &err, extras))
+                });
+            (repo.path, result, state)
+        },
+        |done| {
+            evt_tx
+                .send(WorkerEvent::CloneProgress { done, total })
+                .is_ok()
+        },
+    );
+    if channel_closed {
+        return;
+    }
+    let mut results = Vec::with_capacity(cloned.len());
+    let mut states = Vec::new();
+    for (path, result, state) in cloned {
+        results.push((path, result));
+        states.extend(state);
+    }
+    let _ = evt_tx.send(WorkerEvent::CloneComplete { results, states });
+}
+
+fn run_action(path: &Path, action: &Action) -> Result<String, String> {
+    match action {
+        Action::Fetch => backend().fetch(path),
+        Action::Pull => backend().pull(path),
+        Action::Push => backend().push(path),
+        Action::Checkout { branch, create } => git_checkout(path, branch, *create),
+        Action::DeleteBranch(branch) => git_delete_branch(path, branch),
+        Action::PruneBranches(branches) => git_prune_branches(path, branches),
+        Action::AddWorktree(branch) => git_worktree_add(path, branch),
+        Action::PruneWorktrees => git_worktree_prune(path),
+        Action::UpdateMirror => git_update_mirror(path),
+    }
+}
+
+/// Status of a repository from the backend, or of a bare repository.
+pub fn repo_status(repo: &RepoRef, extras: StatusExtras) -> Result<RepoState, String> {
+    if repo.is_bare() {
+        git_bare_status(&repo.path, extras)
+    } else {
+        backend().status(&repo.path, &repo.git_dir, extras)
+    }
+}
+
+/// Read the status of every repository, reusing cached results for
+/// repositories that did not change, and update the cache.
 fn fetch_status_parallel(
     repos: Vec<RepoRef>,
     evt_tx: &Sender<WorkerEvent>,
+    cache: &mut StatusCache,
+    extras: StatusExtras,
+    (progress_start, progress_weight): (f64, f64),
 ) -> (Vec<RepoState>, bool) {
-    use std::sync::mpsc::channel;
-    use std::sync::{Arc, Mutex};
-
     let total_repos = repos.len().max(1);
-    let states = Arc::new(Mutex::new(Vec::with_capacity(repos.len())));
-    let completed = Arc::new(Mutex::new(0usize));
-    let stop = Arc::new(AtomicBool::new(false));
-
-    // Determine worker count: use available parallelism, cap at 16 to avoid overwhelming the system
-    let worker_count = thread::available_parallelism()
-        .map(|n| n.get().min(16))
-        .unwrap_or(4);
+    let worker_count = default_worker_count();
 
     log_debug(&format!(
         "Fetching status for {} repos using {} workers",
@@ -154,99 +652,158 @@ fn fetch_status_parallel(
         worker_count
     ));
 
-    // Use scoped threads to avoid 'static lifetime requirements
-    thread::scope(|scope| {
-        // Create work queue channel
-        let (work_tx, work_rx) = channel();
-        let work_rx = Arc::new(Mutex::new(work_rx));
+    let cached: &StatusCache = cache;
+    let (results, channel_closed) = run_parallel(
+        repos,
+        worker_count,
+        |repo| {
+            let (mut state, entry) = read_status(&repo, cached, extras);
+            // The recorded commit lives in the superproject, so it is never cached
+            state.submodule = repo
+                .superproject
+                .as_deref()
+                .map(|superproject| Submodule::read(superproject, &repo.path));
+            state.worktree_of = repo.main_worktree.clone();
+            (state, entry)
+        },
+        |count| {
+            let ratio = progress_start + count as f64 / total_repos as f64 * progress_weight;
+            evt_tx.send(WorkerEvent::ScanProgress { ratio }).is_ok()
+        },
+    );
 
-        // Send all work items to the queue
-        for (idx, repo) in repos.into_iter().enumerate() {
-            let _ = work_tx.send((idx, repo));
+    let mut states = Vec::with_capacity(results.len());
+    for (state, entry) in results {
+        if let Some(entry) = entry {
+            cache.insert(entry);
         }
-        drop(work_tx); // Close the channel after sending all work
-
-        // Spawn worker threads
-        let mut handles = Vec::new();
-        for _ in 0..worker_count {
-            let work_rx = Arc::clone(&work_rx);
-            let states = Arc::clone(&states);
-            let completed = Arc::clone(&completed);
-            let stop = Arc::clone(&stop);
-            let evt_tx = evt_tx.clone();
-
-            let handle = scope.spawn(move || {
-                loop {
-                    if stop.load(Ordering::Relaxed) {
-                        break;
-                    }
-                    // Get next work item
-                    let work_item = {
-                        let rx = work_rx.lock().unwrap();
-                        rx.recv()
-                    };
+        states.push(state);
+    }
+    if let Err(err) = cache.save() {
+        log_debug(&format!("Status cache not saved: {err}"));
+    }
+    (states, channel_closed)
+}
 
-                    let (idx, repo) = match work_item {
-                        Ok(item) => item,
-                        Err(_) => break, // Channel closed, no more work
-                    };
-                    if stop.load(Ordering::Relaxed) {
-                        break;
-                    }
+/// Status of one repository from the cache or the backend, with the cache
+/// entry to store for a fresh read.
+fn read_status(
+    repo: &RepoRef,
+    cached: &StatusCache,
+    extras: StatusExtras,
+) -> (RepoState, Option<CacheEntry>) {
+    let status_start = Instant::now();
+    if let Some(status) = cached.lookup(repo, extras) {
+        log_debug(&format!(
+            "Status cached repo={} elapsed_ms={}",
+            repo.path.display(),
+            status_start.elapsed().as_millis()
+        ));
+        return (status, None);
+    }
+    let stamps = git_dir_stamps(&repo.git_dir);
+    let checked_at = SystemTime::now();
+    match repo_status(repo, extras) {
+        Ok(status) => {
+            log_debug(&format!(
+                "Status OK repo={} elapsed_ms={}",
+                repo.path.display(),
+                status_start.elapsed().as_millis()
+            ));
+            let entry = CacheEntry::new(stamps, checked_at, extras, status.clone());
+            (status, Some(entry))
+        }
+        Err(err) => {
+            log_debug(&format!(
+                "Status ERR repo={} elapsed_ms={} error={}",
+                repo.path.display(),
+                status_start.elapsed().as_millis(),
+                err
+            ));
+            (error_repo_state(repo, &err, extras), None)
+        }
+    }
+}
 
-                    let status_start = Instant::now();
-                    let state = match git_status(&repo.path, &repo.git_dir) {
-                        Ok(status) => {
-                            log_debug(&format!(
-                                "Status OK repo={} elapsed_ms={}",
-                                repo.path.display(),
-                                status_start.elapsed().as_millis()
-                            ));
-                            status
-                        }
-                        Err(err) => {
-                            log_debug(&format!(
-                                "Status ERR repo={} elapsed_ms={} error={}",
-                                repo.path.display(),
-                                status_start.elapsed().as_millis(),
-                                err
-                            ));
-                            error_repo_state(&repo, &err)
-                        }
-                    };
+// Use available parallelism, capped at 16 to avoid overwhelming the system
+pub fn default_worker_count() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get().min(16))
+        .unwrap_or(4)
+}
 
-                    states.lock().unwrap().push((idx, state));
+/// Run `job` over `items` on `worker_count` scoped threads and return the
+/// results in input order. `on_complete` receives the number of finished
+/// items; returning `false` stops the remaining work, which is reported
+/// through the returned flag.
+pub fn run_parallel<T, R, F, P>(
+    items: Vec<T>,
+    worker_count: usize,
+    job: F,
+    on_complete: P,
+) -> (Vec<R>, bool)
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+    P: Fn(usize) -> bool + Sync,
+{
+    use std::sync::mpsc::channel;
+    use std::sync::Mutex;
 
-                    let count = {
-                        let mut c = completed.lock().unwrap();
-                        *c += 1;
-                        *c
-                    };
+    let results = Mutex::new(Vec::with_capacity(items.len()));
+    let completed = Mutex::new(0usize);
+    let stop = AtomicBool::new(false);
 
-                    let ratio = DISCOVERY_PROGRESS_WEIGHT
-                        + count as f64 / total_repos as f64 * STATUS_PROGRESS_WEIGHT;
-                    if evt_tx.send(WorkerEvent::ScanProgress { ratio }).is_err() {
-                        stop.store(true, Ordering::Relaxed);
-                        break;
-                    }
+    // Create work queue channel and send all work items to it
+    let (work_tx, work_rx) = channel();
+    for item in items.into_iter().enumerate() {
+        let _ = work_tx.send(item);
+    }
+    drop(work_tx); // Close the channel after sending all work
+    let work_rx = Mutex::new(work_rx);
+
+    // Use scoped threads to avoid 'static lifetime requirements
+    thread::scope(|scope| {
+        for _ in 0..worker_count.max(1) {
+            scope.spawn(|| loop {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                // Get next work item
+                let work_item = {
+                    let rx = work_rx.lock().unwrap();
+                    rx.recv()
+                };
+                let (idx, item) = match work_item {
+                    Ok(item) => item,
+                    Err(_) => break, // Channel closed, no more work
+                };
+                if stop.load(Ordering::Relaxed) {
+                    break;
                 }
-            });
 
-            handles.push(handle);
-        }
+                let result = job(item);
+                results.lock().unwrap().push((idx, result));
 
-        // Wait for all threads to complete
-        for handle in handles {
-            let _ = handle.join();
+                let count = {
+                    let mut c = completed.lock().unwrap();
+                    *c += 1;
+                    *c
+                };
+                if !on_complete(count) {
+                    stop.store(true, Ordering::Relaxed);
+                    break;
+                }
+            });
         }
     });
 
     // Sort by original index to maintain order
-    let mut results = Arc::try_unwrap(states).unwrap().into_inner().unwrap();
+    let mut results = results.into_inner().unwrap();
     results.sort_by_key(|(idx, _)| *idx);
-    let channel_closed = stop.load(Ordering::Relaxed);
     (
-        results.into_iter().map(|(_, state)| state).collect(),
-        channel_closed,
+        results.into_iter().map(|(_, result)| result).collect(),
+        stop.load(Ordering::Relaxed),
     )
 }
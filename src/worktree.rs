@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+
+use crate::branches::branch_refs;
+use crate::discovery::{resolve_git_dir, RepoRef};
+use crate::git::{run_git, GIT_STATUS_TIMEOUT, GIT_TIMEOUT};
+
+/// One entry of `git worktree list --porcelain`.
+#[derive(Debug, Default, PartialEq)]
+pub struct WorktreeEntry {
+    pub path: PathBuf,
+    /// Checked out branch without `refs/heads/`; `None` when detached.
+    pub branch: Option<String>,
+    pub bare: bool,
+    /// The worktree directory is gone and `git worktree prune` would remove it.
+    pub prunable: bool,
+}
+
+pub fn git_worktree_list(path: &Path) -> Result<Vec<WorktreeEntry>, String> {
+    let output = run_git(
+        path,
+        &["worktree", "list", "--porcelain"],
+        GIT_STATUS_TIMEOUT,
+    )?;
+    Ok(parse_worktree_list(&String::from_utf8_lossy(&output)))
+}
+
+fn parse_worktree_list(output: &str) -> Vec<WorktreeEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<WorktreeEntry> = None;
+    for line in output.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "worktree" => {
+                entries.extend(current.take());
+                current = Some(WorktreeEntry {
+                    path: PathBuf::from(value),
+                    ..WorktreeEntry::default()
+                });
+            }
+            "branch" => {
+                if let Some(entry) = current.as_mut() {
+                    let branch = value.strip_prefix("refs/heads/").unwrap_or(value);
+                    entry.branch = Some(branch.to_string());
+                }
+            }
+            "bare" | "prunable" => {
+                if let Some(entry) = current.as_mut() {
+                    entry.bare |= key == "bare";
+                    entry.prunable |= key == "prunable";
+                }
+            }
+            _ => {}
+        }
+    }
+    entries.extend(current);
+    entries
+}
+
+/// Linked worktrees of the repository at `main` that still exist on disk.
+/// The first entry git lists is the main worktree itself.
+pub fn linked_worktree_refs(main: &Path) -> Result<Vec<RepoRef>, String> {
+    let entries = git_worktree_list(main)?;
+    Ok(entries
+        .into_iter()
+        .skip(1)
+        .filter(|entry| !entry.bare && !entry.prunable)
+        .filter_map(|entry| {
+            let git_dir = resolve_git_dir(&entry.path, &entry.path.join(".git")).ok()?;
+            Some(RepoRef {
+                main_worktree: Some(main.to_path_buf()),
+                ..RepoRef::new(entry.path, git_dir)
+            })
+        })
+        .collect())
+}
+
+/// Directory for a new worktree of `main` on `branch`: a sibling named
+/// after the repository and the branch, e.g. `app-feature-login`. The
+/// `.git` suffix of a bare repository is dropped.
+pub fn worktree_path(main: &Path, branch: &str) -> PathBuf {
+    let name = main
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    let name = name.strip_suffix(".git").unwrap_or(&name);
+    main.with_file_name(format!("{name}-{}", branch.replace('/', "-")))
+}
+
+/// Check out `branch` in a new worktree next to `main`. A branch that does
+/// not exist locally or on a remote is created from HEAD.
+pub fn git_worktree_add(main: &Path, branch: &str) -> Result<String, String> {
+    let target = worktree_path(main, branch);
+    if target.exists() {
+        return Err(format!("{} already exists", target.display()));
+    }
+    let target_arg = target.to_string_lossy();
+    if branch_exists(main, branch)? {
+        run_git(main, &["worktree", "add", &target_arg, branch], GIT_TIMEOUT)?;
+    } else {
+        run_git(
+            main,
+            &["worktree", "add", "-b", branch, &target_arg],
+            GIT_TIMEOUT,
+        )?;
+    }
+    Ok(format!("{branch} checked out in {}", target.display()))
+}
+
+/// Whether `branch` exists locally or as a remote-tracking branch, which
+/// `git worktree add` checks out with tracking set up.
+fn branch_exists(path: &Path, branch: &str) -> Result<bool, String> {
+    Ok(!branch_refs(path, branch)?.is_empty())
+}
+
+/// Remove the administrative files of worktrees whose directory is gone.
+pub fn git_worktree_prune(path: &Path) -> Result<String, String> {
+    let prunable = git_worktree_list(path)?
+        .iter()
+        .filter(|entry| entry.prunable)
+        .count();
+    run_git(path, &["worktree", "prune"], GIT_TIMEOUT)?;
+    Ok(match prunable {
+        0 => "nothing to prune".to_string(),
+        1 => "pruned 1 worktree".to_string(),
+        count => format!("pruned {count} worktrees"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_support::{git, TempDir};
+
+    #[test]
+    fn test_parse_worktree_list() {
+        let output = "worktree /src/app\nHEAD 1111\nbranch refs/heads/main\n\n\
+                      worktree /src/app-fix\nHEAD 2222\ndetached\n\n\
+                      worktree /src/app-old\nHEAD 3333\nbranch refs/heads/feature/old\nprunable gitdir file points to non-existent location\n";
+        let entries = parse_worktree_list(output);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].branch.as_deref(), Some("main"));
+        assert_eq!(entries[1].branch, None);
+        assert_eq!(entries[2].branch.as_deref(), Some("feature/old"));
+        assert!(entries[2].prunable && !entries[1].prunable);
+    }
+
+    #[test]
+    fn test_worktree_path() {
+        assert_eq!(
+            worktree_path(Path::new("/src/app"), "feature/login"),
+            PathBuf::from("/src/app-feature-login")
+        );
+        assert_eq!(
+            worktree_path(Path::new("/srv/app.git"), "main"),
+            PathBuf::from("/srv/app-main")
+        );
+    }
+
+    #[test]
+    fn test_add_list_and_prune() {
+        let dir = TempDir::new("worktree");
+        let main = dir.join("app");
+        fs::create_dir_all(&main).unwrap();
+        git(&main, &["init", "-q", "-b", "main"]);
+        git(&main, &["commit", "-q", "--allow-empty", "-m", "initial"]);
+        git(&main, &["branch", "existing"]);
+
+        git_worktree_add(&main, "existing").unwrap();
+        git_worktree_add(&main, "feature/new").unwrap();
+        assert!(git_worktree_add(&main, "existing").is_err());
+
+        let worktrees = linked_worktree_refs(&main).unwrap();
+        let paths: Vec<_> = worktrees.iter().map(|repo| repo.path.clone()).collect();
+        assert_eq!(
+            paths,
+            [dir.join("app-existing"), dir.join("app-feature-new")]
+        );
+        assert!(worktrees
+            .iter()
+            .all(|repo| repo.main_worktree.as_deref() == Some(main.as_path())));
+
+        fs::remove_dir_all(dir.join("app-existing")).unwrap();
+        assert_eq!(linked_worktree_refs(&main).unwrap().len(), 1);
+        assert_eq!(git_worktree_prune(&main).unwrap(), "pruned 1 worktree");
+        assert_eq!(git_worktree_prune(&main).unwrap(), "nothing to prune");
+    }
+}
@@ -1,7 +1,10 @@
 use std::fs;
 use std::process::Command;
 
-use git_dash::discovery::discover_repos_with_progress;
+use git_dash::discovery::{
+    discover_repos, discover_repos_with_progress, discover_targets, DiscoveryOptions, NestedRepos,
+    ScanTargets,
+};
 
 #[test]
 fn test_discover_repos_in_temp_dir() {
@@ -154,3 +157,342 @@ fn test_gitdir_file_handling() {
     // Clean up
     let _ = fs::remove_dir_all(&temp_dir);
 }
+
+#[test]
+fn test_parallel_walk_matches_single_thread() {
+    let temp_dir =
+        std::env::temp_dir().join(format!("git-dash-parallel-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+
+    // 3 groups of 10 plain directories, with a repository in every third one
+    let mut expected = Vec::new();
+    for group in 0..3 {
+        for index in 0..10 {
+            let dir = temp_dir
+                .join(format!("group{group}"))
+                .join(format!("dir{index}"));
+            fs::create_dir_all(dir.join("nested")).unwrap();
+            if index % 3 == 0 {
+                Command::new("git")
+                    .args(["init"])
+                    .current_dir(&dir)
+                    .output()
+                    .unwrap();
+                expected.push(dir);
+            }
+        }
+    }
+    expected.sort();
+
+    let mut last_progress = (0, usize::MAX);
+    let options = DiscoveryOptions {
+        threads: Some(4),
+        ..DiscoveryOptions::default()
+    };
+    let parallel = discover_repos(&temp_dir, &options, |visited, remaining| {
+        last_progress = (visited, remaining);
+        true
+    });
+    let single = DiscoveryOptions {
+        threads: Some(1),
+        ..DiscoveryOptions::default()
+    };
+    let single = discover_repos(&temp_dir, &single, |_, _| true);
+
+    let paths: Vec<_> = parallel.iter().map(|repo| repo.path.clone()).collect();
+    assert_eq!(paths, expected);
+    assert_eq!(parallel, single);
+    // Root, 3 groups, 30 directories and the 18 `nested` ones outside repositories
+    assert_eq!(last_progress, (52, 0));
+
+    let mut reports = Vec::new();
+    let stopped = discover_repos(&temp_dir, &options, |visited, remaining| {
+        reports.push((visited, remaining));
+        false
+    });
+    // Stopped at the report before the walk, so no directory was read
+    assert_eq!(reports, [(0, 1)]);
+    assert!(stopped.len() < expected.len());
+    assert!(stopped.iter().all(|repo| expected.contains(&repo.path)));
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_loops_and_duplicates() {
+    use std::os::unix::fs::symlink;
+
+    let temp_dir =
+        std::env::temp_dir().join(format!("git-dash-symlink-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+
+    let repo = temp_dir.join("projects").join("repo");
+    fs::create_dir_all(&repo).unwrap();
+    Command::new("git")
+        .args(["init"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    let linked = temp_dir.join("linked");
+    fs::create_dir_all(&linked).unwrap();
+    // A loop back to the top and a second way to reach the repository
+    symlink(&temp_dir, linked.join("loop")).unwrap();
+    symlink(&repo, linked.join("repo-link")).unwrap();
+
+    let no_follow = DiscoveryOptions {
+        follow_symlinks: false,
+        ..DiscoveryOptions::default()
+    };
+    let skipped = discover_repos(&temp_dir, &no_follow, |_, _| true);
+    let paths: Vec<_> = skipped.iter().map(|repo| repo.path.clone()).collect();
+    assert_eq!(paths, vec![repo.clone()]);
+
+    // Followed by default
+    let follow = DiscoveryOptions::default();
+    // Listed once, through whichever path the walk reached first
+    let followed = discover_repos(&temp_dir, &follow, |_, _| true);
+    assert_eq!(followed.len(), 1);
+    assert_eq!(
+        fs::canonicalize(&followed[0].path).unwrap(),
+        fs::canonicalize(&repo).unwrap()
+    );
+
+    // From inside the loop, the link is the first way to the repository
+    let from_link = discover_repos(&linked, &follow, |_, _| true);
+    let paths: Vec<_> = from_link.iter().map(|repo| repo.path.clone()).collect();
+    assert_eq!(paths, vec![linked.join("repo-link")]);
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn test_nested_and_submodule_modes() {
+    let temp_dir = std::env::temp_dir().join(format!("git-dash-modes-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+
+    let git = |dir: &std::path::Path, args: &[&str]| {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@test.com"])
+            .args(["-c", "protocol.file.allow=always"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+
+    // An outer repository with a submodule and an unrelated nested repository
+    let library = temp_dir.join("library");
+    let outer = temp_dir.join("outer");
+    let inner = outer.join("tools").join("inner");
+    fs::create_dir_all(&library).unwrap();
+    fs::create_dir_all(&inner).unwrap();
+    git(&library, &["init"]);
+    git(&library, &["commit", "--allow-empty", "-m", "initial"]);
+    git(&outer, &["init"]);
+    git(
+        &outer,
+        &["submodule", "add", library.to_str().unwrap(), "lib"],
+    );
+    git(&outer, &["commit", "-m", "add lib"]);
+    git(&inner, &["init"]);
+    let submodule = outer.join("lib");
+
+    let paths = |options: &DiscoveryOptions| -> Vec<_> {
+        discover_repos(&outer, options, |_, _| true)
+            .into_iter()
+            .map(|repo| (repo.path, repo.superproject))
+            .collect()
+    };
+
+    assert_eq!(
+        paths(&DiscoveryOptions::default()),
+        vec![(outer.clone(), None)]
+    );
+
+    let all = DiscoveryOptions {
+        nested: NestedRepos::All,
+        ..DiscoveryOptions::default()
+    };
+    assert_eq!(
+        paths(&all),
+        vec![
+            (outer.clone(), None),
+            (submodule.clone(), None),
+            (inner.clone(), None)
+        ]
+    );
+
+    let submodules = DiscoveryOptions {
+        nested: NestedRepos::Submodules,
+        ..DiscoveryOptions::default()
+    };
+    assert_eq!(
+        paths(&submodules),
+        vec![(outer.clone(), None), (submodule, Some(outer.clone()))]
+    );
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn test_linked_worktrees_grouped_with_main() {
+    let temp_dir =
+        std::env::temp_dir().join(format!("git-dash-worktrees-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+
+    let root = temp_dir.join("root");
+    let main_repo = root.join("main");
+    let inside = root.join("inside");
+    let outside = temp_dir.join("elsewhere").join("outside");
+    fs::create_dir_all(&main_repo).unwrap();
+    fs::create_dir_all(outside.parent().unwrap()).unwrap();
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@test.com"])
+            .args(args)
+            .current_dir(&main_repo)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+    };
+    git(&["init"]);
+    git(&["commit", "--allow-empty", "-m", "initial"]);
+    git(&["worktree", "add", "-b", "inside", inside.to_str().unwrap()]);
+    git(&[
+        "worktree",
+        "add",
+        "-b",
+        "outside",
+        outside.to_str().unwrap(),
+    ]);
+
+    let repos = discover_repos(&root, &DiscoveryOptions::default(), |_, _| true);
+    let found: Vec<_> = repos
+        .iter()
+        .map(|repo| (repo.path.clone(), repo.main_worktree.clone()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (outside.clone(), Some(main_repo.clone())),
+            (inside, Some(main_repo.clone())),
+            (main_repo.clone(), None),
+        ]
+    );
+
+    // Git 2.48 can record the worktree relative to its admin directory
+    fs::write(
+        main_repo.join(".git/worktrees/outside/gitdir"),
+        "../../../../../elsewhere/outside/.git\n",
+    )
+    .unwrap();
+    let relative = discover_repos(&root, &DiscoveryOptions::default(), |_, _| true);
+    let outside = fs::canonicalize(&outside).unwrap();
+    assert_eq!(relative.len(), 3);
+    assert!(relative
+        .iter()
+        .any(|repo| repo.path == outside && repo.main_worktree == Some(main_repo.clone())));
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn test_bare_repos_discovered() {
+    let temp_dir = std::env::temp_dir().join(format!("git-dash-bare-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+
+    let work = temp_dir.join("work");
+    let mirror = temp_dir.join("mirrors").join("app.git");
+    fs::create_dir_all(&work).unwrap();
+    fs::create_dir_all(&mirror).unwrap();
+    for (dir, args) in [(&work, &["init"][..]), (&mirror, &["init", "--bare"][..])] {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+    }
+    // Looks like a repository's insides but is missing `objects`
+    let lookalike = temp_dir.join("lookalike");
+    fs::create_dir_all(lookalike.join("refs")).unwrap();
+    fs::write(lookalike.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+
+    for nested in [NestedRepos::Skip, NestedRepos::All] {
+        let options = DiscoveryOptions {
+            nested,
+            ..DiscoveryOptions::default()
+        };
+        let repos = discover_repos(&temp_dir, &options, |_, _| true);
+        let found: Vec<_> = repos
+            .iter()
+            .map(|repo| (repo.path.clone(), repo.is_bare()))
+            .collect();
+        // The work repository's own `.git` is never taken for a bare repository
+        assert_eq!(found, vec![(mirror.clone(), true), (work.clone(), false)]);
+        assert_eq!(repos[0].git_dir, mirror);
+    }
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[test]
+fn test_multiple_roots_and_listed_repos() {
+    let temp_dir =
+        std::env::temp_dir().join(format!("git-dash-targets-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+
+    let work = temp_dir.join("work");
+    let oss = temp_dir.join("oss");
+    let api = work.join("api");
+    let tool = oss.join("tool");
+    let notes = temp_dir.join("notes");
+    for repo in [&api, &tool, &notes] {
+        fs::create_dir_all(repo).unwrap();
+        Command::new("git")
+            .args(["init"])
+            .current_dir(repo)
+            .output()
+            .unwrap();
+    }
+
+    // `work/api` is both below a root and a root itself, `tool` also listed
+    let targets = ScanTargets {
+        roots: vec![work.clone(), oss.clone(), api.clone()],
+        repos: vec![notes.clone(), tool.clone(), temp_dir.join("missing")],
+    };
+    let mut roots_walked = Vec::new();
+    let (per_root, repos) =
+        discover_targets(&targets, &DiscoveryOptions::default(), |index, _, _| {
+            if !roots_walked.contains(&index) {
+                roots_walked.push(index);
+            }
+            true
+        });
+    assert_eq!(roots_walked, [0, 1, 2]);
+    assert_eq!(per_root.len(), 3);
+    assert_eq!(per_root[1][0].path, tool);
+    let paths: Vec<_> = repos.iter().map(|repo| repo.path.clone()).collect();
+    assert_eq!(paths, [notes.clone(), tool.clone(), api.clone()]);
+
+    assert_eq!(targets.root_of(&api), Some(api.as_path()));
+    assert_eq!(targets.root_of(&tool), Some(oss.as_path()));
+    assert_eq!(targets.root_of(&notes), None);
+    assert!(targets.contains(&notes) && !targets.contains(&temp_dir));
+
+    // Stopping skips the remaining roots
+    let (per_root, _) = discover_targets(&targets, &DiscoveryOptions::default(), |_, _, _| false);
+    assert_eq!(per_root.len(), 1);
+
+    let (resolved, missing) = ScanTargets {
+        roots: vec![work.join("."), work.clone()],
+        repos: vec![temp_dir.join("missing")],
+    }
+    .resolve();
+    assert_eq!(resolved.roots, [fs::canonicalize(&work).unwrap()]);
+    assert!(resolved.repos.is_empty());
+    assert_eq!(missing, [temp_dir.join("missing")]);
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}